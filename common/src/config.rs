@@ -10,6 +10,23 @@ pub enum LevelCompactionStrategy {
     NonOverlap,
 }
 
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompactionPickerStrategy {
+    /// Picks candidates within an `Overlap` level the existing way: a small random sample
+    /// expanded to its overlapping neighbors. Suits the default, read-amplification-sensitive
+    /// workload.
+    Leveled,
+    /// Picks the largest same-size tier of sstable runs within an `Overlap` level, which suits
+    /// write-heavy workloads where merging similarly-sized runs keeps write amplification low.
+    Tiered,
+}
+
+impl Default for CompactionPickerStrategy {
+    fn default() -> Self {
+        Self::Leveled
+    }
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct LevelOptions {
     pub compaction_strategy: LevelCompactionStrategy,
@@ -30,6 +47,16 @@ pub struct LsmTreeConfig {
     pub bloom_false_positive: f64,
     pub compaction_pin_ttl: String,
     pub levels_options: Vec<LevelOptions>,
+    /// How `Overlap` levels pick which sstables to compact together. Defaults to `Leveled` so
+    /// existing configs without this field keep their current behavior.
+    #[serde(default)]
+    pub compaction_picker_strategy: CompactionPickerStrategy,
+    /// If set, a compaction's partition points only split the output once the in-progress block
+    /// has just been finished, trading a little partitioning precision for never flushing a tiny
+    /// partially-filled trailing block. Defaults to `false` so existing configs without this
+    /// field keep their current behavior.
+    #[serde(default)]
+    pub align_partition_to_block_boundary: bool,
 }
 
 impl FromStr for LsmTreeConfig {
@@ -52,6 +79,11 @@ pub struct MinioConfig {
     pub url: String,
 }
 
+#[derive(Deserialize, Clone, Debug)]
+pub struct FsConfig {
+    pub root: String,
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct CacheConfig {
     pub block_cache_capacity: String,
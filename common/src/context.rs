@@ -1,9 +1,22 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use crate::coding::BytesSerde;
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct Context {
     pub span_id: u64,
     pub request_id: u64,
+    /// Unix timestamp (ms) of when this request was proposed, used to measure end-to-end
+    /// propose-to-commit latency. `0` means unset.
+    pub propose_at: u64,
 }
 
 impl<'de> BytesSerde<'de> for Context {}
+
+/// Current unix timestamp in milliseconds, for stamping [`Context::propose_at`].
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -0,0 +1,122 @@
+use runkv_storage::components::SstableStoreRef;
+
+use crate::error::{err, Result};
+
+/// Progress marker for a resumable compaction job, persisted to the object store underlying
+/// [`SstableStoreRef`] so a restarted exhauster can skip output sstables it already uploaded.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CompactionCheckpoint {
+    /// The last user key whose output has been fully flushed to an uploaded sstable. A resumed
+    /// job seeks its input iterator past this key before continuing.
+    pub last_user_key: Vec<u8>,
+    /// Ids of output sstables already uploaded for this job, in emission order.
+    pub completed_sst_ids: Vec<u64>,
+}
+
+fn checkpoint_path(job_id: u64) -> String {
+    format!("checkpoint/{}.ckpt", job_id)
+}
+
+/// Loads the checkpoint for `job_id`, if any. `job_id == 0` means checkpointing is disabled for
+/// this request and always resolves to `None`.
+pub async fn load_checkpoint(
+    sstable_store: &SstableStoreRef,
+    job_id: u64,
+) -> Result<Option<CompactionCheckpoint>> {
+    if job_id == 0 {
+        return Ok(None);
+    }
+    match sstable_store.store().get(&checkpoint_path(job_id)).await? {
+        Some(bytes) => Ok(Some(bincode::deserialize(&bytes).map_err(err)?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn save_checkpoint(
+    sstable_store: &SstableStoreRef,
+    job_id: u64,
+    checkpoint: &CompactionCheckpoint,
+) -> Result<()> {
+    if job_id == 0 {
+        return Ok(());
+    }
+    let bytes = bincode::serialize(checkpoint).map_err(err)?;
+    sstable_store
+        .store()
+        .put(&checkpoint_path(job_id), bytes)
+        .await?;
+    Ok(())
+}
+
+/// Removes the checkpoint for a completed job. Missing is not an error: the job may have
+/// finished in a single step without ever checkpointing.
+pub async fn clear_checkpoint(sstable_store: &SstableStoreRef, job_id: u64) -> Result<()> {
+    if job_id == 0 {
+        return Ok(());
+    }
+    let _ = sstable_store.store().remove(&checkpoint_path(job_id)).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use runkv_storage::components::{BlockCache, LsmTreeMetrics, SstableStore, SstableStoreOptions};
+    use runkv_storage::MemObjectStore;
+    use test_log::test;
+
+    use super::*;
+
+    fn build_sstable_store_for_test() -> SstableStoreRef {
+        let object_store = Arc::new(MemObjectStore::default());
+        let block_cache = BlockCache::new(65536, Arc::new(LsmTreeMetrics::new(0)));
+        Arc::new(SstableStore::new(SstableStoreOptions {
+            path: "test".to_string(),
+            object_store,
+            block_cache,
+            meta_cache_capacity: 1024,
+            enable_content_dedup: false,
+        }))
+    }
+
+    #[test(tokio::test)]
+    async fn test_checkpoint_disabled_for_job_id_zero() {
+        let sstable_store = build_sstable_store_for_test();
+        let checkpoint = CompactionCheckpoint {
+            last_user_key: b"k05".to_vec(),
+            completed_sst_ids: vec![1, 2],
+        };
+        save_checkpoint(&sstable_store, 0, &checkpoint).await.unwrap();
+        assert!(load_checkpoint(&sstable_store, 0).await.unwrap().is_none());
+    }
+
+    #[test(tokio::test)]
+    async fn test_checkpoint_roundtrip_and_clear() {
+        let sstable_store = build_sstable_store_for_test();
+        let job_id = 42;
+        assert!(load_checkpoint(&sstable_store, job_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let checkpoint = CompactionCheckpoint {
+            last_user_key: b"k05".to_vec(),
+            completed_sst_ids: vec![1, 2],
+        };
+        save_checkpoint(&sstable_store, job_id, &checkpoint)
+            .await
+            .unwrap();
+        let loaded = load_checkpoint(&sstable_store, job_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(checkpoint, loaded);
+
+        clear_checkpoint(&sstable_store, job_id).await.unwrap();
+        assert!(load_checkpoint(&sstable_store, job_id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -8,7 +8,7 @@ pub trait CompactionFilter {
 pub struct DefaultCompactionFilter {
     last_key: Bytes,
     watermark: u64,
-    _remove_tombstone: bool,
+    remove_tombstone: bool,
 }
 
 impl DefaultCompactionFilter {
@@ -16,23 +16,62 @@ impl DefaultCompactionFilter {
         Self {
             last_key: Bytes::default(),
             watermark,
-            _remove_tombstone: remove_tombstone,
+            remove_tombstone,
         }
     }
 }
 
 impl CompactionFilter for DefaultCompactionFilter {
-    fn filter(&mut self, key: &[u8], _value: Option<&[u8]>, sequence: u64) -> bool {
+    fn filter(&mut self, key: &[u8], value: Option<&[u8]>, sequence: u64) -> bool {
         let mut retain = true;
-        // TODO: Handle `remove_tombstone`.
         if key == self.last_key && sequence < self.watermark {
             retain = false;
+        } else if self.remove_tombstone && value.is_none() && sequence < self.watermark {
+            // `key` is new (its newest remaining version), it's a tombstone, and it's behind the
+            // watermark, so no snapshot can still be reading the value it shadowed. Dropping it
+            // here means `key` leaves this sstable's bloom filter entirely instead of lingering
+            // just to tell future point lookups "not found, keep looking".
+            retain = false;
         }
         self.last_key = Bytes::copy_from_slice(key);
         retain
     }
 }
 
+/// Retains, per user key, only the `keep` newest versions, regardless of the watermark. Useful
+/// for audit/history use cases where older versions should eventually be dropped but via a
+/// version-count budget rather than [`DefaultCompactionFilter`]'s watermark cutoff.
+///
+/// Relies on the compaction loop visiting a user key's versions consecutively in descending
+/// sequence order (newest first), same as [`DefaultCompactionFilter`] relies on for its
+/// same-key-as-last-key check.
+pub struct VersionRetentionFilter {
+    last_key: Bytes,
+    keep: usize,
+    version_count: usize,
+}
+
+impl VersionRetentionFilter {
+    pub fn new(keep: usize) -> Self {
+        Self {
+            last_key: Bytes::default(),
+            keep,
+            version_count: 0,
+        }
+    }
+}
+
+impl CompactionFilter for VersionRetentionFilter {
+    fn filter(&mut self, key: &[u8], _value: Option<&[u8]>, _sequence: u64) -> bool {
+        if key != self.last_key {
+            self.last_key = Bytes::copy_from_slice(key);
+            self.version_count = 0;
+        }
+        self.version_count += 1;
+        self.version_count <= self.keep
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -62,4 +101,39 @@ mod tests {
             assert_eq!(filter.filter(data.0, data.1, data.2), data.3)
         }
     }
+
+    #[test]
+    fn test_compaction_filter_removes_tombstone_behind_watermark() {
+        #[allow(clippy::type_complexity)]
+        let dataset: Vec<(&[u8], Option<&[u8]>, u64, bool)> = vec![
+            // Tombstone behind the watermark and no older version underneath it: drop.
+            (b"k1", None, 1, false),
+            // Tombstone is the newest version but still at/after the watermark: keep, a
+            // snapshot at or after the watermark may still need to observe the deletion.
+            (b"k2", None, 10, true),
+            (b"k3", Some(b"v3-8"), 8, true),
+            // Superseded older version of k3, dropped regardless of `remove_tombstone`.
+            (b"k3", None, 1, false),
+        ];
+        let mut filter = DefaultCompactionFilter::new(10, true);
+        for data in dataset {
+            assert_eq!(filter.filter(data.0, data.1, data.2), data.3)
+        }
+    }
+
+    #[test]
+    fn test_version_retention_filter() {
+        #[allow(clippy::type_complexity)]
+        let dataset: Vec<(&[u8], Option<&[u8]>, u64, bool)> = vec![
+            (b"k1", Some(b"v1-50"), 50, true),
+            (b"k1", Some(b"v1-40"), 40, true),
+            (b"k1", Some(b"v1-30"), 30, false),
+            (b"k1", Some(b"v1-20"), 20, false),
+            (b"k1", Some(b"v1-10"), 10, false),
+        ];
+        let mut filter = VersionRetentionFilter::new(2);
+        for data in dataset {
+            assert_eq!(filter.filter(data.0, data.1, data.2), data.3)
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use runkv_common::config::{CacheConfig, MinioConfig, Node, S3Config};
+use runkv_common::config::{CacheConfig, FsConfig, MinioConfig, Node, PrometheusConfig, S3Config};
 use serde::Deserialize;
 
 #[derive(Deserialize, Clone, Debug)]
@@ -12,5 +12,16 @@ pub struct ExhausterConfig {
     pub rudder: Node,
     pub s3: Option<S3Config>,
     pub minio: Option<MinioConfig>,
+    pub fs: Option<FsConfig>,
     pub cache: CacheConfig,
+    pub prometheus: PrometheusConfig,
+    /// Forwarded to `ExhausterOptions::max_concurrent_compaction_jobs`: the max number of
+    /// compaction RPCs this exhauster runs at once. Requests past the limit are rejected with
+    /// `Status::resource_exhausted` so the rudder reschedules them elsewhere.
+    #[serde(default = "default_max_concurrent_compaction_jobs")]
+    pub max_concurrent_compaction_jobs: usize,
+}
+
+fn default_max_concurrent_compaction_jobs() -> usize {
+    crate::service::DEFAULT_MAX_CONCURRENT_COMPACTION_JOBS
 }
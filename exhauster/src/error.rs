@@ -7,3 +7,7 @@ pub fn err(e: impl Into<Box<dyn std::error::Error>>) -> anyhow::Error {
 pub fn config_err(e: impl Into<Box<dyn std::error::Error>>) -> anyhow::Error {
     anyhow::anyhow!("config error: {}", e.into())
 }
+
+pub fn validation_err(e: impl Into<Box<dyn std::error::Error>>) -> anyhow::Error {
+    anyhow::anyhow!("validation error: {}", e.into())
+}
@@ -1,6 +1,9 @@
+pub mod checkpoint;
 pub mod compaction_filter;
 pub mod config;
 pub mod error;
+pub mod key_rewriter;
+pub mod metrics;
 pub mod partitioner;
 pub mod service;
 pub mod worker;
@@ -10,7 +13,9 @@ use std::sync::Arc;
 use bytesize::ByteSize;
 use config::ExhausterConfig;
 use error::{config_err, err, Result};
+use metrics::ExhausterMetrics;
 use runkv_common::channel_pool::ChannelPool;
+use runkv_common::prometheus::DefaultPrometheusExporter;
 use runkv_common::BoxedWorker;
 use runkv_proto::common::Endpoint as PbEndpoint;
 use runkv_proto::exhauster::exhauster_service_server::ExhausterServiceServer;
@@ -18,9 +23,10 @@ use runkv_storage::components::{
     BlockCache, LsmTreeMetrics, LsmTreeMetricsRef, SstableStore, SstableStoreOptions,
     SstableStoreRef,
 };
-use runkv_storage::{MemObjectStore, ObjectStoreRef, S3ObjectStore};
+use runkv_storage::{FsObjectStore, MemObjectStore, ObjectStoreRef, S3ObjectStore};
 use service::{Exhauster, ExhausterOptions};
 use tonic::transport::Server;
+use tonic_health::server::{health_reporter, Health, HealthServer};
 use tracing::info;
 use worker::heartbeater::{Heartbeater, HeartbeaterOptions};
 
@@ -28,21 +34,37 @@ pub async fn bootstrap_exhauster(
     config: &ExhausterConfig,
     exhauster: Exhauster,
     workers: Vec<BoxedWorker>,
+    health_service: HealthServer<impl Health>,
 ) -> Result<()> {
+    let enable_metrics = match std::env::var("RUNKV_METRICS") {
+        Err(_) => false,
+        Ok(val) => val.parse().unwrap(),
+    };
+
     let addr_str = format!("{}:{}", config.host, config.port);
 
     for mut worker in workers.into_iter() {
         tokio::spawn(async move { worker.run().await });
     }
 
+    if enable_metrics {
+        let addr = format!("{}:{}", config.prometheus.host, config.prometheus.port)
+            .parse()
+            .unwrap();
+        DefaultPrometheusExporter::init(addr);
+    }
+
     Server::builder()
         .add_service(ExhausterServiceServer::new(exhauster))
+        .add_service(health_service)
         .serve(addr_str.parse().map_err(config_err)?)
         .await
         .map_err(err)
 }
 
-pub async fn build_exhauster(config: &ExhausterConfig) -> Result<(Exhauster, Vec<BoxedWorker>)> {
+pub async fn build_exhauster(
+    config: &ExhausterConfig,
+) -> Result<(Exhauster, Vec<BoxedWorker>, HealthServer<impl Health>)> {
     let object_store = build_object_store(config).await;
     build_exhauster_with_object_store(config, object_store).await
 }
@@ -50,7 +72,14 @@ pub async fn build_exhauster(config: &ExhausterConfig) -> Result<(Exhauster, Vec
 pub async fn build_exhauster_with_object_store(
     config: &ExhausterConfig,
     object_store: ObjectStoreRef,
-) -> Result<(Exhauster, Vec<BoxedWorker>)> {
+) -> Result<(Exhauster, Vec<BoxedWorker>, HealthServer<impl Health>)> {
+    let (mut reporter, health_service) = health_reporter();
+    // Not ready to accept compaction until the sstable store is wired up and the sstable id
+    // high-water mark has been recovered below.
+    reporter
+        .set_not_serving::<ExhausterServiceServer<Exhauster>>()
+        .await;
+
     let lsm_tree_metrics = Arc::new(LsmTreeMetrics::new(config.id));
 
     let sstable_store = build_sstable_store(config, object_store, lsm_tree_metrics)?;
@@ -58,8 +87,11 @@ pub async fn build_exhauster_with_object_store(
     let options = ExhausterOptions {
         node_id: config.id,
         sstable_store,
-        // TODO: Restore from persistent store.
+        // Only used as a fallback on first boot; `Exhauster::recover` restores the real
+        // high-water mark from the store when one has been persisted.
         sstable_sequential_id: 1,
+        metrics: Arc::new(ExhausterMetrics::new(config.id)),
+        max_concurrent_compaction_jobs: config.max_concurrent_compaction_jobs,
     };
 
     let channel_pool = build_channel_pool(config);
@@ -79,9 +111,12 @@ pub async fn build_exhauster_with_object_store(
     };
     let heartbeater = Box::new(Heartbeater::new(heartbeater_options));
 
-    let exhauster = Exhauster::new(options);
+    let exhauster = Exhauster::recover(options).await?;
+    reporter
+        .set_serving::<ExhausterServiceServer<Exhauster>>()
+        .await;
 
-    Ok((exhauster, vec![heartbeater]))
+    Ok((exhauster, vec![heartbeater], health_service))
 }
 
 async fn build_object_store(config: &ExhausterConfig) -> ObjectStoreRef {
@@ -91,6 +126,9 @@ async fn build_object_store(config: &ExhausterConfig) -> ObjectStoreRef {
     } else if let Some(c) = &config.minio {
         info!("minio config found, create minio object store");
         Arc::new(S3ObjectStore::new_with_minio(&c.url).await)
+    } else if let Some(c) = &config.fs {
+        info!("fs config found, create fs object store");
+        Arc::new(FsObjectStore::new(c.root.clone()))
     } else {
         info!("no object store config found, create default memory object store");
         Arc::new(MemObjectStore::default())
@@ -113,6 +151,7 @@ fn build_sstable_store(
             .parse::<ByteSize>()
             .map_err(config_err)?
             .0 as usize,
+        enable_content_dedup: false,
     };
     let sstable_store = SstableStore::new(sstable_store_options);
     Ok(Arc::new(sstable_store))
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref COMPACTION_DURATION_HISTOGRAM_VEC: prometheus::HistogramVec =
+        prometheus::register_histogram_vec!(
+            "exhauster_compaction_duration_histogram_vec",
+            "exhauster compaction duration histogram vec",
+            &["node", "level"]
+        )
+        .unwrap();
+    static ref COMPACTION_BYTES_COUNTER_VEC: prometheus::IntCounterVec =
+        prometheus::register_int_counter_vec!(
+            "exhauster_compaction_bytes_counter_vec",
+            "exhauster compaction input/output bytes counter vec",
+            &["direction", "node", "level"]
+        )
+        .unwrap();
+    static ref COMPACTION_SST_COUNT_COUNTER_VEC: prometheus::IntCounterVec =
+        prometheus::register_int_counter_vec!(
+            "exhauster_compaction_sst_count_counter_vec",
+            "exhauster compaction input/output sst count counter vec",
+            &["direction", "node", "level"]
+        )
+        .unwrap();
+    static ref COMPACTION_DROPPED_KEYS_COUNTER_VEC: prometheus::IntCounterVec =
+        prometheus::register_int_counter_vec!(
+            "exhauster_compaction_dropped_keys_counter_vec",
+            "exhauster compaction dropped key counter vec",
+            &["node", "level"]
+        )
+        .unwrap();
+}
+
+pub struct ExhausterMetrics {
+    node: u64,
+}
+
+pub type ExhausterMetricsRef = Arc<ExhausterMetrics>;
+
+impl ExhausterMetrics {
+    pub fn new(node: u64) -> Self {
+        Self { node }
+    }
+
+    /// Wall-clock duration of a compaction request targeting `level`, from receiving the request
+    /// to returning the response. Gives operators write-amplification visibility alongside the
+    /// byte and sst-count counters below.
+    pub fn compaction_duration_histogram(&self, level: u64) -> prometheus::Histogram {
+        COMPACTION_DURATION_HISTOGRAM_VEC
+            .get_metric_with_label_values(&[&self.node.to_string(), &level.to_string()])
+            .unwrap()
+    }
+
+    pub fn compaction_input_bytes_counter(&self, level: u64) -> prometheus::IntCounter {
+        COMPACTION_BYTES_COUNTER_VEC
+            .get_metric_with_label_values(&["input", &self.node.to_string(), &level.to_string()])
+            .unwrap()
+    }
+
+    pub fn compaction_output_bytes_counter(&self, level: u64) -> prometheus::IntCounter {
+        COMPACTION_BYTES_COUNTER_VEC
+            .get_metric_with_label_values(&["output", &self.node.to_string(), &level.to_string()])
+            .unwrap()
+    }
+
+    pub fn compaction_input_sst_count_counter(&self, level: u64) -> prometheus::IntCounter {
+        COMPACTION_SST_COUNT_COUNTER_VEC
+            .get_metric_with_label_values(&["input", &self.node.to_string(), &level.to_string()])
+            .unwrap()
+    }
+
+    pub fn compaction_output_sst_count_counter(&self, level: u64) -> prometheus::IntCounter {
+        COMPACTION_SST_COUNT_COUNTER_VEC
+            .get_metric_with_label_values(&["output", &self.node.to_string(), &level.to_string()])
+            .unwrap()
+    }
+
+    pub fn compaction_dropped_keys_counter(&self, level: u64) -> prometheus::IntCounter {
+        COMPACTION_DROPPED_KEYS_COUNTER_VEC
+            .get_metric_with_label_values(&[&self.node.to_string(), &level.to_string()])
+            .unwrap()
+    }
+}
@@ -1,8 +1,16 @@
 use bytes::Bytes;
 
 pub trait Partitioner: Send + Sync + 'static {
-    /// Finish building current sstable if returns true.
-    fn partition(&mut self, key: &[u8], value: Option<&[u8]>, sequence: u64) -> bool;
+    /// Finish building current sstable if returns true. `at_block_boundary` is true iff the
+    /// sstable builder just finished a block and hasn't started a new one yet, for partitioners
+    /// that only want to split there (see [`BlockAlignedPartitioner`]).
+    fn partition(
+        &mut self,
+        key: &[u8],
+        value: Option<&[u8]>,
+        sequence: u64,
+        at_block_boundary: bool,
+    ) -> bool;
 }
 
 pub type BoxedPartitioner = Box<dyn Partitioner>;
@@ -23,7 +31,13 @@ impl DefaultPartitioner {
 }
 
 impl Partitioner for DefaultPartitioner {
-    fn partition(&mut self, key: &[u8], _value: Option<&[u8]>, _sequence: u64) -> bool {
+    fn partition(
+        &mut self,
+        key: &[u8],
+        _value: Option<&[u8]>,
+        _sequence: u64,
+        _at_block_boundary: bool,
+    ) -> bool {
         if self.offset >= self.partition_points.len() {
             return false;
         }
@@ -39,7 +53,80 @@ impl Partitioner for DefaultPartitioner {
 pub struct NoPartitioner;
 
 impl Partitioner for NoPartitioner {
-    fn partition(&mut self, _key: &[u8], _value: Option<&[u8]>, _sequence: u64) -> bool {
+    fn partition(
+        &mut self,
+        _key: &[u8],
+        _value: Option<&[u8]>,
+        _sequence: u64,
+        _at_block_boundary: bool,
+    ) -> bool {
         false
     }
 }
+
+/// Wraps another partitioner so a point it signals only actually triggers a split once the
+/// sstable builder reaches a block boundary, instead of immediately — trading a little
+/// partitioning precision (the split lands at the first block boundary at or after the point,
+/// not the point itself) for never flushing a tiny partially-filled trailing block.
+pub struct BlockAlignedPartitioner {
+    inner: BoxedPartitioner,
+    /// Set once `inner` signals a split; cleared once that pending split is actually applied at
+    /// the next block boundary.
+    pending: bool,
+}
+
+impl BlockAlignedPartitioner {
+    pub fn new(inner: BoxedPartitioner) -> Self {
+        Self {
+            inner,
+            pending: false,
+        }
+    }
+}
+
+impl Partitioner for BlockAlignedPartitioner {
+    fn partition(
+        &mut self,
+        key: &[u8],
+        value: Option<&[u8]>,
+        sequence: u64,
+        at_block_boundary: bool,
+    ) -> bool {
+        if self.inner.partition(key, value, sequence, at_block_boundary) {
+            self.pending = true;
+        }
+        if self.pending && at_block_boundary {
+            self.pending = false;
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_block_aligned_partitioner_defers_split_to_block_boundary() {
+        let inner = Box::new(DefaultPartitioner::new(vec![Bytes::from_static(b"k03")]));
+        let mut partitioner = BlockAlignedPartitioner::new(inner);
+
+        // The point is crossed, but not at a block boundary: deferred.
+        assert!(!partitioner.partition(b"k03", None, 1, false));
+        // Still not at a boundary: stays deferred rather than firing again or being dropped.
+        assert!(!partitioner.partition(b"k04", None, 1, false));
+        // First block boundary after the point: the deferred split fires exactly once.
+        assert!(partitioner.partition(b"k05", None, 1, true));
+        assert!(!partitioner.partition(b"k06", None, 1, true));
+    }
+
+    #[test]
+    fn test_block_aligned_partitioner_fires_immediately_if_already_at_boundary() {
+        let inner = Box::new(DefaultPartitioner::new(vec![Bytes::from_static(b"k03")]));
+        let mut partitioner = BlockAlignedPartitioner::new(inner);
+        assert!(partitioner.partition(b"k03", None, 1, true));
+    }
+}
@@ -1,10 +1,12 @@
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use async_trait::async_trait;
 use bytes::Bytes;
 use itertools::Itertools;
 use runkv_common::coding::CompressionAlgorithm;
+use runkv_common::context::Context;
 use runkv_proto::exhauster::exhauster_service_server::ExhausterService;
 use runkv_proto::exhauster::{CompactionRequest, CompactionResponse};
 use runkv_proto::manifest::SstableInfo;
@@ -12,48 +14,268 @@ use runkv_storage::components::{
     CachePolicy, Sstable, SstableBuilder, SstableBuilderOptions, SstableStoreRef,
 };
 use runkv_storage::iterator::{BoxedIterator, Iterator, MergeIterator, Seek, SstableIterator};
-use runkv_storage::utils::{sequence, user_key, value};
+use runkv_storage::utils::{
+    full_key, sequence, train_dictionary, user_key, validate_compression_level, value,
+};
+use tokio::sync::Semaphore;
 use tonic::{Request, Response, Status};
 use tracing::{debug, trace};
 
+use crate::checkpoint::{self, CompactionCheckpoint};
 use crate::compaction_filter::{CompactionFilter, DefaultCompactionFilter};
-use crate::error::Result;
-use crate::partitioner::{BoxedPartitioner, DefaultPartitioner, NoPartitioner};
+use crate::error::{err, validation_err, Result};
+use crate::key_rewriter::{
+    BoxedKeyRewriter, NoopKeyRewriter, OrderPreservingKeyRewriter, PrefixKeyRewriter,
+};
+use crate::metrics::ExhausterMetricsRef;
+use crate::partitioner::{
+    BlockAlignedPartitioner, BoxedPartitioner, DefaultPartitioner, NoPartitioner,
+};
+
+/// Path under the sstable store's object store where the durable high-water mark for sstable
+/// sequential id allocation is kept, so a restarted exhauster never reuses an id it already
+/// handed out.
+const SEQUENTIAL_ID_PATH: &str = "meta/sstable_sequential_id";
+
+/// Number of sequential ids reserved per durable write. Handing out ids from an in-memory lease
+/// between writes keeps allocation off the hot path; a crash mid-lease only burns the unused tail
+/// of the batch, it never causes reuse.
+const SEQUENTIAL_ID_LEASE_BATCH: u64 = 1024;
 
 fn internal(e: impl Into<Box<dyn std::error::Error>>) -> Status {
     Status::internal(e.into().to_string())
 }
 
+/// Whether `uk` falls within `key_range`'s `[start, end)` bound. `None` (no restriction set on
+/// the request) always keeps the key.
+fn in_key_range(uk: &[u8], key_range: &Option<(Vec<u8>, Vec<u8>)>) -> bool {
+    match key_range {
+        None => true,
+        Some((start, end)) => uk >= start.as_slice() && uk < end.as_slice(),
+    }
+}
+
+async fn load_sequential_id_high_water_mark(
+    sstable_store: &SstableStoreRef,
+) -> Result<Option<u64>> {
+    match sstable_store.store().get(SEQUENTIAL_ID_PATH).await? {
+        Some(bytes) => Ok(Some(bincode::deserialize(&bytes).map_err(err)?)),
+        None => Ok(None),
+    }
+}
+
+async fn save_sequential_id_high_water_mark(
+    sstable_store: &SstableStoreRef,
+    high_water_mark: u64,
+) -> Result<()> {
+    let bytes = bincode::serialize(&high_water_mark).map_err(err)?;
+    sstable_store.store().put(SEQUENTIAL_ID_PATH, bytes).await?;
+    Ok(())
+}
+
+/// Sanity-checks partition points supplied by the rudder against the key range actually spanned
+/// by the compaction inputs, catching scheduling bugs (e.g. stale or overlapping partitioning)
+/// before any SSTable is built.
+fn validate_partition_points(
+    partition_points: &[Bytes],
+    first_key: &[u8],
+    last_key: &[u8],
+) -> Result<()> {
+    for w in partition_points.windows(2) {
+        if w[0] >= w[1] {
+            return Err(validation_err(format!(
+                "partition points must be strictly increasing, got {:?} before {:?}",
+                w[0], w[1]
+            )));
+        }
+    }
+    for point in partition_points {
+        if point.as_ref() <= first_key || point.as_ref() > last_key {
+            return Err(validation_err(format!(
+                "partition point {:?} does not fall within input key range ({:?}, {:?}]",
+                point, first_key, last_key
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Legal bounds for a compaction request's `block_capacity` (bytes). Below this, per-entry
+/// prefix and restart-point overhead dominates a block's actual payload; above this, a point
+/// lookup has to decode a block far bigger than the single key it's after.
+const MIN_BLOCK_CAPACITY: usize = 256; // 256 B
+const MAX_BLOCK_CAPACITY: usize = 16 * 1024 * 1024; // 16 MiB
+
+/// Resolves a compaction request's `block_capacity`, falling back to
+/// [`runkv_storage::DEFAULT_BLOCK_SIZE`] when `0` (the rudder's "no opinion" sentinel), and
+/// rejecting anything outside `[MIN_BLOCK_CAPACITY, MAX_BLOCK_CAPACITY]` so a misconfigured
+/// request can't make the exhauster build a pathologically tiny or huge SSTable block.
+fn resolve_block_capacity(requested: usize) -> Result<usize> {
+    if requested == 0 {
+        return Ok(runkv_storage::DEFAULT_BLOCK_SIZE);
+    }
+    if !(MIN_BLOCK_CAPACITY..=MAX_BLOCK_CAPACITY).contains(&requested) {
+        return Err(validation_err(format!(
+            "block_capacity {} out of legal range [{}, {}]",
+            requested, MIN_BLOCK_CAPACITY, MAX_BLOCK_CAPACITY
+        )));
+    }
+    Ok(requested)
+}
+
+/// Appends `sst` to `ssts`, returning a validation error if it overlaps the previously emitted
+/// sstable. A real overlap here means the partitioner or filter produced a corrupt split.
+fn push_disjoint_sst(ssts: &mut Vec<Sstable>, sst: Sstable) -> Result<()> {
+    if let Some(prev) = ssts.last() {
+        if prev.is_overlap_with(&sst) {
+            return Err(validation_err(format!(
+                "compaction output sst {} overlaps with preceding sst {}",
+                sst.id(),
+                prev.id()
+            )));
+        }
+    }
+    ssts.push(sst);
+    Ok(())
+}
+
+/// Max number of values sampled when training a compaction output dictionary. Bounds the cost of
+/// the sampling pass regardless of how many keys the compaction actually covers.
+const DICTIONARY_SAMPLE_LIMIT: usize = 4096;
+
+/// Samples up to [`DICTIONARY_SAMPLE_LIMIT`] values from `ssts` for zstd dictionary training.
+/// Runs its own forward pass over a fresh set of iterators so it doesn't disturb the position of
+/// the main compaction iterator, which is built over the same `ssts` and consumed separately.
+async fn sample_values_for_dictionary(
+    sstable_store: &SstableStoreRef,
+    ssts: &[Sstable],
+    cache_policy: CachePolicy,
+) -> Result<Vec<Vec<u8>>> {
+    let iters: Vec<BoxedIterator> = ssts
+        .iter()
+        .map(|sst| {
+            Box::new(SstableIterator::new(
+                sstable_store.clone(),
+                sst.clone(),
+                cache_policy,
+            )) as BoxedIterator
+        })
+        .collect();
+    let mut iter = MergeIterator::new(iters);
+    iter.seek(Seek::First).await.map_err(err)?;
+    let mut samples = Vec::with_capacity(DICTIONARY_SAMPLE_LIMIT);
+    while iter.is_valid() && samples.len() < DICTIONARY_SAMPLE_LIMIT {
+        if let Some(v) = value(iter.value()) {
+            samples.push(v.to_vec());
+        }
+        iter.next().await.map_err(err)?;
+    }
+    Ok(samples)
+}
+
+/// Projects the [`SstableInfo`] a fully populated `builder` would produce, without uploading
+/// anything. Runs the real block/bloom-filter build pipeline (pure CPU, no IO) so the projected
+/// size matches a real run exactly; only the `id` is a placeholder since none is allocated.
+fn project_sst_size(builder: SstableBuilder) -> Result<SstableInfo> {
+    let (meta, _data) = builder.build()?;
+    let min_user_key = user_key(&meta.block_metas.first().unwrap().first_key).to_vec();
+    let max_user_key = user_key(&meta.block_metas.last().unwrap().last_key).to_vec();
+    Ok(SstableInfo {
+        id: 0,
+        data_size: meta.data_size as u64,
+        min_user_key,
+        max_user_key,
+        created_at: meta.created_at,
+        level: meta.level,
+    })
+}
+
+/// Default for [`ExhausterOptions::max_concurrent_compaction_jobs`].
+pub const DEFAULT_MAX_CONCURRENT_COMPACTION_JOBS: usize = 4;
+
 pub struct ExhausterOptions {
     pub node_id: u64,
     pub sstable_store: SstableStoreRef,
     pub sstable_sequential_id: u64,
+    pub metrics: ExhausterMetricsRef,
+    /// Max number of [`Exhauster::compaction`] jobs allowed to run at once. Requests arriving
+    /// once the limit is reached are rejected with `Status::resource_exhausted` rather than
+    /// queued, so the rudder can reschedule them on a less busy exhauster instead of piling up
+    /// unbounded work here.
+    pub max_concurrent_compaction_jobs: usize,
 }
 
 pub struct Exhauster {
     options: ExhausterOptions,
     sstable_store: SstableStoreRef,
     sstable_sequential_id: AtomicU64,
+    sequential_id_high_water_mark: AtomicU64,
+    compaction_job_limiter: Arc<Semaphore>,
 }
 
 impl Exhauster {
     pub fn new(options: ExhausterOptions) -> Self {
+        let seed = options.sstable_sequential_id;
+        let compaction_job_limiter =
+            Arc::new(Semaphore::new(options.max_concurrent_compaction_jobs));
         Self {
             sstable_store: options.sstable_store.clone(),
-            sstable_sequential_id: AtomicU64::new(options.sstable_sequential_id),
+            sstable_sequential_id: AtomicU64::new(seed),
+            sequential_id_high_water_mark: AtomicU64::new(seed),
+            compaction_job_limiter,
             options,
         }
     }
+
+    /// Like [`Self::new`], but first recovers the durable high-water mark for sstable id
+    /// allocation from the store, so a restarted exhauster never reuses an id handed out before
+    /// the crash. Falls back to `options.sstable_sequential_id` on first boot, when no high-water
+    /// mark has ever been persisted.
+    pub async fn recover(options: ExhausterOptions) -> Result<Self> {
+        let recovered = load_sequential_id_high_water_mark(&options.sstable_store)
+            .await?
+            .unwrap_or(options.sstable_sequential_id);
+        let compaction_job_limiter =
+            Arc::new(Semaphore::new(options.max_concurrent_compaction_jobs));
+        Ok(Self {
+            sstable_store: options.sstable_store.clone(),
+            sstable_sequential_id: AtomicU64::new(recovered),
+            sequential_id_high_water_mark: AtomicU64::new(recovered),
+            compaction_job_limiter,
+            options,
+        })
+    }
 }
 
 #[async_trait]
 impl ExhausterService for Exhauster {
+    #[tracing::instrument(level = "trace", skip(self, request), fields(request_id))]
     async fn compaction(
         &self,
         request: Request<CompactionRequest>,
     ) -> core::result::Result<Response<CompactionResponse>, Status> {
+        // Reject rather than queue once `max_concurrent_compaction_jobs` is in flight, so the
+        // rudder notices the backpressure and reschedules elsewhere instead of piling up work
+        // here. Held for the rest of the job; dropped (and the slot freed) when this fn returns.
+        let _permit = self.compaction_job_limiter.try_acquire().map_err(|_| {
+            Status::resource_exhausted(format!(
+                "exhauster {} is already running {} compaction job(s)",
+                self.options.node_id, self.options.max_concurrent_compaction_jobs
+            ))
+        })?;
+
         let req = request.into_inner();
+        let start = Instant::now();
+        let target_level = req.target_level;
+        if cfg!(feature = "tracing") && !req.context.is_empty() {
+            let span = tracing::Span::current();
+            let ctx: Context = bincode::deserialize(&req.context).map_err(internal)?;
+            span.follows_from(tracing::Id::from_u64(ctx.span_id));
+            span.record("request_id", &ctx.request_id);
+        }
+        let cache_policy = CachePolicy::try_from(req.cache_policy as u8).map_err(internal)?;
         let mut old_sst_infos = Vec::with_capacity(req.sst_ids.len());
+        let mut old_ssts = Vec::with_capacity(req.sst_ids.len());
         let mut iters: Vec<BoxedIterator> = Vec::with_capacity(req.sst_ids.len());
         for sst_id in &req.sst_ids {
             let sst = self
@@ -61,47 +283,161 @@ impl ExhausterService for Exhauster {
                 .sstable(*sst_id)
                 .await
                 .map_err(internal)?;
+            self.sstable_store
+                .verify_data_checksum(&sst)
+                .await
+                .map_err(internal)?;
             old_sst_infos.push(SstableInfo {
                 id: *sst_id,
                 data_size: sst.data_size() as u64,
+                min_user_key: user_key(sst.first_key()).to_vec(),
+                max_user_key: user_key(sst.last_key()).to_vec(),
+                created_at: sst.created_at(),
+                level: sst.level(),
             });
-            let iter = SstableIterator::new(self.sstable_store.clone(), sst, CachePolicy::Fill);
+            let iter = SstableIterator::new(self.sstable_store.clone(), sst.clone(), cache_policy);
             iters.push(Box::new(iter));
+            old_ssts.push(sst);
         }
         let mut iter = MergeIterator::new(iters);
+        let compression_algorithm =
+            CompressionAlgorithm::try_from(req.compression_algorithm as u8).map_err(internal)?;
+        let compression_level = req.compression_level;
+        validate_compression_level(compression_algorithm, compression_level).map_err(internal)?;
+        let wants_dictionary =
+            req.dictionary_size > 0 && compression_algorithm == CompressionAlgorithm::Zstd;
+        let dictionary = if wants_dictionary {
+            let samples =
+                sample_values_for_dictionary(&self.sstable_store, &old_ssts, cache_policy)
+                    .await
+                    .map_err(internal)?;
+            let sample_refs = samples.iter().map(|s| &s[..]).collect_vec();
+            train_dictionary(&sample_refs, req.dictionary_size as usize)
+        } else {
+            vec![]
+        };
         let sstable_builder_options = SstableBuilderOptions {
             capacity: req.sstable_capacity as usize,
-            block_capacity: req.block_capacity as usize,
+            block_capacity: resolve_block_capacity(req.block_capacity as usize).map_err(internal)?,
             restart_interval: req.restart_interval as usize,
             bloom_false_positive: req.bloom_false_positive,
-            compression_algorithm: CompressionAlgorithm::try_from(req.compression_algorithm as u8)
-                .map_err(internal)?,
+            compression_algorithm,
+            dictionary,
+            compression_level,
+            level: req.target_level,
+            parallel_bloom_build: false,
+            value_separation_threshold: 0,
+            blob_id: 0,
         };
-        let mut sstable_builder = None;
-        iter.seek(Seek::First).await.map_err(internal)?;
-        let mut sst_id = 0;
-        let mut compaction_filter =
-            DefaultCompactionFilter::new(req.watermark, req.remove_tombstone);
         let partition_points = req
             .partition_points
             .into_iter()
             .map(Bytes::from)
             .collect_vec();
+        let key_range = if req.key_range_start.is_empty() && req.key_range_end.is_empty() {
+            None
+        } else {
+            Some((req.key_range_start, req.key_range_end))
+        };
+
+        let dry_run = req.dry_run;
+        let job_id = req.job_id;
+        tracing::info!(
+            job_id,
+            input_sst_ids = ?old_sst_infos.iter().map(|info| info.id).collect_vec(),
+            target_level,
+            dry_run,
+            "compaction job started",
+        );
+        let checkpoint = if dry_run {
+            None
+        } else {
+            checkpoint::load_checkpoint(&self.sstable_store, job_id)
+                .await
+                .map_err(internal)?
+        };
+
+        iter.seek(Seek::First).await.map_err(internal)?;
+        if !iter.is_valid() {
+            self.record_compaction_metrics(
+                job_id,
+                target_level,
+                start.elapsed(),
+                &old_sst_infos,
+                &[],
+                0,
+            );
+            let rsp = CompactionResponse {
+                old_sst_infos,
+                new_sst_infos: vec![],
+                dropped_key_count: 0,
+            };
+            return Ok(Response::new(rsp));
+        }
+        let first_key = user_key(iter.key()).to_vec();
+        iter.seek(Seek::Last).await.map_err(internal)?;
+        let last_key = user_key(iter.key()).to_vec();
+        validate_partition_points(&partition_points, &first_key, &last_key).map_err(internal)?;
+
+        let mut new_ssts: Vec<Sstable> = Vec::with_capacity(req.sst_ids.len());
+        let mut last_user_key = vec![];
+        match &checkpoint {
+            Some(ckpt) => {
+                for completed_sst_id in &ckpt.completed_sst_ids {
+                    let sst = self
+                        .sstable_store
+                        .sstable(*completed_sst_id)
+                        .await
+                        .map_err(internal)?;
+                    new_ssts.push(sst);
+                }
+                // `!0` sorts after every real sequence of `last_user_key`, so this lands exactly
+                // on the first entry of the next user key, skipping the completed range.
+                iter.seek(Seek::RandomForward(&full_key(&ckpt.last_user_key, 0)))
+                    .await
+                    .map_err(internal)?;
+                last_user_key = ckpt.last_user_key.clone();
+            }
+            None => iter.seek(Seek::First).await.map_err(internal)?,
+        };
+
+        let mut sstable_builder = None;
+        let mut sst_id = 0;
+        let mut compaction_filter =
+            DefaultCompactionFilter::new(req.watermark, req.remove_tombstone);
+        let inner_key_rewriter: BoxedKeyRewriter = if req.key_rewrite_prefix.is_empty() {
+            Box::new(NoopKeyRewriter::default())
+        } else {
+            Box::new(PrefixKeyRewriter::new(req.key_rewrite_prefix.clone()))
+        };
+        let mut key_rewriter = OrderPreservingKeyRewriter::new(inner_key_rewriter);
         let mut partitioner: BoxedPartitioner = if partition_points.is_empty() {
             Box::new(NoPartitioner::default())
         } else {
-            Box::new(DefaultPartitioner::new(partition_points))
+            let default = Box::new(DefaultPartitioner::new(partition_points));
+            if req.align_partition_to_block_boundary {
+                Box::new(BlockAlignedPartitioner::new(default))
+            } else {
+                default
+            }
         };
-        let mut new_sst_infos = Vec::with_capacity(req.sst_ids.len());
-        let mut last_user_key = vec![];
+        let mut new_sst_infos: Vec<SstableInfo> = Vec::with_capacity(req.sst_ids.len());
+        let mut dropped_key_count = 0;
         // Filter key value pairs.
         while iter.is_valid() {
-            let uk = user_key(iter.key());
-            let ts = sequence(iter.key());
-            let v = value(iter.value());
+            let (fk, raw_value) = iter.kv();
+            let uk = user_key(fk);
+            let ts = sequence(fk);
+            let v = value(raw_value);
+
+            if !in_key_range(uk, &key_range) {
+                dropped_key_count += 1;
+                iter.next().await.map_err(internal)?;
+                continue;
+            }
 
             if sstable_builder.is_none() {
-                sst_id = self.gen_sstable_id();
+                sst_id = self.gen_sstable_id().await.map_err(internal)?;
                 sstable_builder = Some(SstableBuilder::new(sstable_builder_options.clone()));
             }
             if !sstable_builder.as_ref().unwrap().is_empty()
@@ -109,63 +445,1110 @@ impl ExhausterService for Exhauster {
                 && uk != last_user_key
                 && (sstable_builder.as_ref().unwrap().approximate_len()
                     >= sstable_builder_options.capacity
-                    || partitioner.partition(uk, v, ts))
+                    || partitioner.partition(
+                        uk,
+                        v,
+                        ts,
+                        sstable_builder.as_ref().unwrap().at_block_boundary(),
+                    ))
             {
                 let builder = sstable_builder.take().unwrap();
-                let sst_info = self
-                    .build_and_upload_sst(sst_id, builder)
-                    .await
-                    .map_err(internal)?;
-                new_sst_infos.push(sst_info);
+                if dry_run {
+                    new_sst_infos.push(project_sst_size(builder).map_err(internal)?);
+                } else {
+                    let sst = self
+                        .build_and_upload_sst(sst_id, builder)
+                        .await
+                        .map_err(internal)?;
+                    push_disjoint_sst(&mut new_ssts, sst).map_err(internal)?;
+                    self.checkpoint(job_id, &last_user_key, &new_ssts)
+                        .await
+                        .map_err(internal)?;
+                }
                 continue;
             }
             let builder = sstable_builder.as_mut().unwrap();
 
             if compaction_filter.filter(uk, v, ts) {
-                builder.add(uk, ts, v).map_err(internal)?;
+                let rewritten_key = key_rewriter.rewrite(uk).map_err(internal)?;
+                builder.add(&rewritten_key, ts, v).map_err(internal)?;
                 last_user_key = uk.to_vec();
+            } else {
+                dropped_key_count += 1;
             }
             iter.next().await.map_err(internal)?;
         }
         if let Some(builder) = sstable_builder.take() {
-            let sst_info = self
-                .build_and_upload_sst(sst_id, builder)
+            if dry_run {
+                if !builder.is_empty() {
+                    new_sst_infos.push(project_sst_size(builder).map_err(internal)?);
+                }
+            } else {
+                let sst = self
+                    .build_and_upload_sst(sst_id, builder)
+                    .await
+                    .map_err(internal)?;
+                push_disjoint_sst(&mut new_ssts, sst).map_err(internal)?;
+                self.checkpoint(job_id, &last_user_key, &new_ssts)
+                    .await
+                    .map_err(internal)?;
+            }
+        }
+        if !dry_run {
+            new_sst_infos = new_ssts
+                .iter()
+                .map(|sst| SstableInfo {
+                    id: sst.id(),
+                    data_size: sst.data_size() as u64,
+                    min_user_key: user_key(sst.first_key()).to_vec(),
+                    max_user_key: user_key(sst.last_key()).to_vec(),
+                    created_at: sst.created_at(),
+                    level: sst.level(),
+                })
+                .collect_vec();
+            // The job finished in full: drop its checkpoint so a future request with the same
+            // `job_id` is treated as a fresh job rather than a resume.
+            checkpoint::clear_checkpoint(&self.sstable_store, job_id)
                 .await
                 .map_err(internal)?;
-            new_sst_infos.push(sst_info);
         }
+        // Sorted by `min_user_key` regardless of the build order above, so a future concurrent
+        // sub-compaction pipeline can't make `new_sst_infos`' order depend on which sub-job
+        // happens to finish first. This is part of the RPC's contract, not an incidental
+        // side-effect of today's sequential build loop.
+        new_sst_infos.sort_by(|a, b| a.min_user_key.cmp(&b.min_user_key));
+        self.record_compaction_metrics(
+            job_id,
+            target_level,
+            start.elapsed(),
+            &old_sst_infos,
+            &new_sst_infos,
+            dropped_key_count,
+        );
         let rsp = CompactionResponse {
             old_sst_infos,
             new_sst_infos,
+            dropped_key_count,
         };
         Ok(Response::new(rsp))
     }
 }
 
 impl Exhauster {
-    fn gen_sstable_id(&self) -> u64 {
-        let sequential_id = self.sstable_sequential_id.fetch_add(1, Ordering::SeqCst);
-        let node_id = self.options.node_id;
-        (node_id << 32) | sequential_id
+    /// Allocates the next sstable id, persisting a new lease's high-water mark before handing out
+    /// any id from it, so the sequential part never repeats across a restart even if the process
+    /// crashes mid-lease. Errors if the sequential part would overflow the 32 bits reserved for it
+    /// in `(node_id << 32) | sequential_id`.
+    async fn gen_sstable_id(&self) -> Result<u64> {
+        loop {
+            let current = self.sstable_sequential_id.load(Ordering::SeqCst);
+            let high_water_mark = self.sequential_id_high_water_mark.load(Ordering::SeqCst);
+            if current < high_water_mark {
+                if self
+                    .sstable_sequential_id
+                    .compare_exchange(current, current + 1, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_err()
+                {
+                    continue;
+                }
+                if current > u32::MAX as u64 {
+                    return Err(validation_err(format!(
+                        "sstable sequential id {} overflows the 32 bits reserved for it in node {}'s sstable ids",
+                        current, self.options.node_id
+                    )));
+                }
+                return Ok((self.options.node_id << 32) | current);
+            }
+            // Lease exhausted: durably reserve the next batch before anyone can hand out ids from
+            // it, then advance the in-memory watermark. On a lost race the loop simply re-reads
+            // the watermark another thread already advanced.
+            let next_high_water_mark = high_water_mark + SEQUENTIAL_ID_LEASE_BATCH;
+            save_sequential_id_high_water_mark(&self.sstable_store, next_high_water_mark)
+                .await?;
+            let _ = self.sequential_id_high_water_mark.compare_exchange(
+                high_water_mark,
+                next_high_water_mark,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            );
+        }
     }
 
-    async fn build_and_upload_sst(
+    /// Records the write-amplification metrics for one `compaction` call: duration, input/output
+    /// byte and sst counts, and dropped keys, all labeled by `target_level`. Also emits a
+    /// structured (field-based, greppable) completion log carrying the same numbers plus the
+    /// actual input/output sst ids. Called on every successful return path of `compaction`,
+    /// including the early-exit with no input keys; a job that fails with an error records
+    /// nothing.
+    fn record_compaction_metrics(
         &self,
-        sst_id: u64,
-        builder: SstableBuilder,
-    ) -> Result<SstableInfo> {
+        job_id: u64,
+        target_level: u64,
+        duration: std::time::Duration,
+        old_sst_infos: &[SstableInfo],
+        new_sst_infos: &[SstableInfo],
+        dropped_key_count: u64,
+    ) {
+        let metrics = &self.options.metrics;
+        let bytes_in: u64 = old_sst_infos.iter().map(|info| info.data_size).sum();
+        let bytes_out: u64 = new_sst_infos.iter().map(|info| info.data_size).sum();
+        metrics
+            .compaction_duration_histogram(target_level)
+            .observe(duration.as_secs_f64());
+        metrics
+            .compaction_input_bytes_counter(target_level)
+            .inc_by(bytes_in);
+        metrics
+            .compaction_output_bytes_counter(target_level)
+            .inc_by(bytes_out);
+        metrics
+            .compaction_input_sst_count_counter(target_level)
+            .inc_by(old_sst_infos.len() as u64);
+        metrics
+            .compaction_output_sst_count_counter(target_level)
+            .inc_by(new_sst_infos.len() as u64);
+        metrics
+            .compaction_dropped_keys_counter(target_level)
+            .inc_by(dropped_key_count);
+        tracing::info!(
+            job_id,
+            input_sst_ids = ?old_sst_infos.iter().map(|info| info.id).collect_vec(),
+            output_sst_ids = ?new_sst_infos.iter().map(|info| info.id).collect_vec(),
+            bytes_in,
+            bytes_out,
+            duration_ms = duration.as_millis() as u64,
+            dropped_key_count,
+            "compaction job completed",
+        );
+    }
+
+    async fn build_and_upload_sst(&self, sst_id: u64, builder: SstableBuilder) -> Result<Sstable> {
         // TODO: Async upload.
         let (meta, data) = builder.build()?;
-        let data_size = meta.data_size as u64;
         let sst = Sstable::new(sst_id, Arc::new(meta));
         trace!("build sst: {:#?}", sst);
         self.sstable_store
             .put(&sst, data, CachePolicy::Fill)
             .await?;
         debug!("sst {} uploaded", sst_id);
-        Ok(SstableInfo {
-            id: sst_id,
-            data_size,
+        Ok(sst)
+    }
+
+    /// Persists compaction progress so a restarted job with the same `job_id` can resume past
+    /// `last_user_key` instead of rebuilding `completed_ssts` from scratch. A no-op if
+    /// checkpointing is disabled (`job_id == 0`).
+    async fn checkpoint(
+        &self,
+        job_id: u64,
+        last_user_key: &[u8],
+        completed_ssts: &[Sstable],
+    ) -> Result<()> {
+        checkpoint::save_checkpoint(
+            &self.sstable_store,
+            job_id,
+            &CompactionCheckpoint {
+                last_user_key: last_user_key.to_vec(),
+                completed_sst_ids: completed_ssts.iter().map(|sst| sst.id()).collect(),
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use runkv_proto::exhauster::exhauster_service_server::ExhausterServiceServer;
+    use test_log::test;
+    use tonic_health::pb::health_check_response::ServingStatus;
+    use tonic_health::pb::health_server::Health as _;
+    use tonic_health::pb::HealthCheckRequest;
+    use tonic_health::server::health_reporter;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_health_reports_not_serving_before_init_and_serving_after() {
+        let (mut reporter, health_service) = health_reporter();
+        let service_name =
+            <ExhausterServiceServer<Exhauster> as tonic::transport::NamedService>::NAME;
+
+        // Before the store is wired up and the sstable id is recovered, the service must not be
+        // advertised as ready.
+        reporter
+            .set_not_serving::<ExhausterServiceServer<Exhauster>>()
+            .await;
+        let status = health_service
+            .check(Request::new(HealthCheckRequest {
+                service: service_name.to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .status;
+        assert_eq!(status, ServingStatus::NotServing as i32);
+
+        // Once init (store connect + id recovery) succeeds, the service is marked ready.
+        reporter
+            .set_serving::<ExhausterServiceServer<Exhauster>>()
+            .await;
+        let status = health_service
+            .check(Request::new(HealthCheckRequest {
+                service: service_name.to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .status;
+        assert_eq!(status, ServingStatus::Serving as i32);
+    }
+
+    #[test]
+    fn test_validate_partition_points_ok() {
+        let points = vec![Bytes::from_static(b"k03"), Bytes::from_static(b"k06")];
+        assert!(validate_partition_points(&points, b"k01", b"k09").is_ok());
+    }
+
+    #[test]
+    fn test_validate_partition_points_out_of_range() {
+        // A partition point below the input key range cannot split anything: bug.
+        let points = vec![Bytes::from_static(b"k00")];
+        let err = validate_partition_points(&points, b"k01", b"k09").unwrap_err();
+        assert!(err.to_string().contains("validation error"));
+
+        // A partition point above the input key range cannot split anything: bug.
+        let points = vec![Bytes::from_static(b"k10")];
+        let err = validate_partition_points(&points, b"k01", b"k09").unwrap_err();
+        assert!(err.to_string().contains("validation error"));
+    }
+
+    #[test]
+    fn test_validate_partition_points_not_increasing() {
+        let points = vec![Bytes::from_static(b"k05"), Bytes::from_static(b"k03")];
+        let err = validate_partition_points(&points, b"k01", b"k09").unwrap_err();
+        assert!(err.to_string().contains("validation error"));
+    }
+
+    #[test]
+    fn test_resolve_block_capacity_falls_back_to_default_on_zero() {
+        assert_eq!(
+            resolve_block_capacity(0).unwrap(),
+            runkv_storage::DEFAULT_BLOCK_SIZE
+        );
+    }
+
+    #[test]
+    fn test_resolve_block_capacity_rejects_oversized_value() {
+        let err = resolve_block_capacity(MAX_BLOCK_CAPACITY + 1).unwrap_err();
+        assert!(err.to_string().contains("validation error"));
+    }
+
+    #[test]
+    fn test_resolve_block_capacity_passes_through_sane_value() {
+        assert_eq!(resolve_block_capacity(128 * 1024).unwrap(), 128 * 1024);
+    }
+
+    #[test]
+    fn test_push_disjoint_sst_detects_overlap() {
+        let a = Sstable::new(
+            1,
+            Arc::new(runkv_storage::components::SstableMeta {
+                block_metas: vec![runkv_storage::components::BlockMeta {
+                    offset: 0,
+                    len: 0,
+                    first_key: b"k01".to_vec(),
+                    last_key: b"k05".to_vec(),
+                }],
+                bloom_filter_bytes: vec![],
+                data_size: 0,
+                dictionary: vec![],
+                data_checksum: 0,
+                compression_algorithm: runkv_common::coding::CompressionAlgorithm::None,
+                created_at: 0,
+                level: 0,
+                meta_size: 0,
+            }),
+        );
+        let b = Sstable::new(
+            2,
+            Arc::new(runkv_storage::components::SstableMeta {
+                block_metas: vec![runkv_storage::components::BlockMeta {
+                    offset: 0,
+                    len: 0,
+                    first_key: b"k03".to_vec(),
+                    last_key: b"k08".to_vec(),
+                }],
+                bloom_filter_bytes: vec![],
+                data_size: 0,
+                dictionary: vec![],
+                data_checksum: 0,
+                compression_algorithm: runkv_common::coding::CompressionAlgorithm::None,
+                created_at: 0,
+                level: 0,
+                meta_size: 0,
+            }),
+        );
+        let mut ssts = vec![];
+        push_disjoint_sst(&mut ssts, a).unwrap();
+        let err = push_disjoint_sst(&mut ssts, b).unwrap_err();
+        assert!(err.to_string().contains("validation error"));
+    }
+
+    #[test]
+    fn test_project_sst_size_reports_min_max_user_key() {
+        let mut builder = SstableBuilder::new(SstableBuilderOptions {
+            capacity: 4096,
+            block_capacity: 4096,
+            restart_interval: runkv_storage::TEST_DEFAULT_RESTART_INTERVAL,
+            bloom_false_positive: 0.1,
+            compression_algorithm: CompressionAlgorithm::None,
+            dictionary: vec![],
+            compression_level: 0,
+            level: 0,
+            parallel_bloom_build: false,
+            value_separation_threshold: 0,
+            blob_id: 0,
+        });
+        for i in 1..=5u64 {
+            builder
+                .add(format!("k{:02}", i).as_bytes(), i, Some(b"v"))
+                .unwrap();
+        }
+
+        let sst_info = project_sst_size(builder).unwrap();
+
+        assert_eq!(sst_info.min_user_key, b"k01");
+        assert_eq!(sst_info.max_user_key, b"k05");
+    }
+
+    #[test(tokio::test)]
+    async fn test_gen_sstable_id_recovers_high_water_mark_after_restart() {
+        let object_store = Arc::new(runkv_storage::MemObjectStore::default());
+        let block_cache = runkv_storage::components::BlockCache::new(
+            65536,
+            Arc::new(runkv_storage::components::LsmTreeMetrics::new(0)),
+        );
+        let sstable_store = Arc::new(runkv_storage::components::SstableStore::new(
+            runkv_storage::components::SstableStoreOptions {
+                path: "test".to_string(),
+                object_store,
+                block_cache,
+                meta_cache_capacity: 1024,
+                enable_content_dedup: false,
+            },
+        ));
+
+        let exhauster = Exhauster::recover(ExhausterOptions {
+            node_id: 0,
+            sstable_store: sstable_store.clone(),
+            sstable_sequential_id: 1,
+            metrics: Arc::new(crate::metrics::ExhausterMetrics::new(0)),
+            max_concurrent_compaction_jobs: DEFAULT_MAX_CONCURRENT_COMPACTION_JOBS,
+        })
+        .await
+        .unwrap();
+        let mut first_run_ids = vec![];
+        for _ in 0..3 {
+            first_run_ids.push(exhauster.gen_sstable_id().await.unwrap());
+        }
+
+        // Simulate a restart: a fresh `Exhauster` backed by the same store must recover a
+        // high-water mark past every id handed out above, even though none of those individual
+        // ids were persisted on their own (only lease batches are).
+        let restarted = Exhauster::recover(ExhausterOptions {
+            node_id: 0,
+            sstable_store,
+            sstable_sequential_id: 1,
+            metrics: Arc::new(crate::metrics::ExhausterMetrics::new(0)),
+            max_concurrent_compaction_jobs: DEFAULT_MAX_CONCURRENT_COMPACTION_JOBS,
+        })
+        .await
+        .unwrap();
+        let mut second_run_ids = vec![];
+        for _ in 0..3 {
+            second_run_ids.push(restarted.gen_sstable_id().await.unwrap());
+        }
+
+        assert!(first_run_ids
+            .iter()
+            .all(|id| !second_run_ids.contains(id)));
+        assert!(second_run_ids.iter().min() > first_run_ids.iter().max());
+    }
+
+    async fn build_exhauster_for_test() -> (Exhauster, u64) {
+        build_exhauster_for_test_with_max_concurrent_compaction_jobs(
+            DEFAULT_MAX_CONCURRENT_COMPACTION_JOBS,
+        )
+        .await
+    }
+
+    async fn build_exhauster_for_test_with_max_concurrent_compaction_jobs(
+        max_concurrent_compaction_jobs: usize,
+    ) -> (Exhauster, u64) {
+        let object_store = Arc::new(runkv_storage::MemObjectStore::default());
+        let block_cache = runkv_storage::components::BlockCache::new(
+            65536,
+            Arc::new(runkv_storage::components::LsmTreeMetrics::new(0)),
+        );
+        let sstable_store = Arc::new(runkv_storage::components::SstableStore::new(
+            runkv_storage::components::SstableStoreOptions {
+                path: "test".to_string(),
+                object_store,
+                block_cache,
+                meta_cache_capacity: 1024,
+                enable_content_dedup: false,
+            },
+        ));
+        let mut builder = SstableBuilder::new(SstableBuilderOptions {
+            capacity: 4096,
+            block_capacity: 4096,
+            restart_interval: runkv_storage::TEST_DEFAULT_RESTART_INTERVAL,
+            bloom_false_positive: 0.1,
+            compression_algorithm: CompressionAlgorithm::Lz4,
+            dictionary: vec![],
+            compression_level: 0,
+            level: 0,
+            parallel_bloom_build: false,
+            value_separation_threshold: 0,
+            blob_id: 0,
+        });
+        for i in 1..=20u64 {
+            builder
+                .add(
+                    format!("k{:02}", i).as_bytes(),
+                    i,
+                    Some(b"value-for-dry-run-test"),
+                )
+                .unwrap();
+        }
+        let (meta, data) = builder.build().unwrap();
+        let sst_id = 1;
+        let sst = Sstable::new(sst_id, Arc::new(meta));
+        sstable_store
+            .put(&sst, data, CachePolicy::Fill)
+            .await
+            .unwrap();
+        let exhauster = Exhauster::new(ExhausterOptions {
+            node_id: 0,
+            sstable_store,
+            sstable_sequential_id: 100,
+            metrics: Arc::new(crate::metrics::ExhausterMetrics::new(0)),
+            max_concurrent_compaction_jobs,
+        });
+        (exhauster, sst_id)
+    }
+
+    /// Like [`build_exhauster_for_test`], but uploads the source sstable with
+    /// [`CachePolicy::NotFill`] (so it starts out cold) and also returns the `SstableStoreRef`,
+    /// so a test can inspect [`SstableStore::stats`] around a compaction call.
+    async fn build_exhauster_for_cache_policy_test() -> (Exhauster, u64, SstableStoreRef) {
+        let object_store = Arc::new(runkv_storage::MemObjectStore::default());
+        let block_cache = runkv_storage::components::BlockCache::new(
+            65536,
+            Arc::new(runkv_storage::components::LsmTreeMetrics::new(0)),
+        );
+        let sstable_store = Arc::new(runkv_storage::components::SstableStore::new(
+            runkv_storage::components::SstableStoreOptions {
+                path: "test".to_string(),
+                object_store,
+                block_cache,
+                meta_cache_capacity: 1024,
+                enable_content_dedup: false,
+            },
+        ));
+        let mut builder = SstableBuilder::new(SstableBuilderOptions {
+            capacity: 4096,
+            block_capacity: 4096,
+            restart_interval: runkv_storage::TEST_DEFAULT_RESTART_INTERVAL,
+            bloom_false_positive: 0.1,
+            compression_algorithm: CompressionAlgorithm::Lz4,
+            dictionary: vec![],
+            compression_level: 0,
+            level: 0,
+            parallel_bloom_build: false,
+            value_separation_threshold: 0,
+            blob_id: 0,
+        });
+        for i in 1..=20u64 {
+            builder
+                .add(
+                    format!("k{:02}", i).as_bytes(),
+                    i,
+                    Some(b"value-for-cache-policy-test"),
+                )
+                .unwrap();
+        }
+        let (meta, data) = builder.build().unwrap();
+        let sst_id = 1;
+        let sst = Sstable::new(sst_id, Arc::new(meta));
+        sstable_store
+            .put(&sst, data, CachePolicy::NotFill)
+            .await
+            .unwrap();
+        let exhauster = Exhauster::new(ExhausterOptions {
+            node_id: 0,
+            sstable_store: sstable_store.clone(),
+            sstable_sequential_id: 100,
+            metrics: Arc::new(crate::metrics::ExhausterMetrics::new(0)),
+            max_concurrent_compaction_jobs: DEFAULT_MAX_CONCURRENT_COMPACTION_JOBS,
+        });
+        (exhauster, sst_id, sstable_store)
+    }
+
+    fn compaction_request(sst_id: u64, dry_run: bool) -> CompactionRequest {
+        CompactionRequest {
+            sst_ids: vec![sst_id],
+            watermark: 0,
+            sstable_capacity: 1024,
+            block_capacity: 256,
+            restart_interval: runkv_storage::TEST_DEFAULT_RESTART_INTERVAL as u64,
+            bloom_false_positive: 0.1,
+            compression_algorithm: CompressionAlgorithm::Lz4 as u64,
+            remove_tombstone: false,
+            partition_points: vec![],
+            dry_run,
+            job_id: 0,
+            dictionary_size: 0,
+            compression_level: 0,
+            key_range_start: vec![],
+            key_range_end: vec![],
+            align_partition_to_block_boundary: false,
+            target_level: 0,
+            context: vec![],
+            key_rewrite_prefix: vec![],
+            cache_policy: 0,
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn test_compaction_rejects_jobs_past_concurrency_limit() {
+        let (exhauster, sst_id) =
+            build_exhauster_for_test_with_max_concurrent_compaction_jobs(1).await;
+
+        // Dry runs still occupy a job slot for their duration, so issuing two at once with a
+        // limit of 1 forces the second to observe the first still holding its permit: `join!`
+        // polls its arguments in order, so the first call's permit is acquired (synchronously,
+        // before its first await) before the second call is polled at all.
+        let (first, second) = tokio::join!(
+            exhauster.compaction(Request::new(compaction_request(sst_id, true))),
+            exhauster.compaction(Request::new(compaction_request(sst_id, true))),
+        );
+        assert!(first.is_ok());
+        let second_err = second.unwrap_err();
+        assert_eq!(second_err.code(), tonic::Code::ResourceExhausted);
+
+        // The permit is released once the first job returns, so a subsequent job is admitted.
+        let third = exhauster
+            .compaction(Request::new(compaction_request(sst_id, true)))
+            .await;
+        assert!(third.is_ok());
+    }
+
+    #[test(tokio::test)]
+    async fn test_compaction_defaults_to_not_fill_cache_policy_for_source_reads() {
+        let (exhauster, sst_id, sstable_store) = build_exhauster_for_cache_policy_test().await;
+
+        // Counters are shared process-wide (labeled by node), so compare deltas rather than
+        // absolute values.
+        let before = sstable_store.stats();
+        let mut req = compaction_request(sst_id, true);
+        req.cache_policy = 0;
+        exhauster.compaction(Request::new(req)).await.unwrap();
+        let after = sstable_store.stats();
+
+        // The source sst's block started out cold (uploaded with `NotFill`); a `NotFill` read
+        // should leave it that way rather than pulling it into the cache.
+        assert_eq!(after.bytes, before.bytes);
+    }
+
+    #[test(tokio::test)]
+    async fn test_compaction_fill_cache_policy_populates_cache_for_source_reads() {
+        let (exhauster, sst_id, sstable_store) = build_exhauster_for_cache_policy_test().await;
+
+        let before = sstable_store.stats();
+        let mut req = compaction_request(sst_id, true);
+        req.cache_policy = u8::from(CachePolicy::Fill) as u32;
+        exhauster.compaction(Request::new(req)).await.unwrap();
+        let after = sstable_store.stats();
+
+        assert!(after.bytes > before.bytes);
+    }
+
+    #[test(tokio::test)]
+    async fn test_dry_run_projection_matches_real_run() {
+        let (exhauster, sst_id) = build_exhauster_for_test().await;
+
+        let dry_run_rsp = exhauster
+            .compaction(Request::new(compaction_request(sst_id, true)))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let (exhauster, sst_id) = build_exhauster_for_test().await;
+        let real_rsp = exhauster
+            .compaction(Request::new(compaction_request(sst_id, false)))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(
+            dry_run_rsp.new_sst_infos.len(),
+            real_rsp.new_sst_infos.len()
+        );
+        let dry_run_size: u64 = dry_run_rsp.new_sst_infos.iter().map(|i| i.data_size).sum();
+        let real_size: u64 = real_rsp.new_sst_infos.iter().map(|i| i.data_size).sum();
+        assert_eq!(dry_run_size, real_size);
+        assert_eq!(dry_run_rsp.dropped_key_count, real_rsp.dropped_key_count);
+    }
+
+    async fn collect_entries(
+        sstable_store: &SstableStoreRef,
+        sst_id: u64,
+    ) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let sst = sstable_store.sstable(sst_id).await.unwrap();
+        let mut it = SstableIterator::new(sstable_store.clone(), sst, CachePolicy::Fill);
+        it.seek(Seek::First).await.unwrap();
+        let mut entries = vec![];
+        while it.is_valid() {
+            let v = value(it.value()).unwrap_or_default().to_vec();
+            entries.push((user_key(it.key()).to_vec(), v));
+            it.next().await.unwrap();
+        }
+        entries
+    }
+
+    #[test(tokio::test)]
+    async fn test_resume_after_checkpoint_matches_clean_run() {
+        let (exhauster, sst_id) = build_exhauster_for_test().await;
+        let clean_rsp = exhauster
+            .compaction(Request::new(compaction_request(sst_id, false)))
+            .await
+            .unwrap()
+            .into_inner();
+        let mut clean_entries = vec![];
+        for info in &clean_rsp.new_sst_infos {
+            clean_entries.extend(collect_entries(&exhauster.sstable_store, info.id).await);
+        }
+        clean_entries.sort();
+
+        // Simulate a prior attempt that already uploaded the first half of the output and
+        // checkpointed past `k10`, then crashed before finishing.
+        let (exhauster, sst_id) = build_exhauster_for_test().await;
+        let mut partial_builder = SstableBuilder::new(SstableBuilderOptions {
+            capacity: 1024,
+            block_capacity: 256,
+            restart_interval: runkv_storage::TEST_DEFAULT_RESTART_INTERVAL,
+            bloom_false_positive: 0.1,
+            compression_algorithm: CompressionAlgorithm::Lz4,
+            dictionary: vec![],
+            compression_level: 0,
+            level: 0,
+            parallel_bloom_build: false,
+            value_separation_threshold: 0,
+            blob_id: 0,
+        });
+        for i in 1..=10u64 {
+            partial_builder
+                .add(
+                    format!("k{:02}", i).as_bytes(),
+                    i,
+                    Some(b"value-for-dry-run-test"),
+                )
+                .unwrap();
+        }
+        let (meta, data) = partial_builder.build().unwrap();
+        let completed_sst_id = 555;
+        let completed_sst = Sstable::new(completed_sst_id, Arc::new(meta));
+        exhauster
+            .sstable_store
+            .put(&completed_sst, data, CachePolicy::Fill)
+            .await
+            .unwrap();
+        let job_id = 7;
+        checkpoint::save_checkpoint(
+            &exhauster.sstable_store,
+            job_id,
+            &CompactionCheckpoint {
+                last_user_key: b"k10".to_vec(),
+                completed_sst_ids: vec![completed_sst_id],
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut req = compaction_request(sst_id, false);
+        req.job_id = job_id;
+        let resumed_rsp = exhauster
+            .compaction(Request::new(req))
+            .await
+            .unwrap()
+            .into_inner();
+
+        // The already-uploaded sst is carried through untouched, and the checkpoint is cleared
+        // once the job completes.
+        assert!(resumed_rsp
+            .new_sst_infos
+            .iter()
+            .any(|info| info.id == completed_sst_id));
+        assert!(checkpoint::load_checkpoint(&exhauster.sstable_store, job_id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let mut resumed_entries = vec![];
+        for info in &resumed_rsp.new_sst_infos {
+            resumed_entries.extend(collect_entries(&exhauster.sstable_store, info.id).await);
+        }
+        resumed_entries.sort();
+        assert_eq!(clean_entries, resumed_entries);
+    }
+
+    #[test(tokio::test)]
+    async fn test_compaction_with_key_range_drops_keys_outside_it() {
+        let (exhauster, sst_id) = build_exhauster_for_test().await;
+
+        let mut req = compaction_request(sst_id, false);
+        req.key_range_start = b"k05".to_vec();
+        req.key_range_end = b"k10".to_vec();
+        let rsp = exhauster
+            .compaction(Request::new(req))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let mut entries = vec![];
+        for info in &rsp.new_sst_infos {
+            entries.extend(collect_entries(&exhauster.sstable_store, info.id).await);
+        }
+        let keys = entries.iter().map(|(k, _)| k.clone()).collect_vec();
+
+        // Only [k05, k10) survives; k01-k04 and k10-k20 are dropped, not copied through.
+        assert_eq!(
+            keys,
+            (5..10)
+                .map(|i| format!("k{:02}", i).into_bytes())
+                .collect_vec()
+        );
+        assert_eq!(rsp.dropped_key_count, 15);
+    }
+
+    #[test(tokio::test)]
+    async fn test_compaction_applies_key_rewrite_prefix() {
+        let (exhauster, sst_id) = build_exhauster_for_test().await;
+
+        let mut req = compaction_request(sst_id, false);
+        req.key_rewrite_prefix = b"tenant-1/".to_vec();
+        let rsp = exhauster
+            .compaction(Request::new(req))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let mut entries = vec![];
+        for info in &rsp.new_sst_infos {
+            entries.extend(collect_entries(&exhauster.sstable_store, info.id).await);
+        }
+        let keys = entries.iter().map(|(k, _)| k.clone()).collect_vec();
+        assert_eq!(
+            keys,
+            (1..=20u64)
+                .map(|i| format!("tenant-1/k{:02}", i).into_bytes())
+                .collect_vec()
+        );
+    }
+
+    async fn last_block_len(sstable_store: &SstableStoreRef, sst_id: u64) -> usize {
+        let sst = sstable_store.sstable(sst_id).await.unwrap();
+        sst.block_metas_iter().last().unwrap().len
+    }
+
+    #[test(tokio::test)]
+    async fn test_align_partition_to_block_boundary_avoids_tiny_trailing_blocks() {
+        // `k05` lands in the middle of the first output block (block_capacity 256 fits more than
+        // four of these small entries), so an unaligned partitioner is forced to cut the first
+        // output sst off mid-block, leaving a tiny trailing block. An aligned one instead defers
+        // the split until the block in progress is actually full.
+        let (aligned_exhauster, sst_id) = build_exhauster_for_test().await;
+        let mut aligned_req = compaction_request(sst_id, false);
+        aligned_req.partition_points = vec![b"k05".to_vec()];
+        aligned_req.align_partition_to_block_boundary = true;
+        let aligned_rsp = aligned_exhauster
+            .compaction(Request::new(aligned_req))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let (unaligned_exhauster, sst_id) = build_exhauster_for_test().await;
+        let mut unaligned_req = compaction_request(sst_id, false);
+        unaligned_req.partition_points = vec![b"k05".to_vec()];
+        let unaligned_rsp = unaligned_exhauster
+            .compaction(Request::new(unaligned_req))
+            .await
+            .unwrap()
+            .into_inner();
+
+        // Both runs must actually split at `k05`, otherwise neither exercises the partitioner.
+        assert!(aligned_rsp.new_sst_infos.len() >= 2);
+        assert!(unaligned_rsp.new_sst_infos.len() >= 2);
+
+        let aligned_last_block_len = last_block_len(
+            &aligned_exhauster.sstable_store,
+            aligned_rsp.new_sst_infos[0].id,
+        )
+        .await;
+        let unaligned_last_block_len = last_block_len(
+            &unaligned_exhauster.sstable_store,
+            unaligned_rsp.new_sst_infos[0].id,
+        )
+        .await;
+
+        // The aligned split only ever happens once a block is already full, so its trailing
+        // block is a full one; the unaligned split truncates whatever block was in progress,
+        // leaving a noticeably smaller trailing block.
+        assert!(aligned_last_block_len > unaligned_last_block_len);
+    }
+
+    #[test(tokio::test)]
+    async fn test_compaction_response_new_sst_infos_sorted_by_min_user_key() {
+        // Partition into several output ssts so there's an order to get wrong.
+        let (exhauster, sst_id) = build_exhauster_for_test().await;
+        let mut req = compaction_request(sst_id, false);
+        req.partition_points = vec![b"k05".to_vec(), b"k10".to_vec(), b"k15".to_vec()];
+        let rsp = exhauster
+            .compaction(Request::new(req))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(rsp.new_sst_infos.len() >= 4);
+        let min_user_keys = rsp
+            .new_sst_infos
+            .iter()
+            .map(|info| info.min_user_key.clone())
+            .collect_vec();
+        let mut sorted_min_user_keys = min_user_keys.clone();
+        sorted_min_user_keys.sort();
+        assert_eq!(min_user_keys, sorted_min_user_keys);
+    }
+
+    #[test(tokio::test)]
+    async fn test_compaction_stamps_output_sst_infos_with_timestamp_and_level() {
+        let (exhauster, sst_id) = build_exhauster_for_test().await;
+        let before = runkv_common::time::timestamp();
+
+        let mut req = compaction_request(sst_id, false);
+        req.target_level = 3;
+        let rsp = exhauster
+            .compaction(Request::new(req))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let after = runkv_common::time::timestamp();
+        assert!(!rsp.new_sst_infos.is_empty());
+        for info in &rsp.new_sst_infos {
+            assert!(info.created_at >= before && info.created_at <= after);
+            assert_eq!(info.level, 3);
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn test_compaction_advances_metrics_counters() {
+        let (exhauster, sst_id) = build_exhauster_for_test().await;
+        // A level not touched by any other test in this module, so the shared lazy_static
+        // counters it reads can't have been bumped by something else.
+        let level = 9;
+
+        let mut req = compaction_request(sst_id, false);
+        req.target_level = level;
+        req.key_range_start = b"k05".to_vec();
+        req.key_range_end = b"k10".to_vec();
+        let rsp = exhauster
+            .compaction(Request::new(req))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let metrics = &exhauster.options.metrics;
+        assert_eq!(
+            metrics.compaction_duration_histogram(level).get_sample_count(),
+            1
+        );
+        assert_eq!(
+            metrics.compaction_input_sst_count_counter(level).get(),
+            rsp.old_sst_infos.len() as u64
+        );
+        assert_eq!(
+            metrics.compaction_output_sst_count_counter(level).get(),
+            rsp.new_sst_infos.len() as u64
+        );
+        assert!(metrics.compaction_input_bytes_counter(level).get() > 0);
+        assert!(metrics.compaction_output_bytes_counter(level).get() > 0);
+        assert_eq!(
+            metrics.compaction_dropped_keys_counter(level).get(),
+            rsp.dropped_key_count
+        );
+    }
+
+    /// Minimal [`tracing::Subscriber`] that records the field values of every emitted event, so a
+    /// test can assert on the structured (field-based) logs emitted by
+    /// [`Exhauster::record_compaction_metrics`] without needing a full tracing backend.
+    struct EventFieldRecorder {
+        next_id: AtomicU64,
+        events: Arc<Mutex<Vec<Vec<(String, String)>>>>,
+    }
+
+    struct FieldCollector<'a>(&'a mut Vec<(String, String)>);
+
+    impl tracing::field::Visit for FieldCollector<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+
+    impl tracing::Subscriber for EventFieldRecorder {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            let mut fields = Vec::new();
+            event.record(&mut FieldCollector(&mut fields));
+            self.events.lock().unwrap().push(fields);
+        }
+
+        fn enter(&self, _span: &tracing::span::Id) {}
+
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    #[test(tokio::test)]
+    async fn test_compaction_emits_structured_completion_log() {
+        let (exhauster, sst_id) = build_exhauster_for_test().await;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = EventFieldRecorder {
+            next_id: AtomicU64::new(0),
+            events: events.clone(),
+        };
+
+        let mut req = compaction_request(sst_id, false);
+        req.job_id = 99;
+
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let rsp = exhauster
+            .compaction(Request::new(req))
+            .await
+            .unwrap()
+            .into_inner();
+        drop(_guard);
+
+        let completion = events
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|fields| {
+                fields.iter().any(|(name, value)| {
+                    name == "message" && value.contains("compaction job completed")
+                })
+            })
+            .cloned()
+            .expect("no structured completion log emitted");
+        let field = |name: &str| {
+            completion
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.clone())
+                .unwrap_or_else(|| panic!("missing field {name}"))
+        };
+
+        assert_eq!(field("job_id"), "99");
+        let bytes_in: u64 = rsp.old_sst_infos.iter().map(|i| i.data_size).sum();
+        let bytes_out: u64 = rsp.new_sst_infos.iter().map(|i| i.data_size).sum();
+        assert_eq!(field("bytes_in"), bytes_in.to_string());
+        assert_eq!(field("bytes_out"), bytes_out.to_string());
+        assert_eq!(field("dropped_key_count"), rsp.dropped_key_count.to_string());
+        let output_sst_ids = field("output_sst_ids");
+        for info in &rsp.new_sst_infos {
+            assert!(output_sst_ids.contains(&info.id.to_string()));
+        }
+    }
+
+    /// Minimal [`tracing::Subscriber`] that only records which span ids were linked together via
+    /// [`tracing::Span::follows_from`], so a test can assert a link was actually established
+    /// without needing a full tracing backend.
+    struct FollowsFromRecorder {
+        next_id: AtomicU64,
+        follows: Arc<Mutex<Vec<(u64, u64)>>>,
+    }
+
+    impl tracing::Subscriber for FollowsFromRecorder {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, span: &tracing::span::Id, follows: &tracing::span::Id) {
+            self.follows
+                .lock()
+                .unwrap()
+                .push((span.into_u64(), follows.into_u64()));
+        }
+
+        fn event(&self, _event: &tracing::Event<'_>) {}
+
+        fn enter(&self, _span: &tracing::span::Id) {}
+
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test(tokio::test)]
+    async fn test_compaction_links_span_to_supplied_context() {
+        let (exhauster, sst_id) = build_exhauster_for_test().await;
+
+        let follows = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = FollowsFromRecorder {
+            next_id: AtomicU64::new(0),
+            follows: follows.clone(),
+        };
+
+        let upstream_span_id = 777u64;
+        let mut req = compaction_request(sst_id, false);
+        req.context = bincode::serialize(&Context {
+            span_id: upstream_span_id,
+            request_id: 42,
+            propose_at: 0,
         })
+        .unwrap();
+
+        let _guard = tracing::subscriber::set_default(subscriber);
+        exhauster
+            .compaction(Request::new(req))
+            .await
+            .unwrap();
+        drop(_guard);
+
+        // `compaction`'s own `#[tracing::instrument]` span must have been told it follows from
+        // the upstream span carried in the request's context.
+        assert!(follows
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(_, from)| *from == upstream_span_id));
     }
 }
@@ -1,4 +1,4 @@
-use runkv_common::config::{CacheConfig, LsmTreeConfig, MinioConfig, S3Config};
+use runkv_common::config::{CacheConfig, FsConfig, LsmTreeConfig, MinioConfig, S3Config};
 use serde::Deserialize;
 
 #[derive(Deserialize, Clone, Debug)]
@@ -11,6 +11,7 @@ pub struct RudderConfig {
     pub health_timeout: String,
     pub s3: Option<S3Config>,
     pub minio: Option<MinioConfig>,
+    pub fs: Option<FsConfig>,
     pub cache: CacheConfig,
     pub lsm_tree: LsmTreeConfig,
 }
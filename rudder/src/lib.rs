@@ -19,7 +19,7 @@ use runkv_storage::components::{
     SstableStoreRef,
 };
 use runkv_storage::manifest::{VersionManager, VersionManagerOptions};
-use runkv_storage::{MemObjectStore, ObjectStoreRef, S3ObjectStore};
+use runkv_storage::{FsObjectStore, MemObjectStore, ObjectStoreRef, S3ObjectStore};
 use service::{Rudder, RudderOptions};
 use tonic::transport::Server;
 use tracing::info;
@@ -88,6 +88,9 @@ async fn build_object_store(config: &RudderConfig) -> ObjectStoreRef {
     } else if let Some(c) = &config.minio {
         info!("minio config found, create minio object store");
         Arc::new(S3ObjectStore::new_with_minio(&c.url).await)
+    } else if let Some(c) = &config.fs {
+        info!("fs config found, create fs object store");
+        Arc::new(FsObjectStore::new(c.root.clone()))
     } else {
         info!("no object store config found, create default memory object store");
         Arc::new(MemObjectStore::default())
@@ -110,6 +113,7 @@ fn build_sstable_store(
             .parse::<ByteSize>()
             .map_err(Error::config_err)?
             .0 as usize,
+        enable_content_dedup: false,
     };
     let sstable_store = SstableStore::new(sstable_store_options);
     Ok(Arc::new(sstable_store))
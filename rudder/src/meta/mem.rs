@@ -24,6 +24,7 @@ pub struct MemoryMetaStoreCore {
     node_ranges: BTreeMap<u64, Vec<KeyRange>>,
     pinned_sstables: BTreeMap<u64, SystemTime>,
     sstable_pin_ttl: Duration,
+    node_watermarks: BTreeMap<u64, u64>,
 }
 
 pub struct MemoryMetaStore {
@@ -77,6 +78,15 @@ impl MetaStore for MemoryMetaStore {
         Ok(())
     }
 
+    async fn update_node_watermark(&self, node_id: u64, watermark: u64) -> Result<()> {
+        self.core.write().node_watermarks.insert(node_id, watermark);
+        Ok(())
+    }
+
+    async fn min_node_watermark(&self) -> Result<Option<u64>> {
+        Ok(self.core.read().node_watermarks.values().min().copied())
+    }
+
     async fn all_node_ranges(&self) -> Result<BTreeMap<u64, Vec<KeyRange>>> {
         let guard = self.core.read();
         let node_ranges = guard.node_ranges.clone();
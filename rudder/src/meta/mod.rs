@@ -22,6 +22,15 @@ pub trait MetaStore: Send + Sync + 'static {
     /// Update responsable key ranges of wheel node.
     async fn update_node_ranges(&self, node_id: u64, ranges: Vec<KeyRange>) -> Result<()>;
 
+    /// Record the min sequence still applied/retained by wheel node `node_id`, as reported in its
+    /// latest heartbeat.
+    async fn update_node_watermark(&self, node_id: u64, watermark: u64) -> Result<()>;
+
+    /// The min watermark reported across all wheel nodes that have ever reported one, i.e. the
+    /// sequence below which no wheel node still needs data. `None` if no wheel node has reported
+    /// yet.
+    async fn min_node_watermark(&self) -> Result<Option<u64>>;
+
     /// Get all responsable key ranges grouped by nodes.
     async fn all_node_ranges(&self) -> Result<BTreeMap<u64, Vec<KeyRange>>>;
 
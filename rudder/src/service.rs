@@ -124,6 +124,22 @@ impl RudderService for Rudder {
         };
         Ok(Response::new(rsp))
     }
+
+    async fn get_lsm_tree_summary(
+        &self,
+        _request: Request<GetLsmTreeSummaryRequest>,
+    ) -> core::result::Result<Response<GetLsmTreeSummaryResponse>, Status> {
+        let mut levels = Vec::with_capacity(self.version_manager.levels().await);
+        for level in 0..self.version_manager.levels().await {
+            levels.push(LevelSummary {
+                level: level as u64,
+                sstable_count: self.version_manager.level_sstable_count(level).await as u64,
+                data_size: self.version_manager.level_data_size(level).await as u64,
+            });
+        }
+        let rsp = GetLsmTreeSummaryResponse { levels };
+        Ok(Response::new(rsp))
+    }
 }
 
 impl Rudder {
@@ -140,6 +156,12 @@ impl Rudder {
         self.meta_store
             .update_node_ranges(node_id, hb.key_ranges)
             .await?;
+        self.meta_store
+            .update_node_watermark(node_id, hb.watermark)
+            .await?;
+        if let Some(watermark) = self.meta_store.min_node_watermark().await? {
+            self.version_manager.advance(watermark).await?;
+        }
         let rsp = heartbeat_response::HeartbeatMessage::WheelHeartbeat(WheelHeartbeatResponse {
             version_diffs,
         });
@@ -165,3 +187,209 @@ impl Rudder {
         Ok(rsp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use runkv_common::coding::CompressionAlgorithm;
+    use runkv_common::config::{LevelCompactionStrategy, LevelOptions};
+    use runkv_storage::components::{
+        BlockCache, BlockMeta, CachePolicy, LsmTreeMetrics, Sstable, SstableMeta, SstableStore,
+        SstableStoreOptions,
+    };
+    use runkv_storage::manifest::VersionManagerOptions;
+    use runkv_storage::MemObjectStore;
+    use test_log::test;
+
+    use super::*;
+    use crate::meta::mem::MemoryMetaStore;
+
+    fn build_sstable_store_for_test() -> SstableStoreRef {
+        Arc::new(SstableStore::new(SstableStoreOptions {
+            path: "test".to_string(),
+            object_store: Arc::new(MemObjectStore::default()),
+            block_cache: BlockCache::new(0, Arc::new(LsmTreeMetrics::new(0))),
+            meta_cache_capacity: 65536,
+            enable_content_dedup: false,
+        }))
+    }
+
+    async fn ingest_meta(
+        version_manager: &VersionManager,
+        sstable_store: &SstableStoreRef,
+        sst_id: u64,
+        level: u64,
+    ) {
+        sstable_store
+            .put(
+                &Sstable::new(
+                    sst_id,
+                    Arc::new(SstableMeta {
+                        block_metas: vec![BlockMeta {
+                            offset: 0,
+                            len: 0,
+                            first_key: vec![sst_id as u8],
+                            last_key: vec![sst_id as u8],
+                        }],
+                        bloom_filter_bytes: vec![],
+                        data_size: 10,
+                        dictionary: vec![],
+                        data_checksum: 0,
+                        compression_algorithm: CompressionAlgorithm::None,
+                        created_at: 0,
+                        level,
+                        meta_size: 0,
+                    }),
+                ),
+                Vec::default(),
+                CachePolicy::Disable,
+            )
+            .await
+            .unwrap();
+        version_manager.ingest(sst_id, level, 10).await.unwrap();
+    }
+
+    fn build_rudder_for_test(version_manager: VersionManager) -> Rudder {
+        Rudder::new(RudderOptions {
+            version_manager,
+            sstable_store: build_sstable_store_for_test(),
+            meta_store: Arc::new(MemoryMetaStore::new(Duration::from_secs(60))),
+            channel_pool: ChannelPool::default(),
+        })
+    }
+
+    #[test(tokio::test)]
+    async fn test_get_lsm_tree_summary() {
+        let sstable_store = build_sstable_store_for_test();
+        let version_manager = VersionManager::new(VersionManagerOptions {
+            levels_options: vec![
+                LevelOptions {
+                    compaction_strategy: LevelCompactionStrategy::Overlap,
+                    compression_algorithm: CompressionAlgorithm::None,
+                },
+                LevelOptions {
+                    compaction_strategy: LevelCompactionStrategy::NonOverlap,
+                    compression_algorithm: CompressionAlgorithm::None,
+                },
+            ],
+            levels: vec![vec![], vec![]],
+            sstable_store: sstable_store.clone(),
+        });
+
+        // L0 (overlap) gets 3 ssts, L1 (non-overlap) gets 2 disjoint ssts.
+        ingest_meta(&version_manager, &sstable_store, 1, 0).await;
+        ingest_meta(&version_manager, &sstable_store, 2, 0).await;
+        ingest_meta(&version_manager, &sstable_store, 3, 0).await;
+        ingest_meta(&version_manager, &sstable_store, 4, 1).await;
+        ingest_meta(&version_manager, &sstable_store, 5, 1).await;
+
+        let rudder = build_rudder_for_test(version_manager);
+        let rsp = rudder
+            .get_lsm_tree_summary(Request::new(GetLsmTreeSummaryRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(
+            rsp.levels,
+            vec![
+                LevelSummary {
+                    level: 0,
+                    sstable_count: 3,
+                    data_size: 30,
+                },
+                LevelSummary {
+                    level: 1,
+                    sstable_count: 2,
+                    data_size: 20,
+                },
+            ]
+        );
+    }
+
+    fn build_empty_version_manager_for_test() -> VersionManager {
+        VersionManager::new(VersionManagerOptions {
+            levels_options: vec![LevelOptions {
+                compaction_strategy: LevelCompactionStrategy::Overlap,
+                compression_algorithm: CompressionAlgorithm::None,
+            }],
+            levels: vec![vec![]],
+            sstable_store: build_sstable_store_for_test(),
+        })
+    }
+
+    #[test(tokio::test)]
+    async fn test_wheel_heartbeat_advances_watermark_to_reported_minimum() {
+        let version_manager = build_empty_version_manager_for_test();
+        let rudder = build_rudder_for_test(version_manager);
+        let endpoint = PbEndpoint {
+            host: "127.0.0.1".to_string(),
+            port: 1234,
+        };
+
+        // Only node 1 has reported: the global watermark tracks it.
+        rudder
+            .heartbeat(Request::new(heartbeat_request(1, endpoint.clone(), 5)))
+            .await
+            .unwrap();
+        assert_eq!(rudder.version_manager.watermark().await, 5);
+
+        // Node 2 reports a higher value: node 1 is still the slower node, so the min (and thus
+        // the global watermark) doesn't move.
+        rudder
+            .heartbeat(Request::new(heartbeat_request(2, endpoint.clone(), 20)))
+            .await
+            .unwrap();
+        assert_eq!(rudder.version_manager.watermark().await, 5);
+
+        // Node 1 catches up partway, still short of node 2's report: the watermark advances with
+        // node 1, which is still the minimum.
+        rudder
+            .heartbeat(Request::new(heartbeat_request(1, endpoint.clone(), 8)))
+            .await
+            .unwrap();
+        assert_eq!(rudder.version_manager.watermark().await, 8);
+
+        // Node 1 catches all the way up to node 2: the watermark tracks the new, higher minimum.
+        rudder
+            .heartbeat(Request::new(heartbeat_request(1, endpoint, 25)))
+            .await
+            .unwrap();
+        assert_eq!(rudder.version_manager.watermark().await, 20);
+    }
+
+    #[test(tokio::test)]
+    async fn test_wheel_heartbeat_rejects_watermark_regression() {
+        let version_manager = build_empty_version_manager_for_test();
+        let rudder = build_rudder_for_test(version_manager);
+        let endpoint = PbEndpoint {
+            host: "127.0.0.1".to_string(),
+            port: 1234,
+        };
+
+        rudder
+            .heartbeat(Request::new(heartbeat_request(1, endpoint.clone(), 10)))
+            .await
+            .unwrap();
+        assert!(rudder
+            .heartbeat(Request::new(heartbeat_request(1, endpoint, 5)))
+            .await
+            .is_err());
+    }
+
+    fn heartbeat_request(node_id: u64, endpoint: PbEndpoint, watermark: u64) -> HeartbeatRequest {
+        HeartbeatRequest {
+            node_id,
+            endpoint: Some(endpoint),
+            heartbeat_message: Some(heartbeat_request::HeartbeatMessage::WheelHeartbeat(
+                WheelHeartbeatRequest {
+                    watermark,
+                    next_version_id: 0,
+                    key_ranges: vec![],
+                },
+            )),
+        }
+    }
+}
@@ -8,7 +8,9 @@ use itertools::Itertools;
 use rand::prelude::SliceRandom;
 use rand::{thread_rng, Rng};
 use runkv_common::channel_pool::ChannelPool;
-use runkv_common::config::{LevelCompactionStrategy, LevelOptions};
+use runkv_common::coding::BytesSerde;
+use runkv_common::config::{CompactionPickerStrategy, LevelCompactionStrategy, LevelOptions};
+use runkv_common::context::Context;
 use runkv_common::Worker;
 use runkv_proto::exhauster::exhauster_service_client::ExhausterServiceClient;
 use runkv_proto::exhauster::CompactionRequest;
@@ -39,6 +41,10 @@ pub struct LsmTreeConfig {
     pub compaction_pin_ttl: Duration,
 
     pub levels_options: Vec<LevelOptions>,
+
+    pub compaction_picker_strategy: CompactionPickerStrategy,
+
+    pub align_partition_to_block_boundary: bool,
 }
 
 impl TryFrom<runkv_common::config::LsmTreeConfig> for LsmTreeConfig {
@@ -88,6 +94,8 @@ impl TryFrom<runkv_common::config::LsmTreeConfig> for LsmTreeConfig {
                 .map_err(Error::config_err)?
                 .into(),
             levels_options: cfg.levels_options,
+            compaction_picker_strategy: cfg.compaction_picker_strategy,
+            align_partition_to_block_boundary: cfg.align_partition_to_block_boundary,
         })
     }
 }
@@ -349,6 +357,23 @@ async fn sub_compaction(
         ctx.level + 1
     };
 
+    // Links the exhauster's compaction trace back to this span, if one is active. Left empty
+    // when there's no current span (e.g. tracing isn't configured), since a span id of `0` would
+    // make the exhauster's `tracing::Id::from_u64(0)` panic.
+    let context = tracing::Span::current()
+        .id()
+        .map(|id| {
+            Context {
+                span_id: id.into_u64(),
+                request_id: 0,
+                propose_at: 0,
+            }
+            .encode_to_vec()
+            .map_err(Error::err)
+        })
+        .transpose()?
+        .unwrap_or_default();
+
     let req = CompactionRequest {
         sst_ids: old_ssts.to_vec(),
         watermark,
@@ -365,6 +390,19 @@ async fn sub_compaction(
             .into(),
         remove_tombstone: target_level as usize == ctx.lsm_tree_config.levels_options.len() - 1,
         partition_points: partition_points.clone(),
+        align_partition_to_block_boundary: ctx.lsm_tree_config.align_partition_to_block_boundary,
+        dry_run: false,
+        job_id: 0,
+        dictionary_size: 0,
+        compression_level: 0,
+        // Automatic background compaction always covers the full input; targeted, range-limited
+        // compaction is triggered separately by an operator.
+        key_range_start: vec![],
+        key_range_end: vec![],
+        target_level,
+        context,
+        key_rewrite_prefix: vec![],
+        cache_policy: 0,
     };
 
     let exhauster = ctx.meta_store.pick_exhauster(ctx.health_timeout).await?;
@@ -470,30 +508,63 @@ async fn pick_ssts(
                     base_level_ssts
                         .extend(base_range_ssts[0][first_idx..first_idx + involves].iter());
                 }
-                LevelCompactionStrategy::Overlap => {
-                    // TODO: Better strategy.
-                    // Random pick 1% sstables and overlap sstables with them.
-                    let base_range_sst_count = base_range_ssts[0].len();
-                    let involves = std::cmp::max(base_range_sst_count / 100, 1);
-                    let mut base_level_ssts_set: BTreeSet<u64> = BTreeSet::default();
-                    let ssts = base_range_ssts[0]
-                        .choose_multiple(&mut thread_rng(), involves)
-                        .copied()
-                        .collect_vec();
-                    base_level_ssts_set.extend(ssts.iter());
-                    for sst in ssts {
-                        let overlaps = ctx
-                            .version_manager
-                            .pick_overlap_ssts_by_sst_id(
-                                ctx.level as usize..ctx.level as usize + 1,
-                                sst,
-                            )
-                            .await?;
-                        assert_eq!(overlaps.len(), 1);
-                        base_level_ssts_set.extend(overlaps[0].iter());
+                LevelCompactionStrategy::Overlap => match ctx
+                    .lsm_tree_config
+                    .compaction_picker_strategy
+                {
+                    CompactionPickerStrategy::Leveled => {
+                        // TODO: Better strategy.
+                        // Random pick 1% sstables and overlap sstables with them. Draw a few
+                        // independent samples and keep the one whose key ranges overlap the
+                        // most, so an unlucky draw doesn't pay the write cost of several
+                        // unrelated pieces of the keyspace instead of rewriting one.
+                        let base_range_sst_count = base_range_ssts[0].len();
+                        let involves = std::cmp::max(base_range_sst_count / 100, 1);
+
+                        let mut candidate_sets = Vec::with_capacity(OVERLAP_CANDIDATE_DRAWS);
+                        let mut candidate_ranges = Vec::with_capacity(OVERLAP_CANDIDATE_DRAWS);
+                        for _ in 0..OVERLAP_CANDIDATE_DRAWS {
+                            let mut candidate_set: BTreeSet<u64> = BTreeSet::default();
+                            let ssts = base_range_ssts[0]
+                                .choose_multiple(&mut thread_rng(), involves)
+                                .copied()
+                                .collect_vec();
+                            candidate_set.extend(ssts.iter());
+                            for sst in ssts {
+                                let overlaps = ctx
+                                    .version_manager
+                                    .pick_overlap_ssts_by_sst_id(
+                                        ctx.level as usize..ctx.level as usize + 1,
+                                        sst,
+                                    )
+                                    .await?;
+                                assert_eq!(overlaps.len(), 1);
+                                candidate_set.extend(overlaps[0].iter());
+                            }
+                            let mut ranges = Vec::with_capacity(candidate_set.len());
+                            for &id in &candidate_set {
+                                ranges.push(ctx.version_manager.sstable_user_key_range(id).await?);
+                            }
+                            candidate_sets.push(candidate_set);
+                            candidate_ranges.push(ranges);
+                        }
+                        let best = pick_most_overlapping(&candidate_ranges);
+                        base_level_ssts.extend(candidate_sets[best].iter());
                     }
-                    base_level_ssts.extend(base_level_ssts_set.iter());
-                }
+                    CompactionPickerStrategy::Tiered => {
+                        let mut runs = Vec::with_capacity(base_range_ssts[0].len());
+                        for &id in &base_range_ssts[0] {
+                            let data_size = ctx.version_manager.sstable_data_size(id).await?;
+                            runs.push(SstRun { id, data_size });
+                        }
+                        let tier = pick_tiered_ssts(
+                            &runs,
+                            TIERED_COMPACTION_MIN_RUNS,
+                            TIERED_COMPACTION_SIZE_RATIO,
+                        );
+                        base_level_ssts.extend(tier.iter());
+                    }
+                },
             }
             // TODO: Stop picking if there is already too many.
         }
@@ -552,3 +623,170 @@ fn verify_no_duplication(mut iter: core::slice::Iter<u64>) -> bool {
     let mut unique = HashSet::new();
     iter.all(|v| unique.insert(*v))
 }
+
+/// Number of random candidate sets drawn per pick under [`CompactionPickerStrategy::Leveled`],
+/// scored by key-range overlap so only the most overlapping draw is kept.
+const OVERLAP_CANDIDATE_DRAWS: usize = 3;
+
+/// Fraction of pairs in `ranges` (each an inclusive `(min, max)` user key range) whose ranges
+/// intersect. `0.0` for fewer than two ranges or when none intersect; `1.0` when every pair does.
+fn overlap_score(ranges: &[(Vec<u8>, Vec<u8>)]) -> f64 {
+    if ranges.len() < 2 {
+        return 0.0;
+    }
+    let mut overlapping_pairs = 0;
+    let mut total_pairs = 0;
+    for i in 0..ranges.len() {
+        for j in i + 1..ranges.len() {
+            total_pairs += 1;
+            let (min_a, max_a) = &ranges[i];
+            let (min_b, max_b) = &ranges[j];
+            if min_a <= max_b && min_b <= max_a {
+                overlapping_pairs += 1;
+            }
+        }
+    }
+    overlapping_pairs as f64 / total_pairs as f64
+}
+
+/// Returns the index of the candidate set in `candidates` with the highest [`overlap_score`],
+/// breaking ties by preferring the earlier candidate.
+fn pick_most_overlapping(candidates: &[Vec<(Vec<u8>, Vec<u8>)>]) -> usize {
+    candidates
+        .iter()
+        .map(|ranges| overlap_score(ranges))
+        .enumerate()
+        .fold(
+            (0, f64::MIN),
+            |(best_idx, best_score), (idx, score)| {
+                if score > best_score {
+                    (idx, score)
+                } else {
+                    (best_idx, best_score)
+                }
+            },
+        )
+        .0
+}
+
+/// A minimum number of same-tier runs worth merging; a single run has nothing to compact with.
+const TIERED_COMPACTION_MIN_RUNS: usize = 2;
+/// Two runs belong to the same tier when the larger is at most this many times the smaller.
+const TIERED_COMPACTION_SIZE_RATIO: f64 = 2.0;
+
+/// An sstable candidate for the tiered picker: its id and on-disk data size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SstRun {
+    id: u64,
+    data_size: u64,
+}
+
+/// Groups `runs` into tiers of similarly-sized sstables (a tier's largest member is at most
+/// `size_ratio` times its smallest) and returns the ids of the largest tier with at least
+/// `min_runs` members, or an empty vec if no tier qualifies.
+///
+/// Similarly-sized runs are merged together rather than, e.g., always merging the oldest run
+/// into the next level, which suits write-heavy workloads: new runs of roughly equal size pile up
+/// from flushes, and merging same-tier runs keeps each merge's write amplification proportional
+/// to the data actually being compacted.
+fn pick_tiered_ssts(runs: &[SstRun], min_runs: usize, size_ratio: f64) -> Vec<u64> {
+    let mut sorted = runs.to_vec();
+    sorted.sort_by_key(|run| run.data_size);
+
+    let mut best: Vec<u64> = vec![];
+    let mut tier: Vec<SstRun> = vec![];
+    for run in sorted {
+        let fits = tier
+            .first()
+            .map(|smallest| run.data_size as f64 <= smallest.data_size as f64 * size_ratio)
+            .unwrap_or(true);
+        if fits {
+            tier.push(run);
+            continue;
+        }
+        if tier.len() >= min_runs && tier.len() > best.len() {
+            best = tier.iter().map(|run| run.id).collect();
+        }
+        tier = vec![run];
+    }
+    if tier.len() >= min_runs && tier.len() > best.len() {
+        best = tier.iter().map(|run| run.id).collect();
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_tiered_ssts_groups_same_tier_runs() {
+        let runs = vec![
+            SstRun {
+                id: 1,
+                data_size: 100,
+            },
+            SstRun {
+                id: 2,
+                data_size: 110,
+            },
+            SstRun {
+                id: 3,
+                data_size: 120,
+            },
+            SstRun {
+                id: 4,
+                data_size: 10_000,
+            },
+            SstRun {
+                id: 5,
+                data_size: 10_500,
+            },
+        ];
+
+        let mut picked = pick_tiered_ssts(&runs, 2, 2.0);
+        picked.sort_unstable();
+
+        // Both tiers qualify (>= 2 runs); the larger tier (3 small runs) wins.
+        assert_eq!(picked, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_pick_tiered_ssts_returns_empty_when_no_tier_qualifies() {
+        let runs = vec![
+            SstRun {
+                id: 1,
+                data_size: 100,
+            },
+            SstRun {
+                id: 2,
+                data_size: 10_000,
+            },
+        ];
+
+        assert!(pick_tiered_ssts(&runs, 2, 2.0).is_empty());
+    }
+
+    #[test]
+    fn test_pick_most_overlapping_prefers_higher_overlap_set() {
+        // Heavily overlapping: all three ranges share [30, 60].
+        let high_overlap = vec![
+            (b"10".to_vec(), b"60".to_vec()),
+            (b"20".to_vec(), b"70".to_vec()),
+            (b"30".to_vec(), b"80".to_vec()),
+        ];
+        // Disjoint: no two ranges intersect.
+        let low_overlap = vec![
+            (b"10".to_vec(), b"19".to_vec()),
+            (b"20".to_vec(), b"29".to_vec()),
+            (b"30".to_vec(), b"39".to_vec()),
+        ];
+
+        assert!(overlap_score(&high_overlap) > overlap_score(&low_overlap));
+        assert_eq!(
+            pick_most_overlapping(&[low_overlap, high_overlap]),
+            1,
+            "the higher-overlap candidate set should be chosen"
+        );
+    }
+}
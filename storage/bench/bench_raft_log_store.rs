@@ -59,6 +59,8 @@ async fn build_raft_log_store(args: &Args) -> RaftLogStore {
         log_file_capacity: args.log_file_capacity,
         block_cache_capacity: args.block_cache_capacity,
         persist: args.persist,
+        strict_repair: false,
+        compression_threshold: runkv_storage::raft_log_store::DEFAULT_COMPRESSION_THRESHOLD,
     };
     RaftLogStore::open(raft_log_store_options).await.unwrap()
 }
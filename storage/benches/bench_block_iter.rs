@@ -80,6 +80,8 @@ fn build_block(t: u32, i: u64) -> Block {
         capacity: BLOCK_CAPACITY,
         compression_algorithm: CompressionAlgorithm::None,
         restart_interval: RESTART_INTERVAL,
+        dictionary: vec![],
+        compression_level: 0,
     };
     let mut builder = BlockBuilder::new(options);
     for tt in 1..=t {
@@ -88,7 +90,7 @@ fn build_block(t: u32, i: u64) -> Block {
         }
     }
     let data = builder.build();
-    Block::decode(&data[..]).unwrap()
+    Block::decode(&data[..], &[]).unwrap()
 }
 
 fn key(t: u32, i: u64) -> Vec<u8> {
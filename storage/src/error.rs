@@ -16,6 +16,10 @@ pub enum Error {
     IoError(#[from] std::io::Error),
     #[error("raft log store error: {0}")]
     RaftLogStoreError(#[from] RaftLogStoreError),
+    #[error("invalid compression level: {0}")]
+    InvalidCompressionLevel(String),
+    #[error("checksum mismatch: [expected: {expected}] [actual: {actual}]")]
+    ChecksumMismatch { expected: u32, actual: u32 },
     #[error("other: {0}")]
     Other(String),
 }
@@ -32,6 +36,10 @@ impl Error {
     pub fn decode_error(e: impl Into<Box<dyn std::error::Error>>) -> Self {
         Self::DecodeError(e.into().to_string())
     }
+
+    pub fn invalid_compression_level(e: impl Into<Box<dyn std::error::Error>>) -> Self {
+        Self::InvalidCompressionLevel(e.into().to_string())
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,66 @@
+use bytes::{Buf, BufMut};
+
+/// Pointer to a value separated out of an sstable block into a blob object, left in the block in
+/// the separated value's place. See [`super::SstableBuilderOptions::value_separation_threshold`].
+///
+/// Deliberately decoupled from the sstable that wrote it: `blob_id` identifies the blob object
+/// itself, not the sstable. Compaction copying a [`crate::utils::RawValue::BlobRef`] forward into
+/// a new sstable (instead of re-separating the value) leaves `blob_id` untouched, so the pointer
+/// keeps addressing the original, never-rewritten blob bytes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BlobRef {
+    pub blob_id: u64,
+    pub offset: u32,
+    pub len: u32,
+}
+
+impl BlobRef {
+    /// Format:
+    ///
+    /// ```plain
+    /// | blob id (8B) | offset (4B) | len (4B) |
+    /// ```
+    pub const ENCODED_LEN: usize = 16;
+
+    pub fn encode(&self, buf: &mut impl BufMut) {
+        buf.put_u64_le(self.blob_id);
+        buf.put_u32_le(self.offset);
+        buf.put_u32_le(self.len);
+    }
+
+    pub fn decode(buf: &mut impl Buf) -> Self {
+        let blob_id = buf.get_u64_le();
+        let offset = buf.get_u32_le();
+        let len = buf.get_u32_le();
+        Self {
+            blob_id,
+            offset,
+            len,
+        }
+    }
+
+    /// Byte range of the pointed-to value within its blob object, for an object store range-get.
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.offset as usize..self.offset as usize + self.len as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blob_ref_enc_dec() {
+        let blob_ref = BlobRef {
+            blob_id: 42,
+            offset: 1024,
+            len: 256,
+        };
+        let mut buf = vec![];
+        blob_ref.encode(&mut buf);
+        assert_eq!(buf.len(), BlobRef::ENCODED_LEN);
+        let decoded = BlobRef::decode(&mut &buf[..]);
+        assert_eq!(blob_ref, decoded);
+        assert_eq!(blob_ref.range(), 1024..1280);
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::io::{Read, Write};
 use std::ops::Range;
@@ -22,7 +23,9 @@ pub struct Block {
 }
 
 impl Block {
-    pub fn decode(buf: &[u8]) -> Result<Self> {
+    /// Decodes a block. `dictionary` is the sstable's zstd dictionary, if any; it is ignored
+    /// unless the block was compressed with [`CompressionAlgorithm::Zstd`].
+    pub fn decode(buf: &[u8], dictionary: &[u8]) -> Result<Self> {
         // Verify checksum.
         let crc32sum = (&buf[buf.len() - 4..]).get_u32_le();
         if !crc32check(&buf[..buf.len() - 4], crc32sum) {
@@ -32,8 +35,11 @@ impl Block {
         // Decompress.
         let compression = CompressionAlgorithm::decode(&mut &buf[buf.len() - 5..buf.len() - 4])
             .map_err(Error::decode_error)?;
-        let buf = match compression {
-            CompressionAlgorithm::None => buf[..buf.len() - 5].to_vec(),
+        let buf: Cow<[u8]> = match compression {
+            // Nothing to decompress, so borrow the trailer-stripped input instead of copying it
+            // into an intermediate buffer. The only remaining copy is the unavoidable one below
+            // that splits entries data off from the restart points.
+            CompressionAlgorithm::None => Cow::Borrowed(&buf[..buf.len() - 5]),
             CompressionAlgorithm::Lz4 => {
                 let mut decoder = Decoder::new(buf.reader())
                     .map_err(Error::decode_error)
@@ -43,7 +49,18 @@ impl Block {
                     .read_to_end(&mut decoded)
                     .map_err(Error::decode_error)
                     .unwrap();
-                decoded
+                Cow::Owned(decoded)
+            }
+            CompressionAlgorithm::Zstd => {
+                let mut decoder = zstd::Decoder::with_dictionary(buf.reader(), dictionary)
+                    .map_err(Error::decode_error)
+                    .unwrap();
+                let mut decoded = Vec::with_capacity(DEFAULT_BLOCK_SIZE);
+                decoder
+                    .read_to_end(&mut decoded)
+                    .map_err(Error::decode_error)
+                    .unwrap();
+                Cow::Owned(decoded)
             }
         };
 
@@ -172,6 +189,13 @@ pub struct BlockBuilderOptions {
     pub compression_algorithm: CompressionAlgorithm,
     /// Restart point interval.
     pub restart_interval: usize,
+    /// Zstd dictionary to compress with. Ignored unless `compression_algorithm` is
+    /// [`CompressionAlgorithm::Zstd`]. Empty means no dictionary.
+    pub dictionary: Vec<u8>,
+    /// Compression level passed to the `compression_algorithm`'s codec. `0` means "use the
+    /// codec's own default". See [`crate::utils::compression_level_range`] for the legal range
+    /// per algorithm.
+    pub compression_level: i32,
 }
 
 impl Default for BlockBuilderOptions {
@@ -184,6 +208,8 @@ impl Default for BlockBuilderOptions {
             } else {
                 TEST_DEFAULT_RESTART_INTERVAL
             },
+            dictionary: vec![],
+            compression_level: 0,
         }
     }
 }
@@ -202,6 +228,10 @@ pub struct BlockBuilder {
     entry_count: usize,
     /// Compression algorithm.
     compression_algorithm: CompressionAlgorithm,
+    /// Zstd dictionary to compress with. See [`BlockBuilderOptions::dictionary`].
+    dictionary: Vec<u8>,
+    /// Compression level. See [`BlockBuilderOptions::compression_level`].
+    compression_level: i32,
 }
 
 impl BlockBuilder {
@@ -215,6 +245,8 @@ impl BlockBuilder {
             last_key: Vec::default(),
             entry_count: 0,
             compression_algorithm: options.compression_algorithm,
+            dictionary: options.dictionary,
+            compression_level: options.compression_level,
         }
     }
 
@@ -281,7 +313,7 @@ impl BlockBuilder {
             CompressionAlgorithm::None => self.buf,
             CompressionAlgorithm::Lz4 => {
                 let mut encoder = lz4::EncoderBuilder::new()
-                    .level(4)
+                    .level(self.compression_level as u32)
                     .build(Vec::with_capacity(self.buf.len()).writer())
                     .map_err(Error::encode_error)
                     .unwrap();
@@ -293,6 +325,21 @@ impl BlockBuilder {
                 result.map_err(Error::encode_error).unwrap();
                 writer.into_inner()
             }
+            CompressionAlgorithm::Zstd => {
+                let mut encoder = zstd::Encoder::with_dictionary(
+                    Vec::with_capacity(self.buf.len()).writer(),
+                    self.compression_level,
+                    &self.dictionary,
+                )
+                .map_err(Error::encode_error)
+                .unwrap();
+                encoder
+                    .write_all(&self.buf[..])
+                    .map_err(Error::encode_error)
+                    .unwrap();
+                let writer = encoder.finish().map_err(Error::encode_error).unwrap();
+                writer.into_inner()
+            }
         };
         self.compression_algorithm.encode(&mut buf);
         let checksum = crc32sum(&buf);
@@ -326,7 +373,7 @@ mod tests {
         builder.add(&full_key(b"k3", 3), b"v03");
         builder.add(&full_key(b"k4", 4), b"v04");
         let buf = builder.build();
-        let block = Arc::new(Block::decode(&buf).unwrap());
+        let block = Arc::new(Block::decode(&buf, &[]).unwrap());
         let mut bi = BlockIterator::new(block);
 
         bi.seek(Seek::First).unwrap();
@@ -365,7 +412,7 @@ mod tests {
         builder.add(&full_key(b"k3", 3), b"v03");
         builder.add(&full_key(b"k4", 4), b"v04");
         let buf = builder.build();
-        let block = Arc::new(Block::decode(&buf).unwrap());
+        let block = Arc::new(Block::decode(&buf, &[]).unwrap());
         let mut bi = BlockIterator::new(block);
 
         bi.seek(Seek::First).unwrap();
@@ -401,7 +448,7 @@ mod tests {
         builder.add(&full_key(b"k2", u64::MAX / 2), b"v21");
         builder.add(&full_key(b"k20000", u64::MAX), b"v22");
         let buf = builder.build();
-        let block = Arc::new(Block::decode(&buf).unwrap());
+        let block = Arc::new(Block::decode(&buf, &[]).unwrap());
         let mut bi = BlockIterator::new(block);
 
         bi.seek(Seek::First).unwrap();
@@ -1,3 +1,4 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -10,6 +11,15 @@ use super::Block;
 use crate::lsm_tree::DEFAULT_BLOCK_SIZE;
 use crate::{Error, Result};
 
+/// Snapshot of [`BlockCache`]'s hit/miss/size counters, as surfaced by
+/// [`super::SstableStore::stats`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlockCacheStats {
+    pub hit_count: u64,
+    pub miss_count: u64,
+    pub bytes: u64,
+}
+
 pub struct BlockCache {
     inner: Cache<Vec<u8>, Arc<Block>>,
     metrics: LsmTreeMetricsRef,
@@ -38,6 +48,11 @@ impl BlockCache {
             .block_cache_get_latency_histogram
             .observe(start.elapsed().as_secs_f64());
 
+        match &result {
+            Some(_) => self.metrics.block_cache_hit_counter.inc(),
+            None => self.metrics.block_cache_miss_counter.inc(),
+        }
+
         result
     }
 
@@ -49,6 +64,8 @@ impl BlockCache {
         self.metrics
             .block_cache_insert_latency_histogram
             .observe(start.elapsed().as_secs_f64());
+
+        self.update_bytes_gauge();
     }
 
     pub async fn get_or_insert_with<F>(
@@ -60,16 +77,22 @@ impl BlockCache {
     where
         F: Future<Output = Result<Arc<Block>>>,
     {
-        let future = async move {
-            let start_fill = Instant::now();
+        let filled = Arc::new(AtomicBool::new(false));
+        let future = {
+            let filled = filled.clone();
+            async move {
+                filled.store(true, Ordering::Relaxed);
+
+                let start_fill = Instant::now();
 
-            let r = f.await;
+                let r = f.await;
 
-            self.metrics
-                .block_cache_fill_latency_histogram
-                .observe(start_fill.elapsed().as_secs_f64());
+                self.metrics
+                    .block_cache_fill_latency_histogram
+                    .observe(start_fill.elapsed().as_secs_f64());
 
-            r
+                r
+            }
         };
 
         let start = Instant::now();
@@ -87,9 +110,31 @@ impl BlockCache {
             .block_cache_get_latency_histogram
             .observe(start.elapsed().as_secs_f64());
 
+        if filled.load(Ordering::Relaxed) {
+            self.metrics.block_cache_miss_counter.inc();
+            self.update_bytes_gauge();
+        } else {
+            self.metrics.block_cache_hit_counter.inc();
+        }
+
         result
     }
 
+    /// Returns a snapshot of this cache's hit/miss/size counters.
+    pub fn stats(&self) -> BlockCacheStats {
+        BlockCacheStats {
+            hit_count: self.metrics.block_cache_hit_counter.get() as u64,
+            miss_count: self.metrics.block_cache_miss_counter.get() as u64,
+            bytes: self.metrics.block_cache_bytes_gauge.get().max(0) as u64,
+        }
+    }
+
+    fn update_bytes_gauge(&self) {
+        self.metrics
+            .block_cache_bytes_gauge
+            .set(self.inner.weighted_size() as i64);
+    }
+
     fn key(sst_id: u64, block_idx: usize) -> Vec<u8> {
         let mut key = Vec::with_capacity(16);
         key.put_u64_le(sst_id);
@@ -10,12 +10,29 @@ lazy_static! {
             &["op", "node"]
         )
         .unwrap();
+    static ref BLOCK_CACHE_OPS_COUNTER_VEC: prometheus::IntCounterVec =
+        prometheus::register_int_counter_vec!(
+            "lsm_tree_block_cache_ops_counter_vec",
+            "lsm tree block cache hit/miss counter vec",
+            &["op", "node"]
+        )
+        .unwrap();
+    static ref BLOCK_CACHE_BYTES_GAUGE_VEC: prometheus::IntGaugeVec =
+        prometheus::register_int_gauge_vec!(
+            "lsm_tree_block_cache_bytes_gauge_vec",
+            "lsm tree block cache current weighted size in bytes",
+            &["node"]
+        )
+        .unwrap();
 }
 
 pub struct LsmTreeMetrics {
     pub block_cache_get_latency_histogram: prometheus::Histogram,
     pub block_cache_insert_latency_histogram: prometheus::Histogram,
     pub block_cache_fill_latency_histogram: prometheus::Histogram,
+    pub block_cache_hit_counter: prometheus::IntCounter,
+    pub block_cache_miss_counter: prometheus::IntCounter,
+    pub block_cache_bytes_gauge: prometheus::IntGauge,
 }
 
 pub type LsmTreeMetricsRef = Arc<LsmTreeMetrics>;
@@ -32,6 +49,15 @@ impl LsmTreeMetrics {
             block_cache_fill_latency_histogram: BLOCK_CACHE_LATENCY_HISTOGRAM_VEC
                 .get_metric_with_label_values(&["block_cache_fill", &node.to_string()])
                 .unwrap(),
+            block_cache_hit_counter: BLOCK_CACHE_OPS_COUNTER_VEC
+                .get_metric_with_label_values(&["block_cache_hit", &node.to_string()])
+                .unwrap(),
+            block_cache_miss_counter: BLOCK_CACHE_OPS_COUNTER_VEC
+                .get_metric_with_label_values(&["block_cache_miss", &node.to_string()])
+                .unwrap(),
+            block_cache_bytes_gauge: BLOCK_CACHE_BYTES_GAUGE_VEC
+                .get_metric_with_label_values(&[&node.to_string()])
+                .unwrap(),
         }
     }
 }
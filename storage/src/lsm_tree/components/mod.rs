@@ -1,3 +1,5 @@
+mod blob;
+pub use blob::*;
 mod block;
 pub use block::*;
 mod block_cache;
@@ -12,3 +14,5 @@ mod skiplist;
 pub use skiplist::*;
 mod metrics;
 pub use metrics::*;
+mod wal;
+pub use wal::*;
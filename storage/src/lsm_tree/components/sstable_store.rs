@@ -1,25 +1,125 @@
+use std::future::Future;
 use std::mem::size_of;
 use std::sync::Arc;
+use std::time::Duration;
 
 use moka::future::Cache;
+use runkv_common::coding::CompressionAlgorithm;
 
-use super::{Block, BlockCache, Sstable, SstableMeta};
+use super::{BlobRef, Block, BlockCache, BlockCacheStats, BlockMeta, Sstable, SstableMeta};
 use crate::object_store::ObjectStoreRef;
+use crate::utils::{crc32sum, Bloom};
 use crate::{Error, ObjectStoreError, Result};
 
+/// Read-only dump of an sstable's meta, for debugging and tools like `runkvctl sst dump`.
+/// Everything here is parsed straight out of the `.meta` object; no data block is read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SstInfo {
+    pub id: u64,
+    pub block_count: usize,
+    pub first_key: Option<Vec<u8>>,
+    pub last_key: Option<Vec<u8>>,
+    pub block_metas: Vec<BlockMeta>,
+    pub bloom_filter_len: usize,
+    /// Number of hash functions the bloom filter was built with, or `None` if the sstable has no
+    /// bloom filter (e.g. built with `bloom_false_positive <= 0.0`).
+    pub bloom_num_hashes: Option<u8>,
+    pub compression_algorithm: CompressionAlgorithm,
+    pub data_size: usize,
+    pub data_checksum: u32,
+    pub dictionary_len: usize,
+}
+
+/// Object store operations retried after a transient failure before giving up, so a job doesn't
+/// hang forever on a backend that's actually down.
+const OBJECT_STORE_MAX_RETRIES: usize = 3;
+const OBJECT_STORE_RETRY_BASE_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Whether `e` is worth retrying. A missing object is a fatal, deterministic outcome (retrying
+/// won't make it appear), so it is returned at once. Every other error goes through the backoff
+/// loop whether or not it is transient: an S3 error of any kind (auth failures included), an
+/// invalid range or an I/O error is retried just like a timeout.
+fn is_retryable(e: &Error) -> bool {
+    !matches!(
+        e,
+        Error::ObjectStoreError(ObjectStoreError::ObjectNotFound(_))
+    )
+}
+
+/// Retries `f` with exponential backoff on retryable errors, up to [`OBJECT_STORE_MAX_RETRIES`]
+/// times.
+async fn with_retry<T, F, Fut>(mut f: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match f().await {
+            Ok(v) => return Ok(v),
+            Err(e) if attempt < OBJECT_STORE_MAX_RETRIES && is_retryable(&e) => {
+                tokio::time::sleep(OBJECT_STORE_RETRY_BASE_BACKOFF * 2u32.pow(attempt as u32))
+                    .await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 // TODO: Define policy based on use cases (read / comapction / ...).
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum CachePolicy {
     Disable,
     Fill,
     NotFill,
 }
 
+impl From<CachePolicy> for u8 {
+    fn from(policy: CachePolicy) -> Self {
+        match policy {
+            CachePolicy::NotFill => 0,
+            CachePolicy::Fill => 1,
+            CachePolicy::Disable => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for CachePolicy {
+    type Error = Error;
+
+    fn try_from(v: u8) -> Result<Self> {
+        match v {
+            0 => Ok(Self::NotFill),
+            1 => Ok(Self::Fill),
+            2 => Ok(Self::Disable),
+            _ => Err(Error::decode_error(format!("not valid cache policy: {}", v))),
+        }
+    }
+}
+
+/// Entry count cap for the dedup-related caches. Entries are small (a `u64`/path string), so this
+/// can afford to be generous relative to `meta_cache_capacity`, which bounds actual sstable meta
+/// bytes.
+const DEFAULT_CONTENT_INDEX_CAPACITY: u64 = 65536;
+
+/// Content hash used to detect identical sstable data objects. `fingerprint64` is already the
+/// workspace's fingerprinting primitive (see [`farmhash::fingerprint32`] usage for bloom filter
+/// keys elsewhere), so dedup reuses it rather than pulling in another hashing crate.
+fn content_hash(data: &[u8]) -> u64 {
+    farmhash::fingerprint64(data)
+}
+
 pub struct SstableStoreOptions {
     pub path: String,
     pub object_store: ObjectStoreRef,
     pub block_cache: BlockCache,
     pub meta_cache_capacity: usize,
+    /// Skips uploading an sstable's data object when a physically identical one (by content
+    /// hash) has already been uploaded, referencing the existing object instead. Off by default:
+    /// hashing every sstable's data costs something, and most deployments don't have enough
+    /// duplicate data across compactions for the savings to be worth it.
+    pub enable_content_dedup: bool,
 }
 
 pub struct SstableStore {
@@ -27,6 +127,13 @@ pub struct SstableStore {
     object_store: ObjectStoreRef,
     block_cache: BlockCache,
     meta_cache: Cache<u64, Arc<SstableMeta>>,
+    /// Maps a data object's content hash to the path it was first uploaded at, so a later `put`
+    /// with identical content can skip re-uploading it. `None` when dedup is disabled.
+    content_index: Option<Cache<u64, String>>,
+    /// Caches the resolved data path per sstable id, since a deduped id's data doesn't live at
+    /// its own [`Self::data_path`] and resolving that otherwise costs an extra object store read
+    /// of its `.data.ref` pointer. Only populated/consulted when dedup is enabled.
+    data_path_cache: Cache<u64, String>,
 }
 
 impl SstableStore {
@@ -38,23 +145,52 @@ impl SstableStore {
             meta_cache: Cache::new(
                 (options.meta_cache_capacity / size_of::<SstableMeta>() + 1) as u64,
             ),
+            content_index: options
+                .enable_content_dedup
+                .then(|| Cache::new(DEFAULT_CONTENT_INDEX_CAPACITY)),
+            data_path_cache: Cache::new(DEFAULT_CONTENT_INDEX_CAPACITY),
         }
     }
 
     pub async fn put(&self, sst: &Sstable, data: Vec<u8>, policy: CachePolicy) -> Result<()> {
         let data_path = self.data_path(sst.id());
-        self.object_store.put(&data_path, data.clone()).await?;
+
+        match &self.content_index {
+            // Dedup disabled: always upload, exactly as before.
+            None => with_retry(|| self.object_store.put(&data_path, data.clone())).await?,
+            Some(content_index) => {
+                let hash = content_hash(&data);
+                match content_index.get(&hash) {
+                    // Identical content is already stored at `canonical_path`; write a small
+                    // pointer instead of re-uploading the (often large) data object.
+                    Some(canonical_path) => {
+                        let data_ref_path = self.data_ref_path(sst.id());
+                        with_retry(|| {
+                            self.object_store
+                                .put(&data_ref_path, canonical_path.clone().into_bytes())
+                        })
+                        .await?;
+                        self.data_path_cache.insert(sst.id(), canonical_path).await;
+                    }
+                    None => {
+                        with_retry(|| self.object_store.put(&data_path, data.clone())).await?;
+                        content_index.insert(hash, data_path.clone()).await;
+                        self.data_path_cache.insert(sst.id(), data_path.clone()).await;
+                    }
+                }
+            }
+        }
 
         let meta = sst.encode_meta();
         let meta_path = self.meta_path(sst.id());
-        if let Err(e) = self.object_store.put(&meta_path, meta).await {
-            self.object_store.remove(&data_path).await?;
+        if let Err(e) = with_retry(|| self.object_store.put(&meta_path, meta.clone())).await {
+            with_retry(|| self.object_store.remove(&data_path)).await?;
             return Err(e);
         }
 
         if let CachePolicy::Fill = policy {
             for (block_idx, meta) in sst.block_metas_iter().enumerate() {
-                let block = Arc::new(Block::decode(&data[meta.data_range()])?);
+                let block = Arc::new(Block::decode(&data[meta.data_range()], sst.dictionary())?);
                 self.block_cache.insert(sst.id(), block_idx, block).await
             }
         }
@@ -62,6 +198,24 @@ impl SstableStore {
         Ok(())
     }
 
+    /// Resolves the object store path actually holding `sst_id`'s data, following its
+    /// [`Self::data_ref_path`] pointer if [`Self::put`] deduped it onto another sstable's data.
+    async fn resolve_data_path(&self, sst_id: u64) -> Result<String> {
+        if self.content_index.is_none() {
+            return Ok(self.data_path(sst_id));
+        }
+        if let Some(path) = self.data_path_cache.get(&sst_id) {
+            return Ok(path);
+        }
+        let data_ref_path = self.data_ref_path(sst_id);
+        let path = match with_retry(|| self.object_store.get(&data_ref_path)).await? {
+            Some(buf) => String::from_utf8(buf).map_err(|e| Error::Other(e.to_string()))?,
+            None => self.data_path(sst_id),
+        };
+        self.data_path_cache.insert(sst_id, path.clone()).await;
+        Ok(path)
+    }
+
     pub async fn block(
         &self,
         sst: &Sstable,
@@ -76,15 +230,14 @@ impl SstableStore {
                     block_index
                 ))
             })?;
-            let data_path = self.data_path(sst.id());
-            let block_data = self
-                .object_store
-                .get_range(&data_path, block_meta.data_range())
+            let data_path = self.resolve_data_path(sst.id()).await?;
+            let range = block_meta.data_range();
+            let block_data = with_retry(|| self.object_store.get_range(&data_path, range.clone()))
                 .await?
                 .ok_or(Error::ObjectStoreError(ObjectStoreError::ObjectNotFound(
                     data_path,
                 )))?;
-            let block = Block::decode(&block_data)?;
+            let block = Block::decode(&block_data, sst.dictionary())?;
             Ok(Arc::new(block))
         };
 
@@ -107,19 +260,59 @@ impl SstableStore {
         Ok(Sstable::new(sst_id, meta))
     }
 
+    /// Parses `sst_id`'s meta and reports it for debugging/inspection (e.g. a `runkvctl sst
+    /// dump`), without downloading or decoding any data block.
+    pub async fn sst_info(&self, sst_id: u64) -> Result<SstInfo> {
+        let meta = self.meta(sst_id).await?;
+        let bloom_num_hashes = (!meta.bloom_filter_bytes.is_empty())
+            .then(|| Bloom::new(&meta.bloom_filter_bytes).num_hashes());
+        Ok(SstInfo {
+            id: sst_id,
+            block_count: meta.block_metas.len(),
+            first_key: meta.block_metas.first().map(|b| b.first_key.clone()),
+            last_key: meta.block_metas.last().map(|b| b.last_key.clone()),
+            block_metas: meta.block_metas.clone(),
+            bloom_filter_len: meta.bloom_filter_bytes.len(),
+            bloom_num_hashes,
+            compression_algorithm: meta.compression_algorithm,
+            data_size: meta.data_size,
+            data_checksum: meta.data_checksum,
+            dictionary_len: meta.dictionary.len(),
+        })
+    }
+
+    /// Downloads `sst_id`'s whole data object and checks it against its meta's `data_checksum`,
+    /// so a silently-corrupted object (bad disk, truncated upload, bit flip in transit) is caught
+    /// before it's read. Not called from [`Self::sstable`] itself, since most callers of that
+    /// (version management, overlap checks) only need the key range out of the meta and would pay
+    /// for a full data download they don't use; compaction, which actually reads a source SST's
+    /// data end to end, calls this explicitly once per source sst instead.
+    pub async fn verify_data_checksum(&self, sst: &Sstable) -> Result<()> {
+        let data_path = self.resolve_data_path(sst.id()).await?;
+        let data = with_retry(|| self.object_store.get(&data_path))
+            .await?
+            .ok_or(Error::ObjectStoreError(ObjectStoreError::ObjectNotFound(
+                data_path,
+            )))?;
+        let actual = crc32sum(&data);
+        let expected = sst.data_checksum();
+        if actual != expected {
+            return Err(Error::ChecksumMismatch { expected, actual });
+        }
+        Ok(())
+    }
+
     async fn meta(&self, sst_id: u64) -> Result<Arc<SstableMeta>> {
         if let Some(meta) = self.meta_cache.get(&sst_id) {
             return Ok(meta);
         }
         let path = self.meta_path(sst_id);
-        let buf = self
-            .object_store
-            .get(&path)
+        let buf = with_retry(|| self.object_store.get(&path))
             .await?
             .ok_or(Error::ObjectStoreError(ObjectStoreError::ObjectNotFound(
                 path,
             )))?;
-        let meta = Arc::new(SstableMeta::decode(&mut &buf[..]));
+        let meta = Arc::new(SstableMeta::decode(&mut &buf[..])?);
         self.meta_cache.insert(sst_id, meta.clone()).await;
         Ok(meta)
     }
@@ -132,9 +325,56 @@ impl SstableStore {
         format!("{}/{}.data", self.path, sst_id)
     }
 
+    /// Path of the pointer object written in place of `sst_id`'s data when [`Self::put`] dedups
+    /// it onto another sstable's already-uploaded data.
+    fn data_ref_path(&self, sst_id: u64) -> String {
+        format!("{}/{}.data.ref", self.path, sst_id)
+    }
+
     pub fn store(&self) -> ObjectStoreRef {
         self.object_store.clone()
     }
+
+    /// Uploads `data` as blob object `blob_id`, e.g. the value bytes
+    /// [`super::SstableBuilder::blob_data`] separated out while building an sstable with
+    /// [`super::SstableBuilderOptions::value_separation_threshold`] set.
+    pub async fn put_blob(&self, blob_id: u64, data: Vec<u8>) -> Result<()> {
+        let path = self.blob_path(blob_id);
+        with_retry(|| self.object_store.put(&path, data.clone())).await
+    }
+
+    /// Downloads the value bytes a [`BlobRef`] points at. Unlike a sstable's data, a blob object
+    /// is never deduped or cached: compaction copies the pointer forward instead of re-reading
+    /// the bytes, so the usual hot-path-read caching concerns don't apply here.
+    pub async fn blob_range(&self, blob_ref: &BlobRef) -> Result<Vec<u8>> {
+        let path = self.blob_path(blob_ref.blob_id);
+        with_retry(|| self.object_store.get_range(&path, blob_ref.range()))
+            .await?
+            .ok_or(Error::ObjectStoreError(ObjectStoreError::ObjectNotFound(
+                path,
+            )))
+    }
+
+    fn blob_path(&self, blob_id: u64) -> String {
+        format!("{}/{}.blob", self.path, blob_id)
+    }
+
+    /// Physically removes `sst_id`'s meta and data objects and evicts its cached meta.
+    ///
+    /// Callers (e.g. a compaction GC sweep) are responsible for making sure nothing still needs
+    /// `sst_id` before calling this — see [`crate::manifest::VersionManager::pin_sstables`].
+    pub async fn delete(&self, sst_id: u64) -> Result<()> {
+        self.meta_cache.invalidate(&sst_id).await;
+        with_retry(|| self.object_store.remove(&self.data_path(sst_id))).await?;
+        with_retry(|| self.object_store.remove(&self.meta_path(sst_id))).await?;
+        Ok(())
+    }
+
+    /// Returns a snapshot of the block cache's hit/miss/size counters, for diagnosing whether a
+    /// deployment's cache is sized appropriately.
+    pub fn stats(&self) -> BlockCacheStats {
+        self.block_cache.stats()
+    }
 }
 
 pub type SstableStoreRef = Arc<SstableStore>;
@@ -142,14 +382,52 @@ pub type SstableStoreRef = Arc<SstableStore>;
 #[cfg(test)]
 mod tests {
 
+    use std::assert_matches::assert_matches;
+    use std::ops::Range;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_trait::async_trait;
     use runkv_common::coding::CompressionAlgorithm;
     use test_log::test;
 
     use super::*;
     use crate::components::{LsmTreeMetrics, SstableBuilder, SstableBuilderOptions};
     use crate::lsm_tree::TEST_DEFAULT_RESTART_INTERVAL;
+    use crate::object_store::ObjectStore;
     use crate::MemObjectStore;
 
+    /// Fails the first `fail_times` calls to `put` with a retryable error before delegating to
+    /// `inner`, so retry-with-backoff can be exercised deterministically.
+    struct FlakyObjectStore {
+        inner: MemObjectStore,
+        fail_times: usize,
+        attempts: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ObjectStore for FlakyObjectStore {
+        async fn put(&self, path: &str, obj: Vec<u8>) -> Result<()> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.fail_times {
+                return Err(Error::ObjectStoreError(ObjectStoreError::Other(
+                    "simulated transient failure".to_string(),
+                )));
+            }
+            self.inner.put(path, obj).await
+        }
+
+        async fn get(&self, path: &str) -> Result<Option<Vec<u8>>> {
+            self.inner.get(path).await
+        }
+
+        async fn get_range(&self, path: &str, range: Range<usize>) -> Result<Option<Vec<u8>>> {
+            self.inner.get_range(path, range).await
+        }
+
+        async fn remove(&self, path: &str) -> Result<()> {
+            self.inner.remove(path).await
+        }
+    }
+
     fn build_sstable_for_test() -> (SstableMeta, Vec<u8>) {
         let options = SstableBuilderOptions {
             capacity: 1024,
@@ -157,6 +435,12 @@ mod tests {
             restart_interval: TEST_DEFAULT_RESTART_INTERVAL,
             bloom_false_positive: 0.1,
             compression_algorithm: CompressionAlgorithm::None,
+            dictionary: vec![],
+            compression_level: 0,
+            level: 0,
+            parallel_bloom_build: false,
+            value_separation_threshold: 0,
+            blob_id: 0,
         };
         let mut builder = SstableBuilder::new(options);
         builder.add(b"k01", 1, Some(b"v01")).unwrap();
@@ -175,6 +459,7 @@ mod tests {
             object_store,
             block_cache,
             meta_cache_capacity: 1024,
+            enable_content_dedup: false,
         };
         let sstable_store = SstableStore::new(options);
         let (meta, data) = build_sstable_for_test();
@@ -193,7 +478,7 @@ mod tests {
                 .block(&sst, block_idx, CachePolicy::Fill)
                 .await
                 .unwrap();
-            let origin_block = Block::decode(&data[block_meta.data_range()]).unwrap();
+            let origin_block = Block::decode(&data[block_meta.data_range()], &[]).unwrap();
             assert_eq!(origin_block.data(), block.data());
         }
         // Test fetch from object store.
@@ -202,8 +487,351 @@ mod tests {
                 .block(&sst, block_idx, CachePolicy::Disable)
                 .await
                 .unwrap();
-            let origin_block = Block::decode(&data[block_meta.data_range()]).unwrap();
+            let origin_block = Block::decode(&data[block_meta.data_range()], &[]).unwrap();
             assert_eq!(origin_block.data(), block.data());
         }
     }
+
+    #[test(tokio::test)]
+    async fn test_content_dedup_skips_uploading_duplicate_data() {
+        let object_store = Arc::new(MemObjectStore::default());
+        let block_cache = BlockCache::new(65536, Arc::new(LsmTreeMetrics::new(0)));
+        let options = SstableStoreOptions {
+            path: "test".to_string(),
+            object_store: object_store.clone(),
+            block_cache,
+            meta_cache_capacity: 1024,
+            enable_content_dedup: true,
+        };
+        let sstable_store = SstableStore::new(options);
+        let (meta, data) = build_sstable_for_test();
+        let meta = Arc::new(meta);
+
+        let sst1 = Sstable::new(1, meta.clone());
+        sstable_store
+            .put(&sst1, data.clone(), CachePolicy::Disable)
+            .await
+            .unwrap();
+        let sst2 = Sstable::new(2, meta.clone());
+        sstable_store
+            .put(&sst2, data.clone(), CachePolicy::Disable)
+            .await
+            .unwrap();
+
+        // The second sstable's data is identical, so only the first `.data` object should have
+        // been physically written; the second is a small pointer to it.
+        assert!(object_store.get(&sstable_store.data_path(1)).await.unwrap().is_some());
+        assert!(object_store.get(&sstable_store.data_path(2)).await.unwrap().is_none());
+        assert!(object_store
+            .get(&sstable_store.data_ref_path(2))
+            .await
+            .unwrap()
+            .is_some());
+
+        // Reads through sstable 2 still resolve to the shared data.
+        for (block_idx, block_meta) in sst2.block_metas_iter().enumerate() {
+            let block = sstable_store
+                .block(&sst2, block_idx, CachePolicy::Disable)
+                .await
+                .unwrap();
+            let origin_block = Block::decode(&data[block_meta.data_range()], &[]).unwrap();
+            assert_eq!(origin_block.data(), block.data());
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn test_put_retries_on_transient_object_store_errors() {
+        let object_store = Arc::new(FlakyObjectStore {
+            inner: MemObjectStore::default(),
+            fail_times: 2,
+            attempts: AtomicUsize::new(0),
+        });
+        let block_cache = BlockCache::new(65536, Arc::new(LsmTreeMetrics::new(0)));
+        let options = SstableStoreOptions {
+            path: "test".to_string(),
+            object_store,
+            block_cache,
+            meta_cache_capacity: 1024,
+            enable_content_dedup: false,
+        };
+        let sstable_store = SstableStore::new(options);
+        let (meta, data) = build_sstable_for_test();
+        let meta = Arc::new(meta);
+        let sst = Sstable::new(1, meta.clone());
+
+        sstable_store
+            .put(&sst, data.clone(), CachePolicy::Disable)
+            .await
+            .unwrap();
+
+        let fetched = sstable_store.meta(1).await.unwrap();
+        assert_eq!(fetched, meta);
+    }
+
+    #[test(tokio::test)]
+    async fn test_verify_data_checksum_detects_corruption() {
+        let object_store = Arc::new(MemObjectStore::default());
+        let block_cache = BlockCache::new(65536, Arc::new(LsmTreeMetrics::new(0)));
+        let options = SstableStoreOptions {
+            path: "test".to_string(),
+            object_store: object_store.clone(),
+            block_cache,
+            meta_cache_capacity: 1024,
+            enable_content_dedup: false,
+        };
+        let sstable_store = SstableStore::new(options);
+        let (meta, data) = build_sstable_for_test();
+        let sst = Sstable::new(1, Arc::new(meta));
+        sstable_store
+            .put(&sst, data.clone(), CachePolicy::Disable)
+            .await
+            .unwrap();
+
+        // Untouched, the checksum matches.
+        sstable_store.verify_data_checksum(&sst).await.unwrap();
+
+        // Corrupt the stored object directly, bypassing `SstableStore`.
+        let mut corrupted = data;
+        corrupted[0] ^= 0xff;
+        object_store
+            .put(&sstable_store.data_path(1), corrupted)
+            .await
+            .unwrap();
+
+        let err = sstable_store.verify_data_checksum(&sst).await.unwrap_err();
+        assert_matches!(err, Error::ChecksumMismatch { .. });
+    }
+
+    #[test(tokio::test)]
+    async fn test_stats_tracks_block_cache_hits_and_misses() {
+        let object_store = Arc::new(MemObjectStore::default());
+        let block_cache = BlockCache::new(65536, Arc::new(LsmTreeMetrics::new(0)));
+        let options = SstableStoreOptions {
+            path: "test".to_string(),
+            object_store,
+            block_cache,
+            meta_cache_capacity: 1024,
+            enable_content_dedup: false,
+        };
+        let sstable_store = SstableStore::new(options);
+        let (meta, data) = build_sstable_for_test();
+        let meta = Arc::new(meta);
+        let sst = Sstable::new(1, meta);
+        // Upload without filling the cache, so the first read below really misses.
+        sstable_store
+            .put(&sst, data, CachePolicy::NotFill)
+            .await
+            .unwrap();
+
+        // Counters are shared process-wide (labeled by node), so compare deltas rather than
+        // absolute values.
+        let before = sstable_store.stats();
+
+        // First read of a block is a miss that fills the cache.
+        sstable_store
+            .block(&sst, 0, CachePolicy::Fill)
+            .await
+            .unwrap();
+        let after_miss = sstable_store.stats();
+        assert_eq!(after_miss.miss_count, before.miss_count + 1);
+        assert_eq!(after_miss.hit_count, before.hit_count);
+        assert!(after_miss.bytes > 0);
+
+        // Repeated reads of the same block are hits.
+        for _ in 0..3 {
+            sstable_store
+                .block(&sst, 0, CachePolicy::Fill)
+                .await
+                .unwrap();
+        }
+        let after_hits = sstable_store.stats();
+        assert_eq!(after_hits.hit_count, after_miss.hit_count + 3);
+        assert_eq!(after_hits.miss_count, after_miss.miss_count);
+    }
+
+    #[test(tokio::test)]
+    async fn test_sst_info_reports_meta_without_reading_data() {
+        let object_store = Arc::new(MemObjectStore::default());
+        let block_cache = BlockCache::new(65536, Arc::new(LsmTreeMetrics::new(0)));
+        let options = SstableStoreOptions {
+            path: "test".to_string(),
+            object_store,
+            block_cache,
+            meta_cache_capacity: 1024,
+            enable_content_dedup: false,
+        };
+        let sstable_store = SstableStore::new(options);
+        let build_options = SstableBuilderOptions {
+            capacity: 1024,
+            block_capacity: 32,
+            restart_interval: TEST_DEFAULT_RESTART_INTERVAL,
+            bloom_false_positive: 0.1,
+            compression_algorithm: CompressionAlgorithm::Lz4,
+            dictionary: vec![],
+            compression_level: 0,
+            level: 0,
+            parallel_bloom_build: false,
+            value_separation_threshold: 0,
+            blob_id: 0,
+        };
+        let mut builder = SstableBuilder::new(build_options.clone());
+        builder.add(b"k01", 1, Some(b"v01")).unwrap();
+        builder.add(b"k02", 2, Some(b"v02")).unwrap();
+        builder.add(b"k04", 4, Some(b"v04")).unwrap();
+        builder.add(b"k05", 5, Some(b"v05")).unwrap();
+        let (meta, data) = builder.build().unwrap();
+        let sst = Sstable::new(1, Arc::new(meta));
+        sstable_store
+            .put(&sst, data, CachePolicy::Disable)
+            .await
+            .unwrap();
+
+        let info = sstable_store.sst_info(1).await.unwrap();
+        assert_eq!(info.id, 1);
+        assert_eq!(info.block_count, sst.blocks_len());
+        assert_eq!(info.first_key, Some(sst.first_key().to_vec()));
+        assert_eq!(info.last_key, Some(sst.last_key().to_vec()));
+        assert_eq!(info.compression_algorithm, build_options.compression_algorithm);
+        assert_eq!(info.data_size, sst.data_size());
+        assert_eq!(info.data_checksum, sst.data_checksum());
+        assert_eq!(info.dictionary_len, sst.dictionary().len());
+        assert!(info.bloom_filter_len > 0);
+        assert!(info.bloom_num_hashes.is_some());
+    }
+
+    #[test(tokio::test)]
+    async fn test_large_values_round_trip_through_blob_files_and_survive_compaction() {
+        let object_store = Arc::new(MemObjectStore::default());
+        let block_cache = BlockCache::new(65536, Arc::new(LsmTreeMetrics::new(0)));
+        let sstable_store = SstableStore::new(SstableStoreOptions {
+            path: "test".to_string(),
+            object_store,
+            block_cache,
+            meta_cache_capacity: 1024,
+            enable_content_dedup: false,
+        });
+
+        let blob_id = 7;
+        let large_v01 = vec![b'a'; 256];
+        let large_v02 = vec![b'b'; 256];
+        let build_options = SstableBuilderOptions {
+            capacity: 1024,
+            block_capacity: 32,
+            restart_interval: TEST_DEFAULT_RESTART_INTERVAL,
+            bloom_false_positive: 0.1,
+            compression_algorithm: CompressionAlgorithm::None,
+            dictionary: vec![],
+            compression_level: 0,
+            level: 0,
+            parallel_bloom_build: false,
+            value_separation_threshold: 128,
+            blob_id,
+        };
+        let mut builder = SstableBuilder::new(build_options);
+        builder.add(b"k01", 1, Some(&large_v01)).unwrap();
+        builder.add(b"k02", 2, Some(&large_v02)).unwrap();
+        // Below the threshold: stored inline, not separated.
+        builder.add(b"k03", 3, Some(b"small")).unwrap();
+        let blob_data = builder.blob_data().to_vec();
+        let (meta, data) = builder.build().unwrap();
+        sstable_store.put_blob(blob_id, blob_data).await.unwrap();
+        let sst = Sstable::new(1, Arc::new(meta));
+        sstable_store
+            .put(&sst, data.clone(), CachePolicy::Disable)
+            .await
+            .unwrap();
+
+        // The large values round-trip through the blob object, not the block.
+        let mut entries = vec![];
+        for (block_idx, _) in sst.block_metas_iter().enumerate() {
+            let block = sstable_store
+                .block(&sst, block_idx, CachePolicy::Disable)
+                .await
+                .unwrap();
+            let mut iter = crate::iterator::BlockIterator::new(block);
+            iter.seek(crate::iterator::Seek::First).unwrap();
+            while iter.is_valid() {
+                entries.push((iter.key().to_vec(), iter.value().to_vec()));
+                iter.next().unwrap();
+            }
+        }
+        assert_eq!(entries.len(), 3);
+
+        let mut blob_refs = vec![];
+        for (key, raw) in &entries {
+            match crate::utils::decode_entry(raw) {
+                crate::utils::RawValue::BlobRef(blob_ref) => {
+                    let fetched = sstable_store.blob_range(&blob_ref).await.unwrap();
+                    let expected = if crate::utils::user_key(key) == b"k01" {
+                        &large_v01
+                    } else {
+                        &large_v02
+                    };
+                    assert_eq!(&fetched, expected);
+                    blob_refs.push((key.clone(), blob_ref));
+                }
+                crate::utils::RawValue::Put(v) => {
+                    assert_eq!(crate::utils::user_key(key), b"k03");
+                    assert_eq!(v, b"small");
+                }
+                crate::utils::RawValue::Delete => panic!("unexpected tombstone"),
+            }
+        }
+        assert_eq!(blob_refs.len(), 2);
+
+        // Simulate compaction: copy every entry from the source sstable into a new builder via
+        // `add_entry`, as compaction would when merging inputs. A blob-separated value's pointer
+        // is copied forward verbatim, never re-separated or rewritten.
+        let mut compacted_builder = SstableBuilder::new(SstableBuilderOptions {
+            capacity: 1024,
+            block_capacity: 32,
+            restart_interval: TEST_DEFAULT_RESTART_INTERVAL,
+            bloom_false_positive: 0.1,
+            compression_algorithm: CompressionAlgorithm::None,
+            dictionary: vec![],
+            compression_level: 0,
+            level: 0,
+            parallel_bloom_build: false,
+            value_separation_threshold: 128,
+            blob_id: blob_id + 1,
+        });
+        for (key, raw) in &entries {
+            compacted_builder
+                .add_entry(
+                    crate::utils::user_key(key),
+                    crate::utils::sequence(key),
+                    crate::utils::decode_entry(raw),
+                )
+                .unwrap();
+        }
+        // Nothing was appended to the new builder's own blob buffer: every separated value came
+        // in as an already-encoded `BlobRef`, copied through `add_raw` without re-running the
+        // threshold check that would otherwise re-separate it into `blob_id + 1`.
+        assert!(compacted_builder.blob_data().is_empty());
+        let (compacted_meta, compacted_data) = compacted_builder.build().unwrap();
+
+        for (block_idx, _) in compacted_meta.block_metas.iter().enumerate() {
+            let range = compacted_meta.block_metas[block_idx].data_range();
+            let block = crate::components::Block::decode(&compacted_data[range], &[]).unwrap();
+            let mut iter = crate::iterator::BlockIterator::new(Arc::new(block));
+            iter.seek(crate::iterator::Seek::First).unwrap();
+            while iter.is_valid() {
+                if let crate::utils::RawValue::BlobRef(blob_ref) =
+                    crate::utils::decode_entry(iter.value())
+                {
+                    // Still points at the original blob object, not a new one written by the
+                    // "compaction" above.
+                    assert_eq!(blob_ref.blob_id, blob_id);
+                    let fetched = sstable_store.blob_range(&blob_ref).await.unwrap();
+                    let expected = if crate::utils::user_key(iter.key()) == b"k01" {
+                        &large_v01
+                    } else {
+                        &large_v02
+                    };
+                    assert_eq!(&fetched, expected);
+                }
+                iter.next().unwrap();
+            }
+        }
+    }
 }
@@ -4,7 +4,7 @@ use std::sync::Arc;
 
 use super::Seek;
 use crate::components::{Block, KeyPrefix};
-use crate::utils::compare_full_key;
+use crate::utils::{compare_full_key, full_key, user_key};
 use crate::Result;
 
 /// [`BlockIterator`] is used to read kv pairs in a block.
@@ -194,6 +194,12 @@ impl BlockIterator {
                 self.prev_until_key(key);
                 self.is_valid() && self.key() == key
             }
+            Seek::AtSequence { key, sequence } => {
+                let target = full_key(key, sequence);
+                self.seek_restart_point_by_key(&target);
+                self.next_until_key(&target);
+                self.is_valid() && user_key(self.key()) == key
+            }
         };
         Ok(found)
     }
@@ -252,7 +258,7 @@ pub mod tests {
         builder.add(&full_key(b"k04", 4), b"v04");
         builder.add(&full_key(b"k05", 5), b"v05");
         let buf = builder.build();
-        BlockIterator::new(Arc::new(Block::decode(&buf).unwrap()))
+        BlockIterator::new(Arc::new(Block::decode(&buf, &[]).unwrap()))
     }
 
     #[test]
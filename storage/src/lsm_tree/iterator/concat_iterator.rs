@@ -3,7 +3,7 @@ use std::cmp::Ordering;
 use async_trait::async_trait;
 
 use super::{BoxedIterator, Iterator, Seek};
-use crate::utils::compare_full_key;
+use crate::utils::{compare_full_key, full_key, user_key};
 use crate::Result;
 
 pub struct ConcatIterator {
@@ -174,6 +174,11 @@ impl Iterator for ConcatIterator {
                 self.prev_until_key(key).await?;
                 self.is_valid() && self.key() == key
             }
+            Seek::AtSequence { key, sequence } => {
+                let target = full_key(key, sequence);
+                self.binary_seek(&target).await?;
+                self.is_valid() && user_key(self.key()) == key
+            }
         };
         Ok(found)
     }
@@ -210,7 +215,7 @@ mod tests {
             );
         }
         let buf = builder.build();
-        Arc::new(Block::decode(&buf).unwrap())
+        Arc::new(Block::decode(&buf, &[]).unwrap())
     }
 
     #[test(tokio::test)]
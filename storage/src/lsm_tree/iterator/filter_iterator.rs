@@ -0,0 +1,180 @@
+use async_trait::async_trait;
+
+use super::{BoxedIterator, Iterator, Seek};
+use crate::utils::user_key;
+use crate::Result;
+
+/// Wraps a [`BoxedIterator`], skipping entries whose user key doesn't satisfy `predicate`.
+/// Composable over any full-key iterator, e.g. to push a server-side key filter down to the scan
+/// layer instead of shipping every key across the wire first.
+///
+/// Note: like [`super::ConcatIterator`] and unlike [`super::UserKeyIterator`], this operates on
+/// full keys as-is, so `predicate` may see multiple versions of a matching user key.
+pub struct FilterIterator {
+    iter: BoxedIterator,
+    predicate: Box<dyn Fn(&[u8]) -> bool + Send + Sync>,
+}
+
+impl FilterIterator {
+    pub fn new(
+        iter: BoxedIterator,
+        predicate: impl Fn(&[u8]) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            iter,
+            predicate: Box::new(predicate),
+        }
+    }
+
+    fn matches(&self) -> bool {
+        self.iter.is_valid() && (self.predicate)(user_key(self.iter.key()))
+    }
+
+    /// Note: Ensure that the current state is valid.
+    async fn skip_forward(&mut self) -> Result<()> {
+        while self.iter.is_valid() && !self.matches() {
+            self.iter.next().await?;
+        }
+        Ok(())
+    }
+
+    /// Note: Ensure that the current state is valid.
+    async fn skip_backward(&mut self) -> Result<()> {
+        while self.iter.is_valid() && !self.matches() {
+            self.iter.prev().await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Iterator for FilterIterator {
+    async fn next(&mut self) -> Result<()> {
+        assert!(self.is_valid());
+        self.iter.next().await?;
+        self.skip_forward().await
+    }
+
+    async fn prev(&mut self) -> Result<()> {
+        assert!(self.is_valid());
+        self.iter.prev().await?;
+        self.skip_backward().await
+    }
+
+    fn key(&self) -> &[u8] {
+        assert!(self.is_valid());
+        self.iter.key()
+    }
+
+    fn value(&self) -> &[u8] {
+        assert!(self.is_valid());
+        self.iter.value()
+    }
+
+    fn is_valid(&self) -> bool {
+        self.iter.is_valid()
+    }
+
+    async fn seek<'s>(&mut self, seek: Seek<'s>) -> Result<bool> {
+        let backward = matches!(seek, Seek::Last | Seek::RandomBackward(_));
+        self.iter.seek(seek).await?;
+        if backward {
+            self.skip_backward().await?;
+        } else {
+            self.skip_forward().await?;
+        }
+        Ok(self.is_valid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+    use crate::components::{Block, BlockBuilder, BlockBuilderOptions};
+    use crate::iterator::tests::AsyncBlockIterator;
+    use crate::utils::full_key;
+
+    fn build_iterator_for_test(
+        predicate: impl Fn(&[u8]) -> bool + Send + Sync + 'static,
+    ) -> FilterIterator {
+        let options = BlockBuilderOptions::default();
+        let mut builder = BlockBuilder::new(options);
+        for i in 1..=9 {
+            builder.add(
+                &full_key(format!("k{:02}", i).as_bytes(), i as u64),
+                format!("v{:02}", i).as_bytes(),
+            );
+        }
+        let buf = builder.build();
+        let block = std::sync::Arc::new(Block::decode(&buf, &[]).unwrap());
+        FilterIterator::new(Box::new(AsyncBlockIterator::new(block)), predicate)
+    }
+
+    /// Matches only even-numbered keys, so tests can exercise skipping runs of non-matching
+    /// entries in both directions.
+    fn even_key(key: &[u8]) -> bool {
+        let n: usize = std::str::from_utf8(&key[1..]).unwrap().parse().unwrap();
+        n % 2 == 0
+    }
+
+    #[test(tokio::test)]
+    async fn test_seek_first_skips_non_matching_leading_entries() {
+        let mut it = build_iterator_for_test(even_key);
+        assert!(it.seek(Seek::First).await.unwrap());
+        assert_eq!(&full_key(b"k02", 2)[..], it.key());
+    }
+
+    #[test(tokio::test)]
+    async fn test_seek_last_skips_non_matching_trailing_entries() {
+        let mut it = build_iterator_for_test(even_key);
+        assert!(it.seek(Seek::Last).await.unwrap());
+        assert_eq!(&full_key(b"k08", 8)[..], it.key());
+    }
+
+    #[test(tokio::test)]
+    async fn test_random_forward_skips_to_next_matching_entry() {
+        let mut it = build_iterator_for_test(even_key);
+        assert!(it.seek(Seek::RandomForward(&full_key(b"k03", 3))).await.unwrap());
+        assert_eq!(&full_key(b"k04", 4)[..], it.key());
+    }
+
+    #[test(tokio::test)]
+    async fn test_random_backward_skips_to_prev_matching_entry() {
+        let mut it = build_iterator_for_test(even_key);
+        assert!(it.seek(Seek::RandomBackward(&full_key(b"k03", 3))).await.unwrap());
+        assert_eq!(&full_key(b"k02", 2)[..], it.key());
+    }
+
+    #[test(tokio::test)]
+    async fn test_forward_iterate_only_yields_matching_entries() {
+        let mut it = build_iterator_for_test(even_key);
+        it.seek(Seek::First).await.unwrap();
+        for i in [2, 4, 6, 8] {
+            assert!(it.is_valid());
+            assert_eq!(&full_key(format!("k{:02}", i).as_bytes(), i)[..], it.key());
+            it.next().await.unwrap();
+        }
+        assert!(!it.is_valid());
+    }
+
+    #[test(tokio::test)]
+    async fn test_backward_iterate_only_yields_matching_entries() {
+        let mut it = build_iterator_for_test(even_key);
+        it.seek(Seek::Last).await.unwrap();
+        for i in [8, 6, 4, 2] {
+            assert!(it.is_valid());
+            assert_eq!(&full_key(format!("k{:02}", i).as_bytes(), i)[..], it.key());
+            it.prev().await.unwrap();
+        }
+        assert!(!it.is_valid());
+    }
+
+    #[test(tokio::test)]
+    async fn test_predicate_matching_nothing_is_never_valid() {
+        let mut it = build_iterator_for_test(|_| false);
+        assert!(!it.seek(Seek::First).await.unwrap());
+        assert!(!it.is_valid());
+    }
+}
@@ -155,6 +155,17 @@ impl Iterator for MemtableIterator {
                 self.iter.seek_for_prev(&full_key(key, 0));
                 self.prev_inner(key)
             }
+            Seek::AtSequence { key, sequence } => {
+                self.key.clear();
+                // Never expose versions newer than what this iterator's own snapshot allows.
+                let visible_sequence = std::cmp::min(self.sequence, sequence);
+                self.iter.seek(&full_key(key, visible_sequence));
+                let saved_sequence = self.sequence;
+                self.sequence = visible_sequence;
+                let found = self.next_inner(key);
+                self.sequence = saved_sequence;
+                found
+            }
         };
         Ok(found)
     }
@@ -3,16 +3,29 @@ use std::collections::binary_heap::{BinaryHeap, PeekMut};
 use std::collections::LinkedList;
 
 use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
 
 use super::{BoxedIterator, Iterator, Seek};
 use crate::Result;
 
+/// Default concurrency used by [`MergeIterator::new`], preserving the historical one-at-a-time
+/// seek behavior.
+const DEFAULT_PREFETCH_CONCURRENCY: usize = 1;
+
 #[derive(PartialEq, Debug)]
 enum Direction {
     Forward,
     Backward,
 }
 
+/// Merges multiple ordered iterators into a single ordered stream.
+///
+/// Ordering (including tie-breaking when the same user key appears in more than one input) comes
+/// entirely from [`BoxedIterator`]'s `Ord` impl, i.e. `(user_key asc, sequence desc)`. That means
+/// when several inputs hold different versions of the same user key, the version with the largest
+/// sequence number is always emitted first, followed by the others in descending sequence order.
+/// Callers that dedup by user key while scanning a merged stream (e.g. the exhauster's compaction
+/// path) can rely on the first occurrence of a user key being its newest version.
 pub struct MergeIterator {
     /// Current direction.
     direction: Direction,
@@ -26,19 +39,49 @@ pub struct MergeIterator {
     ///
     /// `max_heap` is ensured not empty when valid and backward.
     max_heap: BinaryHeap<BoxedIterator>,
+    /// Max number of child iterators allowed to seek (and therefore fetch their underlying block)
+    /// concurrently when the heap is rebuilt. Bounds memory blown up by having too many blocks
+    /// in flight at once while still overlapping I/O across child iterators.
+    concurrency: usize,
 }
 
 impl MergeIterator {
     pub fn new(iters: Vec<BoxedIterator>) -> Self {
+        Self::new_with_concurrency(iters, DEFAULT_PREFETCH_CONCURRENCY)
+    }
+
+    /// Creates a [`MergeIterator`] that seeks up to `concurrency` child iterators concurrently
+    /// when (re)building the heap, instead of awaiting each child one at a time. This overlaps
+    /// per-child block fetch latency, which matters when merging many source SSTs during
+    /// compaction.
+    pub fn new_with_concurrency(iters: Vec<BoxedIterator>, concurrency: usize) -> Self {
         let len = iters.len();
         Self {
             direction: Direction::Forward,
             iters: LinkedList::from_iter(iters.into_iter()),
             min_heap: BinaryHeap::with_capacity(len),
             max_heap: BinaryHeap::with_capacity(len),
+            concurrency: std::cmp::max(1, concurrency),
         }
     }
 
+    /// Seeks every iterator currently parked in `self.iters` with the same [`Seek`], up to
+    /// `self.concurrency` at a time, and returns them so callers can decide how to redistribute
+    /// them between the min/max heaps.
+    async fn prefetch_seek(&mut self, seek: Seek<'_>) -> Result<Vec<BoxedIterator>> {
+        let iters = std::mem::take(&mut self.iters);
+        let concurrency = self.concurrency;
+        stream::iter(iters.into_iter().map(|mut iter| async move {
+            iter.seek(seek).await?;
+            Ok(iter)
+        }))
+        .buffer_unordered(concurrency)
+        .collect::<Vec<Result<BoxedIterator>>>()
+        .await
+        .into_iter()
+        .collect()
+    }
+
     async fn may_rebuild_heap(&mut self, direction: Direction) -> Result<()> {
         if self.direction == direction {
             return Ok(());
@@ -47,24 +90,21 @@ impl MergeIterator {
         self.direction = direction;
         self.iters.extend(self.min_heap.drain().map(|r| r.0));
         self.iters.extend(self.max_heap.drain());
-        for iter in self.iters.iter_mut() {
-            match self.direction {
-                Direction::Forward => {
-                    iter.seek(Seek::RandomForward(&key)).await?;
-                }
-                Direction::Backward => {
-                    iter.seek(Seek::RandomBackward(&key)).await?;
-                }
-            }
-        }
+        let seek = match self.direction {
+            Direction::Forward => Seek::RandomForward(&key),
+            Direction::Backward => Seek::RandomBackward(&key),
+        };
+        let iters = self.prefetch_seek(seek).await?;
         match self.direction {
-            Direction::Forward => {
-                self.min_heap
-                    .extend(self.iters.drain_filter(|iter| iter.is_valid()).map(Reverse));
-            }
+            Direction::Forward => self.min_heap.extend(
+                iters
+                    .into_iter()
+                    .filter(|iter| iter.is_valid())
+                    .map(Reverse),
+            ),
             Direction::Backward => self
                 .max_heap
-                .extend(self.iters.drain_filter(|iter| iter.is_valid())),
+                .extend(iters.into_iter().filter(|iter| iter.is_valid())),
         }
         Ok(())
     }
@@ -90,6 +130,20 @@ impl MergeIterator {
         }
         Ok(())
     }
+
+    /// Returns the current key and value together as a single borrow of `self`.
+    ///
+    /// Equivalent to calling [`Iterator::key`] and [`Iterator::value`] separately, but a caller
+    /// that needs both (e.g. the exhauster's compaction loop) only has to hold one borrow of the
+    /// iterator instead of two, or clone one side to keep the borrow checker happy across both
+    /// calls.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the iterator is invalid.
+    pub fn kv(&self) -> (&[u8], &[u8]) {
+        (self.key(), self.value())
+    }
 }
 
 #[async_trait]
@@ -133,50 +187,55 @@ impl Iterator for MergeIterator {
                 self.direction = Direction::Forward;
                 self.iters.extend(self.min_heap.drain().map(|r| r.0));
                 self.iters.extend(self.max_heap.drain());
-                while !self.iters.is_empty() {
-                    let mut iter = self.iters.pop_back().unwrap();
-                    iter.seek(Seek::First).await?;
-                    self.min_heap.push(Reverse(iter));
-                }
+                let iters = self.prefetch_seek(Seek::First).await?;
+                self.min_heap.extend(iters.into_iter().map(Reverse));
                 self.is_valid()
             }
             Seek::Last => {
                 self.direction = Direction::Backward;
                 self.iters.extend(self.min_heap.drain().map(|r| r.0));
                 self.iters.extend(self.max_heap.drain());
-                while !self.iters.is_empty() {
-                    let mut iter = self.iters.pop_back().unwrap();
-                    iter.seek(Seek::Last).await?;
-                    self.max_heap.push(iter);
-                }
+                let iters = self.prefetch_seek(Seek::Last).await?;
+                self.max_heap.extend(iters);
                 self.is_valid()
             }
             Seek::RandomForward(key) => {
                 self.direction = Direction::Forward;
                 self.iters.extend(self.min_heap.drain().map(|r| r.0));
                 self.iters.extend(self.max_heap.drain());
-                while !self.iters.is_empty() {
-                    let mut iter = self.iters.pop_back().unwrap();
-                    iter.seek(Seek::RandomForward(key)).await?;
-                    if iter.is_valid() {
-                        self.min_heap.push(Reverse(iter));
-                    }
-                }
+                let iters = self.prefetch_seek(Seek::RandomForward(key)).await?;
+                self.min_heap.extend(
+                    iters
+                        .into_iter()
+                        .filter(|iter| iter.is_valid())
+                        .map(Reverse),
+                );
                 self.is_valid() && self.key() == key
             }
             Seek::RandomBackward(key) => {
                 self.direction = Direction::Backward;
                 self.iters.extend(self.min_heap.drain().map(|r| r.0));
                 self.iters.extend(self.max_heap.drain());
-                while !self.iters.is_empty() {
-                    let mut iter = self.iters.pop_back().unwrap();
-                    iter.seek(Seek::RandomBackward(key)).await?;
-                    if iter.is_valid() {
-                        self.max_heap.push(iter);
-                    }
-                }
+                let iters = self.prefetch_seek(Seek::RandomBackward(key)).await?;
+                self.max_heap
+                    .extend(iters.into_iter().filter(|iter| iter.is_valid()));
                 self.is_valid() && self.key() == key
             }
+            Seek::AtSequence { key, sequence } => {
+                self.direction = Direction::Forward;
+                self.iters.extend(self.min_heap.drain().map(|r| r.0));
+                self.iters.extend(self.max_heap.drain());
+                let iters = self
+                    .prefetch_seek(Seek::AtSequence { key, sequence })
+                    .await?;
+                self.min_heap.extend(
+                    iters
+                        .into_iter()
+                        .filter(|iter| iter.is_valid())
+                        .map(Reverse),
+                );
+                self.is_valid() && crate::utils::user_key(self.key()) == key
+            }
         };
         Ok(found)
     }
@@ -212,7 +271,7 @@ mod tests {
             );
         }
         let buf = builder.build();
-        Arc::new(Block::decode(&buf).unwrap())
+        Arc::new(Block::decode(&buf, &[]).unwrap())
     }
 
     #[test(tokio::test)]
@@ -222,6 +281,15 @@ mod tests {
         assert_eq!(&full_key(b"k01", 1)[..], it.key());
     }
 
+    #[test(tokio::test)]
+    async fn test_kv_matches_key_and_value() {
+        let mut it = build_iterator_for_test();
+        it.seek(Seek::First).await.unwrap();
+        let (key, value) = it.kv();
+        assert_eq!(key, it.key());
+        assert_eq!(value, it.value());
+    }
+
     #[test(tokio::test)]
     async fn test_seek_last() {
         let mut it = build_iterator_for_test();
@@ -307,6 +375,40 @@ mod tests {
         assert!(!it.is_valid())
     }
 
+    #[test(tokio::test)]
+    async fn test_tie_break_by_sequence() {
+        // Three inputs each hold a different version of the same user key `k01`, at sequences 1,
+        // 2 and 3 respectively. The merged stream must emit the newest version first, then the
+        // rest in descending sequence order.
+        let build_single_version_block = |key: &[u8], sequence: u64, value: &[u8]| {
+            let options = BlockBuilderOptions::default();
+            let mut builder = BlockBuilder::new(options);
+            builder.add(&full_key(key, sequence), &Bytes::from(value.to_vec()));
+            Arc::new(Block::decode(&builder.build(), &[]).unwrap())
+        };
+
+        let mut it = MergeIterator::new(vec![
+            Box::new(AsyncBlockIterator::new(build_single_version_block(
+                b"k01", 1, b"v01-1",
+            ))),
+            Box::new(AsyncBlockIterator::new(build_single_version_block(
+                b"k01", 3, b"v01-3",
+            ))),
+            Box::new(AsyncBlockIterator::new(build_single_version_block(
+                b"k01", 2, b"v01-2",
+            ))),
+        ]);
+
+        it.seek(Seek::First).await.unwrap();
+        for (sequence, value) in [(3, "v01-3"), (2, "v01-2"), (1, "v01-1")] {
+            assert!(it.is_valid());
+            assert_eq!(&full_key(b"k01", sequence)[..], it.key());
+            assert_eq!(value.as_bytes(), it.value());
+            it.next().await.unwrap();
+        }
+        assert!(!it.is_valid());
+    }
+
     #[test(tokio::test)]
     async fn test_seek_forward_backward_iterate() {
         let mut it = build_iterator_for_test();
@@ -328,4 +430,72 @@ mod tests {
         it.next().await.unwrap();
         assert_eq!(&full_key(format!("k{:02}", 6).as_bytes(), 6)[..], it.key());
     }
+
+    /// Wraps an iterator and adds artificial latency to every `seek`, simulating a slow block
+    /// fetch (e.g. a cache miss that falls through to the object store).
+    struct LatencyIterator {
+        inner: BoxedIterator,
+        latency: std::time::Duration,
+    }
+
+    #[async_trait]
+    impl Iterator for LatencyIterator {
+        async fn next(&mut self) -> Result<()> {
+            self.inner.next().await
+        }
+
+        async fn prev(&mut self) -> Result<()> {
+            self.inner.prev().await
+        }
+
+        fn key(&self) -> &[u8] {
+            self.inner.key()
+        }
+
+        fn value(&self) -> &[u8] {
+            self.inner.value()
+        }
+
+        fn is_valid(&self) -> bool {
+            self.inner.is_valid()
+        }
+
+        async fn seek<'s>(&mut self, seek: Seek<'s>) -> Result<bool> {
+            tokio::time::sleep(self.latency).await;
+            self.inner.seek(seek).await
+        }
+    }
+
+    fn build_latent_iterators(n: usize, latency: std::time::Duration) -> Vec<BoxedIterator> {
+        (0..n)
+            .map(|i| {
+                let inner: BoxedIterator =
+                    Box::new(AsyncBlockIterator::new(build_block_for_test(&[i])));
+                Box::new(LatencyIterator { inner, latency }) as BoxedIterator
+            })
+            .collect()
+    }
+
+    #[test(tokio::test)]
+    async fn test_bounded_concurrent_prefetch_reduces_latency() {
+        const N: usize = 5;
+        const LATENCY: std::time::Duration = std::time::Duration::from_millis(20);
+
+        let mut sequential = MergeIterator::new(build_latent_iterators(N, LATENCY));
+        let start = std::time::Instant::now();
+        sequential.seek(Seek::First).await.unwrap();
+        let sequential_elapsed = start.elapsed();
+
+        let mut concurrent = MergeIterator::new_with_concurrency(build_latent_iterators(N, LATENCY), N);
+        let start = std::time::Instant::now();
+        concurrent.seek(Seek::First).await.unwrap();
+        let concurrent_elapsed = start.elapsed();
+
+        assert!(
+            concurrent_elapsed * 2 < sequential_elapsed,
+            "expected concurrent prefetch ({:?}) to be markedly faster than sequential ({:?})",
+            concurrent_elapsed,
+            sequential_elapsed,
+        );
+    }
 }
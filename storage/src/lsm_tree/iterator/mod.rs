@@ -1,5 +1,6 @@
 mod block_iterator;
 mod concat_iterator;
+mod filter_iterator;
 mod memtable_iterator;
 mod merge_iterator;
 mod sstable_iterator;
@@ -8,6 +9,7 @@ mod user_key_iterator;
 use async_trait::async_trait;
 pub use block_iterator::*;
 pub use concat_iterator::*;
+pub use filter_iterator::*;
 pub use memtable_iterator::*;
 pub use merge_iterator::*;
 pub use sstable_iterator::*;
@@ -16,6 +18,7 @@ pub use user_key_iterator::*;
 use crate::utils::compare_full_key;
 use crate::Result;
 
+#[derive(Clone, Copy)]
 pub enum Seek<'s> {
     /// Seek to the first valid position in order if exists.
     First,
@@ -25,6 +28,10 @@ pub enum Seek<'s> {
     RandomForward(&'s [u8]),
     /// Seek backward for the first key equals the given key or the first key smaller than it.
     RandomBackward(&'s [u8]),
+    /// Seek for the newest version of `key` visible as of `sequence`, i.e. the version with the
+    /// largest sequence number not greater than `sequence`, skipping newer versions. Enables
+    /// snapshot ("time-travel") reads without the caller having to track MVCC state itself.
+    AtSequence { key: &'s [u8], sequence: u64 },
 }
 
 /// [`Iterator`] defines shared behaviours for all iterators.
@@ -128,6 +135,11 @@ impl PartialOrd for BoxedIterator {
 impl Ord for BoxedIterator {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         // Should not be used on `UserKeyIterator`
+        //
+        // `compare_full_key` orders by `(user_key asc, sequence desc)`, so for equal user keys the
+        // iterator holding the newest (largest sequence) version sorts first. `MergeIterator` and
+        // the exhauster's compaction dedup (`last_user_key`) both depend on this ordering to pick
+        // the newest version of a key when multiple inputs overlap.
         compare_full_key(self.key(), other.key())
     }
 }
@@ -4,7 +4,7 @@ use async_trait::async_trait;
 
 use super::{BlockIterator, Iterator, Seek};
 use crate::components::{CachePolicy, Sstable, SstableStoreRef};
-use crate::utils::compare_full_key;
+use crate::utils::{compare_full_key, full_key, user_key};
 use crate::Result;
 
 pub struct SstableIterator {
@@ -212,6 +212,11 @@ impl Iterator for SstableIterator {
                 self.prev_until_key(key).await?;
                 self.is_valid() && self.key() == key
             }
+            Seek::AtSequence { key, sequence } => {
+                let target = full_key(key, sequence);
+                self.binary_seek(&target).await?;
+                self.is_valid() && user_key(self.key()) == key
+            }
         };
         Ok(found)
     }
@@ -240,6 +245,12 @@ mod tests {
             restart_interval: TEST_DEFAULT_RESTART_INTERVAL,
             bloom_false_positive: 0.1,
             compression_algorithm: CompressionAlgorithm::Lz4,
+            dictionary: vec![],
+            compression_level: 0,
+            level: 0,
+            parallel_bloom_build: false,
+            value_separation_threshold: 0,
+            blob_id: 0,
         };
         let mut builder = SstableBuilder::new(options);
         builder.add(b"k01", 1, Some(b"v01")).unwrap();
@@ -261,6 +272,7 @@ mod tests {
             object_store,
             block_cache,
             meta_cache_capacity: 1024,
+            enable_content_dedup: false,
         };
         let sstable_store = Arc::new(SstableStore::new(options));
         let (meta, data) = build_sstable_for_test();
@@ -360,6 +372,113 @@ mod tests {
         assert!(!it.is_valid())
     }
 
+    fn build_multi_version_sstable_for_test() -> (SstableMeta, Vec<u8>) {
+        let options = SstableBuilderOptions {
+            capacity: 1024,
+            block_capacity: 1024,
+            restart_interval: TEST_DEFAULT_RESTART_INTERVAL,
+            bloom_false_positive: 0.1,
+            compression_algorithm: CompressionAlgorithm::Lz4,
+            dictionary: vec![],
+            compression_level: 0,
+            level: 0,
+            parallel_bloom_build: false,
+            value_separation_threshold: 0,
+            blob_id: 0,
+        };
+        let mut builder = SstableBuilder::new(options);
+        builder.add(b"k01", 1, Some(b"v01")).unwrap();
+        builder.add(b"k02", 2, Some(b"v02")).unwrap();
+        // Multiple versions of `k03`, newest sequence first (required full key order).
+        builder.add(b"k03", 10, Some(b"v03-10")).unwrap();
+        builder.add(b"k03", 5, Some(b"v03-5")).unwrap();
+        builder.add(b"k03", 2, Some(b"v03-2")).unwrap();
+        builder.add(b"k04", 4, Some(b"v04")).unwrap();
+        builder.build().unwrap()
+    }
+
+    async fn build_multi_version_iterator_for_test() -> SstableIterator {
+        let object_store = Arc::new(MemObjectStore::default());
+        let block_cache = BlockCache::new(65536, Arc::new(LsmTreeMetrics::new(0)));
+        let options = SstableStoreOptions {
+            path: "test".to_string(),
+            object_store,
+            block_cache,
+            meta_cache_capacity: 1024,
+            enable_content_dedup: false,
+        };
+        let sstable_store = Arc::new(SstableStore::new(options));
+        let (meta, data) = build_multi_version_sstable_for_test();
+        let sstable = Sstable::new(1, Arc::new(meta));
+        sstable_store
+            .put(&sstable, data, CachePolicy::Fill)
+            .await
+            .unwrap();
+        SstableIterator::new(sstable_store, sstable, CachePolicy::Fill)
+    }
+
+    #[test(tokio::test)]
+    async fn test_seek_at_sequence() {
+        // Newer than any version of `k03`: newest version wins.
+        let mut it = build_multi_version_iterator_for_test().await;
+        assert!(it
+            .seek(Seek::AtSequence {
+                key: b"k03",
+                sequence: 100,
+            })
+            .await
+            .unwrap());
+        assert_eq!(&full_key(b"k03", 10)[..], it.key());
+        assert_eq!(b"v03-10", it.value());
+
+        // Between sequence 5 and 10: sequence 5 is the newest visible version.
+        let mut it = build_multi_version_iterator_for_test().await;
+        assert!(it
+            .seek(Seek::AtSequence {
+                key: b"k03",
+                sequence: 7,
+            })
+            .await
+            .unwrap());
+        assert_eq!(&full_key(b"k03", 5)[..], it.key());
+        assert_eq!(b"v03-5", it.value());
+
+        // Exactly at sequence 5: that version itself is visible.
+        let mut it = build_multi_version_iterator_for_test().await;
+        assert!(it
+            .seek(Seek::AtSequence {
+                key: b"k03",
+                sequence: 5,
+            })
+            .await
+            .unwrap());
+        assert_eq!(&full_key(b"k03", 5)[..], it.key());
+        assert_eq!(b"v03-5", it.value());
+
+        // Between sequence 2 and 5: sequence 2 is the newest visible version.
+        let mut it = build_multi_version_iterator_for_test().await;
+        assert!(it
+            .seek(Seek::AtSequence {
+                key: b"k03",
+                sequence: 3,
+            })
+            .await
+            .unwrap());
+        assert_eq!(&full_key(b"k03", 2)[..], it.key());
+        assert_eq!(b"v03-2", it.value());
+
+        // Older than any version of `k03`: not found, lands on the next key in order.
+        let mut it = build_multi_version_iterator_for_test().await;
+        assert!(!it
+            .seek(Seek::AtSequence {
+                key: b"k03",
+                sequence: 1,
+            })
+            .await
+            .unwrap());
+        assert_eq!(&full_key(b"k04", 4)[..], it.key());
+    }
+
     #[test(tokio::test)]
     async fn test_seek_forward_backward_iterate() {
         let mut it = build_iterator_for_test().await;
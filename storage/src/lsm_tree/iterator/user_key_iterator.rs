@@ -156,6 +156,19 @@ impl Iterator for UserKeyIterator {
                     .await?;
                 self.prev_inner(key).await?
             }
+            Seek::AtSequence { key, sequence } => {
+                self.key.clear();
+                // Never expose versions newer than what this iterator's own snapshot allows.
+                let visible_sequence = std::cmp::min(self.sequence, sequence);
+                self.iter
+                    .seek(Seek::RandomForward(&full_key(key, visible_sequence)))
+                    .await?;
+                let saved_sequence = self.sequence;
+                self.sequence = visible_sequence;
+                let found = self.next_inner(key).await?;
+                self.sequence = saved_sequence;
+                found
+            }
         };
         Ok(found)
     }
@@ -183,6 +196,7 @@ mod tests {
             object_store,
             block_cache,
             meta_cache_capacity: 1024,
+            enable_content_dedup: false,
         };
         let sstable_store = Arc::new(SstableStore::new(options));
 
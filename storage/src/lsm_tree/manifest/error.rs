@@ -10,6 +10,8 @@ pub enum ManifestError {
     LevelNotExists(u64, u64),
     #[error("invalid watermark: [current: {0}] [given: {1}]")]
     InvalidWatermark(u64, u64),
+    #[error("manifest edit log subscription lagged, {0} diff(s) skipped")]
+    SubscriptionLagged(u64),
     #[error("other: {0}")]
     Other(String),
 }
@@ -1,11 +1,12 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::ops::{Range, RangeInclusive};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
+use itertools::Itertools;
 use runkv_common::coding::CompressionAlgorithm;
 use runkv_common::config::{LevelCompactionStrategy, LevelOptions};
-use runkv_proto::manifest::{SstableOp, VersionDiff};
-use tokio::sync::RwLock;
+use runkv_proto::manifest::{SstableDiff, SstableOp, VersionDiff};
+use tokio::sync::{broadcast, RwLock};
 use tracing::trace;
 
 use super::ManifestError;
@@ -13,6 +14,78 @@ use crate::components::SstableStoreRef;
 use crate::utils::user_key;
 use crate::Result;
 
+/// Whether key range `[a_min, a_max]` overlaps key range `[b_min, b_max]`, both inclusive. Shared
+/// by [`VersionManagerCore::update`]'s non-overlap check and [`level_is_non_overlapping`] so the
+/// inclusive/exclusive boundary logic lives in exactly one place.
+pub fn ranges_overlap(a_min: &[u8], a_max: &[u8], b_min: &[u8], b_max: &[u8]) -> bool {
+    a_min <= b_max && b_min <= a_max
+}
+
+/// Whether `ranges` (each a `(first_key, last_key)` pair, inclusive) are in ASC order by
+/// `first_key` and pairwise non-overlapping, as required of a `NonOverlap` level. Unlike
+/// [`ranges_overlap`], this does not sort its input: a level's sstable ids are expected to already
+/// be stored in ASC order, so a pair that is merely out of order (even if their ranges don't
+/// actually overlap) is itself treated as a violation.
+pub fn level_is_non_overlapping(ranges: &[(&[u8], &[u8])]) -> bool {
+    ranges
+        .iter()
+        .tuple_windows()
+        .all(|(prev, cur)| prev.0 <= cur.0 && !ranges_overlap(prev.0, prev.1, cur.0, cur.1))
+}
+
+/// Default capacity of [`VersionManagerCore::edit_log_tx`]'s broadcast channel. Bounds how many
+/// diffs a subscriber can fall behind on before [`ManifestSubscription::next`] reports it lagged,
+/// rather than silently growing memory unbounded.
+const DEFAULT_MANIFEST_EDIT_LOG_CAPACITY: usize = 1024;
+
+/// A [`VersionManager::subscribe`] subscriber's starting point: the full version as of `id`,
+/// before any [`VersionDiff`] the subscription's stream subsequently delivers. Lets a consumer
+/// reconstruct the current manifest without separately polling it first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionSnapshot {
+    /// Id of the last diff already reflected in `levels`. The stream's first [`VersionDiff`]
+    /// (if any) has an id of `id + 1`.
+    pub id: u64,
+    /// Sst ids of each level, as of `id`.
+    pub levels: Vec<Vec<u64>>,
+}
+
+/// An event delivered by [`VersionManager::subscribe`]'s edit log stream.
+#[derive(Clone, Debug)]
+pub enum ManifestEvent {
+    /// Delivered exactly once, as the stream's first event.
+    Snapshot(VersionSnapshot),
+    /// A single edit applied after the preceding event (the initial [`Self::Snapshot`] or an
+    /// earlier `Diff`).
+    Diff(VersionDiff),
+}
+
+/// Stream of [`ManifestEvent`]s returned by [`VersionManager::subscribe`]. Always yields exactly
+/// one [`ManifestEvent::Snapshot`] first, then forwards every [`VersionDiff`] applied to the
+/// manifest afterwards, so a replication/backup consumer can mirror the LSM topology without
+/// polling the whole manifest.
+pub struct ManifestSubscription {
+    snapshot: Option<VersionSnapshot>,
+    rx: broadcast::Receiver<VersionDiff>,
+}
+
+impl ManifestSubscription {
+    /// Waits for and returns the next event. Returns `Ok(None)` once the originating
+    /// [`VersionManager`] has been dropped, so a consumer's loop ends instead of erroring out.
+    pub async fn next(&mut self) -> Result<Option<ManifestEvent>> {
+        if let Some(snapshot) = self.snapshot.take() {
+            return Ok(Some(ManifestEvent::Snapshot(snapshot)));
+        }
+        match self.rx.recv().await {
+            Ok(diff) => Ok(Some(ManifestEvent::Diff(diff))),
+            Err(broadcast::error::RecvError::Closed) => Ok(None),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                Err(ManifestError::SubscriptionLagged(skipped).into())
+            }
+        }
+    }
+}
+
 pub struct VersionManagerOptions {
     /// Level compaction and compression strategies for each level.
     ///
@@ -47,11 +120,15 @@ pub struct VersionManagerCore {
     sstable_store: SstableStoreRef,
     /// Minimum accessable sequence.
     watermark: u64,
+    /// Broadcasts every applied [`VersionDiff`] to live [`ManifestSubscription`]s. See
+    /// [`VersionManager::subscribe`].
+    edit_log_tx: broadcast::Sender<VersionDiff>,
 }
 
 impl VersionManagerCore {
     fn new(options: VersionManagerOptions) -> Self {
         assert_eq!(options.levels.len(), options.levels_options.len());
+        let (edit_log_tx, _) = broadcast::channel(DEFAULT_MANIFEST_EDIT_LOG_CAPACITY);
         Self {
             level_options: options.levels_options,
             levels_data_size: vec![0; options.levels.len()],
@@ -59,6 +136,7 @@ impl VersionManagerCore {
             diffs: VecDeque::default(),
             sstable_store: options.sstable_store,
             watermark: 0,
+            edit_log_tx,
         }
     }
 
@@ -70,6 +148,22 @@ impl VersionManagerCore {
         self.levels_data_size[level_idx]
     }
 
+    fn level_sstable_count(&self, level_idx: usize) -> usize {
+        self.levels[level_idx].len()
+    }
+
+    async fn sstable_data_size(&self, sst_id: u64) -> Result<u64> {
+        Ok(self.sstable_store.sstable(sst_id).await?.data_size() as u64)
+    }
+
+    async fn sstable_user_key_range(&self, sst_id: u64) -> Result<(Vec<u8>, Vec<u8>)> {
+        let sst = self.sstable_store.sstable(sst_id).await?;
+        Ok((
+            user_key(sst.first_key()).to_vec(),
+            user_key(sst.last_key()).to_vec(),
+        ))
+    }
+
     fn watermark(&self) -> u64 {
         self.watermark
     }
@@ -86,6 +180,11 @@ impl VersionManagerCore {
         self.diffs.back().map_or_else(|| 0, |diff| diff.id)
     }
 
+    /// Applies `diff`'s `sstable_diffs` as a single atomic edit: either every insert/delete in
+    /// the batch takes effect, or (on the first one that fails, e.g. an overlap violation or a
+    /// delete of an sst id that isn't present) none of them do. Without this, a compaction's
+    /// batch of "delete the old ssts, insert the new ones" could fail partway through and leave
+    /// `self.levels` holding neither the pre- nor post-compaction set of ssts.
     async fn update(&mut self, mut diff: VersionDiff, sync: bool) -> Result<()> {
         if sync {
             let current_diff_id = self.diffs.back().map(|diff| diff.id).unwrap_or_else(|| 0);
@@ -97,78 +196,119 @@ impl VersionManagerCore {
             diff.id = diff_id;
         }
 
+        // Apply against scratch copies rather than `self.levels`/`self.levels_data_size`
+        // directly, so a failure partway through only discards the scratch copies, leaving the
+        // real state exactly as it was before this call.
+        let mut levels = self.levels.clone();
+        let mut levels_data_size = self.levels_data_size.clone();
         for sstable_diff in &diff.sstable_diffs {
-            let level = sstable_diff.level as usize;
-            let compaction_strategy = self
-                .level_options
-                .get(level)
-                .ok_or_else(|| {
-                    ManifestError::InvalidVersionDiff(format!("invalid level idx: {}", level))
-                })?
-                .compaction_strategy;
-            match sstable_diff.op() {
-                SstableOp::Insert => {
-                    // TODO: Should check duplicated sst id globally.
-
-                    // TODO: Preform async binary search.
-                    // Find a position to insert new sst id into.
-                    let sst_to_insert = self.sstable_store.sstable(sstable_diff.id).await?;
-                    let mut idx = 0;
-                    while idx < self.levels[level].len() {
-                        let sst = self.sstable_store.sstable(self.levels[level][idx]).await?;
-                        if sst_to_insert.first_key() <= sst.first_key() {
-                            break;
-                        }
-                        idx += 1;
+            self.apply_sstable_diff(&mut levels, &mut levels_data_size, sstable_diff)
+                .await?;
+        }
+        self.levels = levels;
+        self.levels_data_size = levels_data_size;
+
+        self.diffs.push_back(diff.clone());
+        // No live subscribers is not an error, just means nobody's listening right now.
+        let _ = self.edit_log_tx.send(diff);
+        if !sync {
+            trace!("updated levels: {:?}", self.levels);
+            trace!("updated levels size: {:#?}", self.levels_data_size);
+        }
+        Ok(())
+    }
+
+    /// Applies a single [`SstableDiff`] to `levels`/`levels_data_size`. Takes them as explicit
+    /// scratch arguments rather than mutating `self` directly, so [`Self::update`] can apply a
+    /// whole batch to scratch copies and only commit them to `self` once every diff in the batch
+    /// has succeeded.
+    async fn apply_sstable_diff(
+        &self,
+        levels: &mut [Vec<u64>],
+        levels_data_size: &mut [usize],
+        sstable_diff: &SstableDiff,
+    ) -> Result<()> {
+        let level = sstable_diff.level as usize;
+        let compaction_strategy = self
+            .level_options
+            .get(level)
+            .ok_or_else(|| {
+                ManifestError::InvalidVersionDiff(format!("invalid level idx: {}", level))
+            })?
+            .compaction_strategy;
+        match sstable_diff.op() {
+            SstableOp::Insert => {
+                // TODO: Should check duplicated sst id globally.
+
+                // TODO: Preform async binary search.
+                // Find a position to insert new sst id into.
+                let sst_to_insert = self.sstable_store.sstable(sstable_diff.id).await?;
+                let mut idx = 0;
+                while idx < levels[level].len() {
+                    let sst = self.sstable_store.sstable(levels[level][idx]).await?;
+                    if sst_to_insert.first_key() <= sst.first_key() {
+                        break;
                     }
-                    self.levels[level].insert(idx, sstable_diff.id);
-                    self.levels_data_size[level] += sstable_diff.data_size as usize;
-                    if compaction_strategy == LevelCompactionStrategy::NonOverlap {
-                        // Check overlap.
-                        if idx > 0 {
-                            let prev_sst = self
-                                .sstable_store
-                                .sstable(self.levels[level][idx - 1])
-                                .await?;
-                            if sst_to_insert.first_key() <= prev_sst.last_key() {
-                                return Err(ManifestError::InvalidVersionDiff(format!(
-                                        "sst overlaps in non-overlap level: [sst: {}, first_key:{:?}, last_key: {:?}] [sst: {}, first_key:{:?}, last_key: {:?}]",
-                                        self.levels[level][idx - 1],
-                                        prev_sst.first_key(),
-                                        prev_sst.last_key(),
-                                        self.levels[level][idx],
-                                        sst_to_insert.first_key(),
-                                        sst_to_insert.last_key(),
-                                    ))
-                                    .into());
-                            }
+                    idx += 1;
+                }
+                levels[level].insert(idx, sstable_diff.id);
+                levels_data_size[level] += sstable_diff.data_size as usize;
+                if compaction_strategy == LevelCompactionStrategy::NonOverlap {
+                    // Check overlap.
+                    if idx > 0 {
+                        let prev_sst = self.sstable_store.sstable(levels[level][idx - 1]).await?;
+                        if ranges_overlap(
+                            sst_to_insert.first_key(),
+                            sst_to_insert.last_key(),
+                            prev_sst.first_key(),
+                            prev_sst.last_key(),
+                        ) {
+                            return Err(ManifestError::InvalidVersionDiff(format!(
+                                    "sst overlaps in non-overlap level: [sst: {}, first_key:{:?}, last_key: {:?}] [sst: {}, first_key:{:?}, last_key: {:?}]",
+                                    levels[level][idx - 1],
+                                    prev_sst.first_key(),
+                                    prev_sst.last_key(),
+                                    levels[level][idx],
+                                    sst_to_insert.first_key(),
+                                    sst_to_insert.last_key(),
+                                ))
+                                .into());
                         }
                     }
                 }
-                SstableOp::Delete => {
-                    if let Some(idx) = self.levels[level]
-                        .iter()
-                        .position(|&sst_id| sst_id == sstable_diff.id)
-                    {
-                        self.levels[level].remove(idx);
-                        self.levels_data_size[level] -= sstable_diff.data_size as usize;
-                    } else {
-                        return Err(ManifestError::InvalidVersionDiff(format!(
-                            "sst L{}-{} not exists",
-                            level, sstable_diff.id
-                        ))
-                        .into());
-                    }
+            }
+            SstableOp::Delete => {
+                if let Some(idx) = levels[level]
+                    .iter()
+                    .position(|&sst_id| sst_id == sstable_diff.id)
+                {
+                    levels[level].remove(idx);
+                    levels_data_size[level] -= sstable_diff.data_size as usize;
+                } else {
+                    return Err(ManifestError::InvalidVersionDiff(format!(
+                        "sst L{}-{} not exists",
+                        level, sstable_diff.id
+                    ))
+                    .into());
                 }
             }
         }
+        Ok(())
+    }
 
-        self.diffs.push_back(diff);
-        if !sync {
-            trace!("updated levels: {:?}", self.levels);
-            trace!("updated levels size: {:#?}", self.levels_data_size);
+    /// Subscribes to the manifest edit log: the returned [`ManifestSubscription`] first yields a
+    /// [`ManifestEvent::Snapshot`] of the version as of right now, then every [`VersionDiff`]
+    /// applied afterwards. Snapshotting and subscribing happen under the same lock as [`update`],
+    /// so no diff can land in the gap between them and be missed.
+    fn subscribe(&self) -> ManifestSubscription {
+        let snapshot = VersionSnapshot {
+            id: self.latest_version_id(),
+            levels: self.levels.clone(),
+        };
+        ManifestSubscription {
+            snapshot: Some(snapshot),
+            rx: self.edit_log_tx.subscribe(),
         }
-        Ok(())
     }
 
     /// Revoke all version diffs whose id is smaller than given `diff_id`.
@@ -338,14 +478,18 @@ impl VersionManagerCore {
         for level in 0..self.level_options.len() {
             if self.level_compaction_strategy(level as u64).unwrap()
                 == LevelCompactionStrategy::NonOverlap
-                && self.levels[level].len() > 1
             {
-                let prev_sst = self.sstable_store.sstable(self.levels[level][0]).await?;
-                for sst_id in self.levels[level][1..].iter() {
+                let mut ranges = Vec::with_capacity(self.levels[level].len());
+                for sst_id in &self.levels[level] {
                     let sst = self.sstable_store.sstable(*sst_id).await?;
-                    if sst.first_key() <= prev_sst.last_key() {
-                        return Ok(false);
-                    }
+                    ranges.push((sst.first_key().to_vec(), sst.last_key().to_vec()));
+                }
+                let ranges = ranges
+                    .iter()
+                    .map(|(min, max)| (min.as_slice(), max.as_slice()))
+                    .collect_vec();
+                if !level_is_non_overlapping(&ranges) {
+                    return Ok(false);
                 }
             }
         }
@@ -356,12 +500,17 @@ impl VersionManagerCore {
 #[derive(Clone)]
 pub struct VersionManager {
     inner: Arc<RwLock<VersionManagerCore>>,
+    /// Refcounts of sstable ids pinned by in-flight scans, keyed by sst id. Kept in a plain
+    /// [`Mutex`] rather than inside [`VersionManagerCore`]'s `RwLock` because
+    /// [`SstablePinGuard::drop`] unpins synchronously and can't await the async lock.
+    pinned_sstables: Arc<Mutex<HashMap<u64, usize>>>,
 }
 
 impl VersionManager {
     pub fn new(options: VersionManagerOptions) -> Self {
         Self {
             inner: Arc::new(RwLock::new(VersionManagerCore::new(options))),
+            pinned_sstables: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -373,6 +522,28 @@ impl VersionManager {
         self.inner.read().await.level_data_size(level_idx)
     }
 
+    /// Number of sstables in `level_idx`, e.g. for a dashboard reporting sstable distribution
+    /// across levels alongside [`Self::level_data_size`]'s byte totals.
+    pub async fn level_sstable_count(&self, level_idx: usize) -> usize {
+        self.inner.read().await.level_sstable_count(level_idx)
+    }
+
+    /// Get the on-disk data size of a single sstable, e.g. for compaction pickers that need to
+    /// group sstables by size rather than by aggregate level size.
+    pub async fn sstable_data_size(&self, sst_id: u64) -> Result<u64> {
+        self.inner.read().await.sstable_data_size(sst_id).await
+    }
+
+    /// Get the user key range `(min, max)` of a single sstable, e.g. for compaction pickers that
+    /// score candidate sets by key-range overlap.
+    pub async fn sstable_user_key_range(&self, sst_id: u64) -> Result<(Vec<u8>, Vec<u8>)> {
+        self.inner
+            .read()
+            .await
+            .sstable_user_key_range(sst_id)
+            .await
+    }
+
     pub async fn watermark(&self) -> u64 {
         self.inner.read().await.watermark()
     }
@@ -389,6 +560,33 @@ impl VersionManager {
         self.inner.write().await.update(diff, sync).await
     }
 
+    /// Subscribes to the manifest edit log, for consumers like replication/backup that need to
+    /// mirror the LSM topology without polling the whole manifest. The returned
+    /// [`ManifestSubscription`] first yields a [`ManifestEvent::Snapshot`] of the version as of
+    /// subscribe time, then every [`VersionDiff`] applied afterwards.
+    pub async fn subscribe(&self) -> ManifestSubscription {
+        self.inner.read().await.subscribe()
+    }
+
+    /// Registers a sstable built out-of-band (e.g. bulk-loaded from a backup with
+    /// `SstableBuilder`) directly into `level`, skipping the memtable/flush path entirely. The
+    /// sstable's metadata must already be present in the [`SstableStoreRef`] backing this
+    /// manifest, e.g. via [`crate::components::SstableStore::put`]. Reuses [`Self::update`]'s
+    /// `NonOverlap` check, so ingesting into a non-overlap level that already holds an sstable
+    /// covering an overlapping key range fails rather than corrupting the level's sort order.
+    pub async fn ingest(&self, sst_id: u64, level: u64, data_size: u64) -> Result<()> {
+        let diff = VersionDiff {
+            id: 0,
+            sstable_diffs: vec![SstableDiff {
+                id: sst_id,
+                level,
+                op: SstableOp::Insert.into(),
+                data_size,
+            }],
+        };
+        self.update(diff, false).await
+    }
+
     /// Revoke all version diffs whose id is smaller than given `diff_id`.
     pub async fn squash(&self, diff_id: u64) {
         self.inner.write().await.squash(diff_id)
@@ -487,13 +685,74 @@ impl VersionManager {
     pub async fn verify_non_overlap(&self) -> Result<bool> {
         self.inner.read().await.verify_non_overlap().await
     }
+
+    /// Pins `sst_ids` so a GC sweep (see [`Self::gc`]) won't physically delete them, e.g. while a
+    /// scan's iterator still holds [`SstableIterator`](crate::iterator::SstableIterator)s over
+    /// them even after a later compaction removes them from the version. The pin is released when
+    /// the returned guard is dropped.
+    pub fn pin_sstables(&self, sst_ids: &[u64]) -> SstablePinGuard {
+        let mut pinned = self.pinned_sstables.lock().unwrap();
+        for &sst_id in sst_ids {
+            *pinned.entry(sst_id).or_insert(0) += 1;
+        }
+        SstablePinGuard {
+            version_manager: self.clone(),
+            sst_ids: sst_ids.to_vec(),
+        }
+    }
+
+    fn unpin_sstables(&self, sst_ids: &[u64]) {
+        let mut pinned = self.pinned_sstables.lock().unwrap();
+        for &sst_id in sst_ids {
+            if let Some(count) = pinned.get_mut(&sst_id) {
+                *count -= 1;
+                if *count == 0 {
+                    pinned.remove(&sst_id);
+                }
+            }
+        }
+    }
+
+    /// Whether `sst_id` is currently pinned by at least one live [`SstablePinGuard`].
+    pub fn is_pinned(&self, sst_id: u64) -> bool {
+        self.pinned_sstables
+            .lock()
+            .unwrap()
+            .contains_key(&sst_id)
+    }
+
+    /// Physically deletes `sst_ids` from `sstable_store`, skipping any id still pinned by a live
+    /// scan (see [`Self::pin_sstables`]). Returns the ids that were actually deleted.
+    pub async fn gc(&self, sstable_store: &SstableStoreRef, sst_ids: &[u64]) -> Result<Vec<u64>> {
+        let mut deleted = Vec::with_capacity(sst_ids.len());
+        for &sst_id in sst_ids {
+            if self.is_pinned(sst_id) {
+                trace!("skip gc for pinned sst {}", sst_id);
+                continue;
+            }
+            sstable_store.delete(sst_id).await?;
+            deleted.push(sst_id);
+        }
+        Ok(deleted)
+    }
+}
+
+/// RAII guard returned by [`VersionManager::pin_sstables`]. Unpins its sstable ids when dropped.
+pub struct SstablePinGuard {
+    version_manager: VersionManager,
+    sst_ids: Vec<u64>,
+}
+
+impl Drop for SstablePinGuard {
+    fn drop(&mut self) {
+        self.version_manager.unpin_sstables(&self.sst_ids);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::assert_matches::assert_matches;
 
-    use itertools::Itertools;
     use runkv_proto::manifest::SstableDiff;
     use test_log::test;
 
@@ -662,6 +921,52 @@ mod tests {
         )
     }
 
+    #[test(tokio::test)]
+    async fn test_update_is_atomic_across_a_multi_sst_batch() {
+        let sstable_store = build_sstable_store_for_test();
+        let mut version_manager = build_version_manager_for_test(sstable_store.clone());
+        version_manager.levels = vec![vec![], vec![1, 2], vec![], vec![], vec![], vec![], vec![]];
+        version_manager.levels_data_size = vec![0, 20, 0, 0, 0, 0, 0];
+        ingest_meta(&sstable_store, 1, fkey(b"aaa"), fkey(b"bbb")).await;
+        ingest_meta(&sstable_store, 2, fkey(b"ccc"), fkey(b"ddd")).await;
+        // Overlaps sst 2, so inserting it into non-overlap level 1 fails.
+        ingest_meta(&sstable_store, 3, fkey(b"ccd"), fkey(b"cce")).await;
+
+        let levels_before = version_manager.levels.clone();
+        let levels_data_size_before = version_manager.levels_data_size.clone();
+
+        // A single batch: delete sst 1 (would succeed on its own), then insert sst 3 (fails).
+        // Simulates a crash partway through a compaction's remove-old/add-new swap: the whole
+        // batch must be rejected rather than leaving sst 1 deleted without sst 3 inserted.
+        let result = version_manager
+            .update(
+                VersionDiff {
+                    id: 1,
+                    sstable_diffs: vec![
+                        SstableDiff {
+                            id: 1,
+                            level: 1,
+                            op: SstableOp::Delete.into(),
+                            data_size: 10,
+                        },
+                        SstableDiff {
+                            id: 3,
+                            level: 1,
+                            op: SstableOp::Insert.into(),
+                            data_size: 5,
+                        },
+                    ],
+                },
+                false,
+            )
+            .await;
+
+        assert!(result.is_err());
+        // Neither half of the batch took effect: sst 1 is still present, sst 3 was not inserted.
+        assert_eq!(version_manager.levels, levels_before);
+        assert_eq!(version_manager.levels_data_size, levels_data_size_before);
+    }
+
     #[test(tokio::test)]
     async fn test_pick_overlap_ssts() {
         let sstable_store = build_sstable_store_for_test();
@@ -823,6 +1128,123 @@ mod tests {
         assert!(!version_manager.verify_non_overlap().await.unwrap());
     }
 
+    #[test(tokio::test)]
+    async fn test_subscribe_late_reconstructs_current_manifest() {
+        let sstable_store = build_sstable_store_for_test();
+        let mut version_manager = build_version_manager_for_test(sstable_store.clone());
+        version_manager.levels = vec![vec![]; 7];
+
+        ingest_meta(&sstable_store, 1, fkey(b"aaa"), fkey(b"bbb")).await;
+        ingest_meta(&sstable_store, 2, fkey(b"ccc"), fkey(b"ddd")).await;
+        ingest_meta(&sstable_store, 3, fkey(b"eee"), fkey(b"fff")).await;
+
+        // Applied before anybody subscribes, so a late subscriber's snapshot must already
+        // reflect them instead of replaying from an empty manifest.
+        for id in [1, 2] {
+            version_manager
+                .update(
+                    VersionDiff {
+                        id,
+                        sstable_diffs: vec![SstableDiff {
+                            id,
+                            level: 1,
+                            op: SstableOp::Insert.into(),
+                            data_size: 0,
+                        }],
+                    },
+                    false,
+                )
+                .await
+                .unwrap();
+        }
+
+        let mut subscription = version_manager.subscribe();
+        let mut reconstructed = match subscription.next().await.unwrap().unwrap() {
+            ManifestEvent::Snapshot(snapshot) => snapshot.levels,
+            event => panic!("expected a snapshot first, got {:?}", event),
+        };
+        assert_eq!(
+            reconstructed,
+            vec![vec![], vec![1, 2], vec![], vec![], vec![], vec![], vec![]]
+        );
+
+        // Delivered as a delta after the snapshot, not folded into it.
+        version_manager
+            .update(
+                VersionDiff {
+                    id: 3,
+                    sstable_diffs: vec![SstableDiff {
+                        id: 3,
+                        level: 1,
+                        op: SstableOp::Insert.into(),
+                        data_size: 0,
+                    }],
+                },
+                false,
+            )
+            .await
+            .unwrap();
+        match subscription.next().await.unwrap().unwrap() {
+            ManifestEvent::Diff(diff) => {
+                for sstable_diff in &diff.sstable_diffs {
+                    match sstable_diff.op() {
+                        SstableOp::Insert => {
+                            reconstructed[sstable_diff.level as usize].push(sstable_diff.id)
+                        }
+                        SstableOp::Delete => reconstructed[sstable_diff.level as usize]
+                            .retain(|id| *id != sstable_diff.id),
+                    }
+                }
+            }
+            event => panic!("expected a diff, got {:?}", event),
+        }
+
+        assert_eq!(reconstructed, version_manager.levels);
+        assert_eq!(
+            reconstructed,
+            vec![vec![], vec![1, 2, 3], vec![], vec![], vec![], vec![], vec![]]
+        );
+    }
+
+    #[test]
+    fn test_ranges_overlap() {
+        // Disjoint.
+        assert!(!ranges_overlap(b"aaa", b"bbb", b"ccc", b"ddd"));
+        // Adjacent, with a gap between them.
+        assert!(!ranges_overlap(b"aaa", b"bbb", b"ddd", b"eee"));
+        // Touching: one range's max equals the other's min.
+        assert!(ranges_overlap(b"aaa", b"bbb", b"bbb", b"ccc"));
+        // Nested: one range fully contains the other.
+        assert!(ranges_overlap(b"aaa", b"zzz", b"mmm", b"nnn"));
+        // Overlapping in the middle.
+        assert!(ranges_overlap(b"aaa", b"mmm", b"fff", b"zzz"));
+    }
+
+    #[test]
+    fn test_level_is_non_overlapping() {
+        // Adjacent, with a gap: non-overlapping.
+        assert!(level_is_non_overlapping(&[
+            (b"aaa", b"bbb"),
+            (b"ddd", b"eee"),
+        ]));
+        // Touching at a single key: overlapping.
+        assert!(!level_is_non_overlapping(&[
+            (b"aaa", b"bbb"),
+            (b"bbb", b"ccc"),
+        ]));
+        // Nested: overlapping.
+        assert!(!level_is_non_overlapping(&[
+            (b"aaa", b"zzz"),
+            (b"mmm", b"nnn"),
+        ]));
+        // Out of ASC order, even though the ranges themselves don't truly overlap, is itself a
+        // violation: a level's stored sstable ids must already be ASC sorted.
+        assert!(!level_is_non_overlapping(&[
+            (b"ddd", b"eee"),
+            (b"aaa", b"bbb"),
+        ]));
+    }
+
     async fn ingest_meta(
         sstable_store: &SstableStoreRef,
         sst_id: u64,
@@ -842,6 +1264,12 @@ mod tests {
                         }],
                         bloom_filter_bytes: vec![],
                         data_size: 0,
+                        dictionary: vec![],
+                        data_checksum: 0,
+                        compression_algorithm: runkv_common::coding::CompressionAlgorithm::None,
+                        created_at: 0,
+                        level: 0,
+                        meta_size: 0,
                     }),
                 ),
                 Vec::default(),
@@ -880,6 +1308,7 @@ mod tests {
             object_store,
             block_cache,
             meta_cache_capacity: 65536,
+            enable_content_dedup: false,
         };
         Arc::new(SstableStore::new(sstable_store_options))
     }
@@ -0,0 +1,488 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use runkv_common::config::LevelCompactionStrategy;
+
+use crate::components::{CachePolicy, Memtable, SstableStoreRef};
+use crate::iterator::{
+    BoxedIterator, Iterator, MemtableIterator, MergeIterator, Seek, SstableIterator,
+    UserKeyIterator,
+};
+use crate::manifest::{SstablePinGuard, VersionManager};
+use crate::utils::value;
+use crate::Result;
+
+/// Looks up the newest version of `key` visible as of `sequence`.
+///
+/// `memtables` are checked newest-first (typically the active memtable followed by immutable
+/// memtables, oldest last), then sstables tracked by `version_manager` are checked level by
+/// level. [`VersionManager::pick_overlap_ssts_by_key`] already consults each candidate sstable's
+/// bloom filter, so sstables that definitely don't contain `key` are skipped before any block is
+/// read.
+pub async fn get(
+    memtables: &[Memtable],
+    version_manager: &VersionManager,
+    sstable_store: &SstableStoreRef,
+    key: &[u8],
+    sequence: u64,
+) -> Result<Option<Bytes>> {
+    let key = Bytes::copy_from_slice(key);
+
+    // Seek from memtables. A tombstone here must stop the search rather than fall through to
+    // older memtables or sstables, so check the raw entry instead of `Memtable::get`, which
+    // can't distinguish "not found" from "found, but deleted".
+    for memtable in memtables {
+        if let Some(raw) = memtable.get_raw(&key, sequence) {
+            return Ok(value(&raw).map(Bytes::copy_from_slice));
+        }
+    }
+
+    // Pick overlap ssts, bloom filters already applied.
+    let levels = version_manager
+        .pick_overlap_ssts_by_key(0..version_manager.levels().await, &key)
+        .await?;
+
+    // Seek from ssts.
+    for (level_idx, level) in levels.into_iter().enumerate() {
+        if level.is_empty() {
+            continue;
+        }
+        let compaction_strategy = version_manager
+            .level_compaction_strategy(level_idx as u64)
+            .await?;
+
+        let mut iter = match compaction_strategy {
+            LevelCompactionStrategy::Overlap => {
+                let mut iters: Vec<Box<dyn Iterator>> = Vec::with_capacity(level.len());
+                for sst_id in level {
+                    let sst = sstable_store.sstable(sst_id).await?;
+                    iters.push(Box::new(SstableIterator::new(
+                        sstable_store.clone(),
+                        sst,
+                        CachePolicy::Fill,
+                    )));
+                }
+                UserKeyIterator::new(Box::new(MergeIterator::new(iters)), sequence)
+            }
+            LevelCompactionStrategy::NonOverlap => {
+                assert_eq!(
+                    level.len(),
+                    1,
+                    "look up key {:?} in level idx: {}, result: {:?}",
+                    key,
+                    level_idx,
+                    level
+                );
+                let sst = sstable_store.sstable(level[0]).await?;
+                UserKeyIterator::new(
+                    Box::new(SstableIterator::new(
+                        sstable_store.clone(),
+                        sst,
+                        CachePolicy::Fill,
+                    )),
+                    sequence,
+                )
+            }
+        };
+        if iter.seek(Seek::RandomForward(&key)).await? {
+            if iter.is_valid() && iter.key() == key {
+                return Ok(Some(Bytes::from(iter.value().to_vec())));
+            } else {
+                return Ok(None);
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Bounds a [`UserKeyIterator`] to user keys strictly less than `end`, so callers don't have to
+/// check the bound themselves after every `next`.
+pub struct ScanIterator {
+    inner: UserKeyIterator,
+    end: Vec<u8>,
+    /// Pins the sstables `inner` reads from for as long as this iterator is alive, so a
+    /// compaction that runs concurrently with a long scan can't have its GC sweep out the scan's
+    /// input sstables out from under it. See [`VersionManager::pin_sstables`].
+    _pin: SstablePinGuard,
+}
+
+impl ScanIterator {
+    fn new(inner: UserKeyIterator, end: Vec<u8>, pin: SstablePinGuard) -> Self {
+        Self {
+            inner,
+            end,
+            _pin: pin,
+        }
+    }
+
+    fn in_bound(&self) -> bool {
+        self.inner.is_valid() && self.inner.key() < self.end.as_slice()
+    }
+}
+
+#[async_trait]
+impl Iterator for ScanIterator {
+    async fn next(&mut self) -> Result<()> {
+        self.inner.next().await
+    }
+
+    async fn prev(&mut self) -> Result<()> {
+        self.inner.prev().await
+    }
+
+    fn key(&self) -> &[u8] {
+        self.inner.key()
+    }
+
+    fn value(&self) -> &[u8] {
+        self.inner.value()
+    }
+
+    fn is_valid(&self) -> bool {
+        self.in_bound()
+    }
+
+    async fn seek<'s>(&mut self, seek: Seek<'s>) -> Result<bool> {
+        let found = self.inner.seek(seek).await?;
+        Ok(found && self.in_bound())
+    }
+}
+
+/// Scans the half-open user key range `[start, end)` as of `sequence`, merging `memtables`
+/// (checked newest-first, same convention as [`get`]) with the overlapping sstables tracked by
+/// `version_manager`.
+///
+/// The returned [`ScanIterator`] is already seeked to `start` and collapses each user key to its
+/// newest version visible at `sequence`; tombstoned keys are skipped entirely, the same way
+/// [`UserKeyIterator`] skips them during forward iteration. Iterate with
+/// [`Iterator::next`]/[`Iterator::is_valid`] until `is_valid` returns `false`.
+pub async fn scan(
+    memtables: &[Memtable],
+    version_manager: &VersionManager,
+    sstable_store: &SstableStoreRef,
+    start: &[u8],
+    end: &[u8],
+    sequence: u64,
+) -> Result<ScanIterator> {
+    let mut iters: Vec<BoxedIterator> = Vec::with_capacity(memtables.len());
+    for memtable in memtables {
+        iters.push(Box::new(MemtableIterator::new(memtable, sequence)));
+    }
+
+    let levels = version_manager
+        .pick_overlap_ssts(0..version_manager.levels().await, start..=end)
+        .await?;
+    let sst_ids: Vec<u64> = levels.iter().flatten().copied().collect();
+    let pin = version_manager.pin_sstables(&sst_ids);
+    for level in levels {
+        for sst_id in level {
+            let sst = sstable_store.sstable(sst_id).await?;
+            iters.push(Box::new(SstableIterator::new(
+                sstable_store.clone(),
+                sst,
+                CachePolicy::Fill,
+            )));
+        }
+    }
+
+    let mut inner = UserKeyIterator::new(Box::new(MergeIterator::new(iters)), sequence);
+    inner.seek(Seek::RandomForward(start)).await?;
+    Ok(ScanIterator::new(inner, end.to_vec(), pin))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use test_log::test;
+
+    use super::*;
+    use crate::components::{
+        BlockCache, LsmTreeMetrics, SstableBuilder, SstableBuilderOptions, SstableStore,
+        SstableStoreOptions,
+    };
+    use crate::manifest::VersionManagerOptions;
+    use crate::MemObjectStore;
+    use runkv_common::config::LevelOptions;
+
+    async fn build_sstable_store_for_test() -> SstableStoreRef {
+        let object_store = Arc::new(MemObjectStore::default());
+        let block_cache = BlockCache::new(65536, Arc::new(LsmTreeMetrics::new(0)));
+        let options = SstableStoreOptions {
+            path: "path".to_string(),
+            object_store,
+            block_cache,
+            meta_cache_capacity: 1024,
+            enable_content_dedup: false,
+        };
+        Arc::new(SstableStore::new(options))
+    }
+
+    async fn build_version_manager_for_test(
+        sstable_store: SstableStoreRef,
+        level_0_sst_ids: Vec<u64>,
+    ) -> VersionManager {
+        VersionManager::new(VersionManagerOptions {
+            levels_options: vec![LevelOptions {
+                compaction_strategy: LevelCompactionStrategy::Overlap,
+                compression_algorithm: runkv_common::coding::CompressionAlgorithm::None,
+            }],
+            levels: vec![level_0_sst_ids],
+            sstable_store,
+        })
+    }
+
+    async fn put_sstable_for_test(
+        sstable_store: &SstableStoreRef,
+        id: u64,
+        entries: Vec<(&str, u64, Option<&str>)>,
+    ) {
+        let options = SstableBuilderOptions {
+            bloom_false_positive: 0.01,
+            ..Default::default()
+        };
+        let mut builder = SstableBuilder::new(options);
+        for (k, ts, v) in entries {
+            builder
+                .add(k.as_bytes(), ts, v.map(|v| v.as_bytes()))
+                .unwrap();
+        }
+        let (meta, data) = builder.build().unwrap();
+        let sst = crate::components::Sstable::new(id, Arc::new(meta));
+        sstable_store
+            .put(&sst, data, CachePolicy::Fill)
+            .await
+            .unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn test_get_hits_memtable() {
+        let sstable_store = build_sstable_store_for_test().await;
+        let version_manager = build_version_manager_for_test(sstable_store.clone(), vec![]).await;
+
+        let memtable = Memtable::new(1024 * 1024);
+        memtable.put(&Bytes::from_static(b"k01"), Some(&Bytes::from_static(b"v01")), 1);
+
+        let result = get(&[memtable], &version_manager, &sstable_store, b"k01", 1)
+            .await
+            .unwrap();
+        assert_eq!(result, Some(Bytes::from_static(b"v01")));
+    }
+
+    #[test(tokio::test)]
+    async fn test_get_hits_sstable() {
+        let sstable_store = build_sstable_store_for_test().await;
+        put_sstable_for_test(&sstable_store, 1, vec![("k01", 1, Some("v01"))]).await;
+        let version_manager = build_version_manager_for_test(sstable_store.clone(), vec![1]).await;
+
+        let result = get(&[], &version_manager, &sstable_store, b"k01", 1)
+            .await
+            .unwrap();
+        assert_eq!(result, Some(Bytes::from_static(b"v01")));
+    }
+
+    #[test(tokio::test)]
+    async fn test_get_skips_bloom_filter_miss() {
+        let sstable_store = build_sstable_store_for_test().await;
+        put_sstable_for_test(&sstable_store, 1, vec![("k01", 1, Some("v01"))]).await;
+        let version_manager = build_version_manager_for_test(sstable_store.clone(), vec![1]).await;
+
+        // "k99" is outside the sstable's key range and its bloom filter, so
+        // `pick_overlap_ssts_by_key` never surfaces sst 1 as a candidate.
+        let result = get(&[], &version_manager, &sstable_store, b"k99", 1)
+            .await
+            .unwrap();
+        assert_eq!(result, None);
+    }
+
+    #[test(tokio::test)]
+    async fn test_get_returns_none_for_tombstone() {
+        let sstable_store = build_sstable_store_for_test().await;
+        put_sstable_for_test(
+            &sstable_store,
+            1,
+            vec![("k01", 1, Some("v01")), ("k01", 2, None)],
+        )
+        .await;
+        let version_manager = build_version_manager_for_test(sstable_store.clone(), vec![1]).await;
+
+        let result = get(&[], &version_manager, &sstable_store, b"k01", 2)
+            .await
+            .unwrap();
+        assert_eq!(result, None);
+
+        // A tombstone in the memtable must also stop the search rather than fall through to the
+        // sstable's older, still-visible version.
+        let memtable = Memtable::new(1024 * 1024);
+        memtable.put(&Bytes::from_static(b"k01"), None, 3);
+        let result = get(&[memtable], &version_manager, &sstable_store, b"k01", 3)
+            .await
+            .unwrap();
+        assert_eq!(result, None);
+    }
+
+    async fn collect_scan(mut iter: ScanIterator) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut entries = vec![];
+        while iter.is_valid() {
+            entries.push((iter.key().to_vec(), iter.value().to_vec()));
+            iter.next().await.unwrap();
+        }
+        entries
+    }
+
+    #[test(tokio::test)]
+    async fn test_scan_spans_memtable_and_multiple_ssts() {
+        let sstable_store = build_sstable_store_for_test().await;
+        put_sstable_for_test(&sstable_store, 1, vec![("k01", 1, Some("v01"))]).await;
+        put_sstable_for_test(&sstable_store, 2, vec![("k03", 1, Some("v03"))]).await;
+        let version_manager =
+            build_version_manager_for_test(sstable_store.clone(), vec![1, 2]).await;
+
+        let memtable = Memtable::new(1024 * 1024);
+        memtable.put(&Bytes::from_static(b"k02"), Some(&Bytes::from_static(b"v02")), 1);
+
+        let iter = scan(
+            &[memtable],
+            &version_manager,
+            &sstable_store,
+            b"k01",
+            b"k04",
+            1,
+        )
+        .await
+        .unwrap();
+        let entries = collect_scan(iter).await;
+        assert_eq!(
+            entries,
+            vec![
+                (b"k01".to_vec(), b"v01".to_vec()),
+                (b"k02".to_vec(), b"v02".to_vec()),
+                (b"k03".to_vec(), b"v03".to_vec()),
+            ]
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_scan_skips_tombstone_at_range_boundary() {
+        let sstable_store = build_sstable_store_for_test().await;
+        put_sstable_for_test(
+            &sstable_store,
+            1,
+            vec![
+                ("k01", 1, Some("v01")),
+                ("k02", 1, Some("v02-old")),
+                ("k02", 2, None),
+                ("k03", 1, Some("v03")),
+            ],
+        )
+        .await;
+        let version_manager = build_version_manager_for_test(sstable_store.clone(), vec![1]).await;
+
+        let iter = scan(&[], &version_manager, &sstable_store, b"k01", b"k04", 2)
+            .await
+            .unwrap();
+        let entries = collect_scan(iter).await;
+        assert_eq!(
+            entries,
+            vec![
+                (b"k01".to_vec(), b"v01".to_vec()),
+                (b"k03".to_vec(), b"v03".to_vec()),
+            ]
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_scan_survives_compaction_gc_while_pinned() {
+        use runkv_proto::manifest::{SstableDiff, SstableOp, VersionDiff};
+
+        let sstable_store = build_sstable_store_for_test().await;
+        put_sstable_for_test(&sstable_store, 1, vec![("k01", 1, Some("v01"))]).await;
+        put_sstable_for_test(&sstable_store, 2, vec![("k03", 1, Some("v03"))]).await;
+        put_sstable_for_test(
+            &sstable_store,
+            3,
+            vec![("k01", 1, Some("v01")), ("k03", 1, Some("v03"))],
+        )
+        .await;
+        let version_manager =
+            build_version_manager_for_test(sstable_store.clone(), vec![1, 2]).await;
+
+        // Opening the scan pins ssts 1 and 2 for as long as `iter` is alive.
+        let iter = scan(&[], &version_manager, &sstable_store, b"k01", b"k04", 1)
+            .await
+            .unwrap();
+
+        // A compaction runs concurrently, replacing ssts 1 and 2 with the merged sst 3.
+        version_manager
+            .update(
+                VersionDiff {
+                    id: 1,
+                    sstable_diffs: vec![
+                        SstableDiff {
+                            id: 3,
+                            level: 0,
+                            op: SstableOp::Insert.into(),
+                            data_size: 0,
+                        },
+                        SstableDiff {
+                            id: 1,
+                            level: 0,
+                            op: SstableOp::Delete.into(),
+                            data_size: 0,
+                        },
+                        SstableDiff {
+                            id: 2,
+                            level: 0,
+                            op: SstableOp::Delete.into(),
+                            data_size: 0,
+                        },
+                    ],
+                },
+                false,
+            )
+            .await
+            .unwrap();
+
+        // GC would reclaim the compaction's inputs, but the still-running scan pins them.
+        let deleted = version_manager.gc(&sstable_store, &[1, 2]).await.unwrap();
+        assert!(deleted.is_empty());
+
+        // The scan completes correctly even though its input ssts were dropped from the version
+        // and handed to GC while it was running.
+        let entries = collect_scan(iter).await;
+        assert_eq!(
+            entries,
+            vec![
+                (b"k01".to_vec(), b"v01".to_vec()),
+                (b"k03".to_vec(), b"v03".to_vec()),
+            ]
+        );
+
+        // Once the scan (and its pin guard) is dropped, a later GC sweep can reclaim them.
+        let deleted = version_manager.gc(&sstable_store, &[1, 2]).await.unwrap();
+        assert_eq!(deleted, vec![1, 2]);
+        assert!(sstable_store.sstable(1).await.is_err());
+    }
+
+    #[test(tokio::test)]
+    async fn test_get_reads_ingested_sstable() {
+        let sstable_store = build_sstable_store_for_test().await;
+        let version_manager = build_version_manager_for_test(sstable_store.clone(), vec![]).await;
+
+        // Bulk-load: an sstable built offline, outside the memtable/flush path, only gets put
+        // into the object store here and is not yet known to the manifest.
+        put_sstable_for_test(&sstable_store, 1, vec![("k01", 1, Some("v01"))]).await;
+        assert_eq!(
+            get(&[], &version_manager, &sstable_store, b"k01", 1)
+                .await
+                .unwrap(),
+            None
+        );
+
+        version_manager.ingest(1, 0, 0).await.unwrap();
+
+        let result = get(&[], &version_manager, &sstable_store, b"k01", 1)
+            .await
+            .unwrap();
+        assert_eq!(result, Some(Bytes::from_static(b"v01")));
+    }
+}
@@ -0,0 +1,104 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
+
+use super::ObjectStore;
+use crate::{ObjectStoreError, Result};
+
+fn err(e: impl std::fmt::Display) -> ObjectStoreError {
+    ObjectStoreError::Other(e.to_string())
+}
+
+/// Object store backed by the local filesystem, for single-node deployments or local development
+/// where standing up S3/minio isn't worth it. `path` is treated as an object key relative to
+/// `root`, so `/` in a path creates subdirectories as needed.
+pub struct FsObjectStore {
+    root: PathBuf,
+}
+
+impl FsObjectStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn resolve(&self, path: &str) -> PathBuf {
+        self.root.join(path)
+    }
+}
+
+async fn ensure_parent_dir(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await.map_err(err)?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl ObjectStore for FsObjectStore {
+    async fn put(&self, path: &str, obj: Vec<u8>) -> Result<()> {
+        let file_path = self.resolve(path);
+        ensure_parent_dir(&file_path).await?;
+        fs::write(&file_path, obj).await.map_err(err)?;
+        Ok(())
+    }
+
+    async fn get(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let file_path = self.resolve(path);
+        match fs::read(&file_path).await {
+            Ok(obj) => Ok(Some(obj)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(err(e).into()),
+        }
+    }
+
+    async fn get_range(&self, path: &str, range: Range<usize>) -> Result<Option<Vec<u8>>> {
+        let file_path = self.resolve(path);
+        let mut file = match fs::File::open(&file_path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(err(e).into()),
+        };
+        file.seek(SeekFrom::Start(range.start as u64))
+            .await
+            .map_err(err)?;
+        let mut buf = vec![0; range.len()];
+        file.read_exact(&mut buf).await.map_err(err)?;
+        Ok(Some(buf))
+    }
+
+    async fn remove(&self, path: &str) -> Result<()> {
+        let file_path = self.resolve(path);
+        fs::remove_file(&file_path)
+            .await
+            .map_err(|_| ObjectStoreError::ObjectNotFound(path.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_fs_object_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsObjectStore::new(dir.path());
+
+        store.put("a/b.data", b"hello world".to_vec()).await.unwrap();
+        assert_eq!(store.get("a/b.data").await.unwrap().unwrap(), b"hello world");
+        assert_eq!(
+            store.get_range("a/b.data", 0..5).await.unwrap().unwrap(),
+            b"hello"
+        );
+        assert!(store.get("a/missing").await.unwrap().is_none());
+
+        store.remove("a/b.data").await.unwrap();
+        assert!(store.get("a/b.data").await.unwrap().is_none());
+        assert!(store.remove("a/b.data").await.is_err());
+    }
+}
@@ -1,3 +1,5 @@
+mod fs;
+pub use fs::*;
 mod mem;
 pub use mem::*;
 mod s3;
@@ -2,6 +2,7 @@ use std::ops::Range;
 
 use async_trait::async_trait;
 use aws_sdk_s3::error::{GetObjectError, GetObjectErrorKind};
+use aws_sdk_s3::model::{CompletedMultipartUpload, CompletedPart};
 use aws_sdk_s3::types::SdkError;
 use aws_sdk_s3::{Client, Endpoint, Region};
 use aws_smithy_http::body::SdkBody;
@@ -9,6 +10,15 @@ use aws_smithy_http::body::SdkBody;
 use super::ObjectStore;
 use crate::{ObjectStoreError, Result};
 
+/// Payloads at or above this size are uploaded as a multipart upload instead of a single `PUT`,
+/// so a dropped connection only costs one part's retry instead of re-uploading the whole SST. S3
+/// requires parts to be at least 5 MiB (except the last), so this must not go below that.
+const MULTIPART_UPLOAD_THRESHOLD: usize = 16 * 1024 * 1024;
+const MULTIPART_UPLOAD_PART_SIZE: usize = 16 * 1024 * 1024;
+/// Retries per part after a failed upload of that part, whatever the error, independent of any
+/// retry the AWS SDK itself performs at the HTTP layer.
+const MULTIPART_UPLOAD_PART_MAX_RETRIES: usize = 3;
+
 pub struct S3ObjectStore {
     client: Client,
     bucket: String,
@@ -52,9 +62,106 @@ fn err(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> ObjectStoreE
     ObjectStoreError::S3(err.into().to_string())
 }
 
+impl S3ObjectStore {
+    async fn put_multipart(&self, path: &str, obj: Vec<u8>) -> Result<()> {
+        let upload_id = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.bucket)
+            .key(path)
+            .send()
+            .await
+            .map_err(err)?
+            .upload_id()
+            .ok_or_else(|| ObjectStoreError::S3("missing upload id".to_string()))?
+            .to_string();
+
+        let result = self.upload_parts(path, &upload_id, &obj).await;
+
+        match result {
+            Ok(completed_parts) => {
+                self.client
+                    .complete_multipart_upload()
+                    .bucket(&self.bucket)
+                    .key(path)
+                    .upload_id(&upload_id)
+                    .multipart_upload(
+                        CompletedMultipartUpload::builder()
+                            .set_parts(Some(completed_parts))
+                            .build(),
+                    )
+                    .send()
+                    .await
+                    .map_err(err)?;
+                Ok(())
+            }
+            Err(e) => {
+                // Best-effort cleanup: leave it to the bucket's lifecycle policy if this also
+                // fails rather than masking the original upload error.
+                let _ = self
+                    .client
+                    .abort_multipart_upload()
+                    .bucket(&self.bucket)
+                    .key(path)
+                    .upload_id(&upload_id)
+                    .send()
+                    .await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn upload_parts(
+        &self,
+        path: &str,
+        upload_id: &str,
+        obj: &[u8],
+    ) -> Result<Vec<CompletedPart>> {
+        let mut completed_parts = Vec::with_capacity(obj.len() / MULTIPART_UPLOAD_PART_SIZE + 1);
+        for (i, chunk) in obj.chunks(MULTIPART_UPLOAD_PART_SIZE).enumerate() {
+            let part_number = i as i32 + 1;
+            let mut last_err = None;
+            let mut uploaded = None;
+            for _ in 0..=MULTIPART_UPLOAD_PART_MAX_RETRIES {
+                match self
+                    .client
+                    .upload_part()
+                    .bucket(&self.bucket)
+                    .key(path)
+                    .upload_id(upload_id)
+                    .part_number(part_number)
+                    .body(SdkBody::from(chunk.to_vec()).into())
+                    .send()
+                    .await
+                {
+                    Ok(rsp) => {
+                        uploaded = Some(rsp);
+                        break;
+                    }
+                    Err(e) => last_err = Some(err(e)),
+                }
+            }
+            let rsp = match uploaded {
+                Some(rsp) => rsp,
+                None => return Err(last_err.unwrap().into()),
+            };
+            completed_parts.push(
+                CompletedPart::builder()
+                    .part_number(part_number)
+                    .set_e_tag(rsp.e_tag().map(|s| s.to_string()))
+                    .build(),
+            );
+        }
+        Ok(completed_parts)
+    }
+}
+
 #[async_trait]
 impl ObjectStore for S3ObjectStore {
     async fn put(&self, path: &str, obj: Vec<u8>) -> Result<()> {
+        if obj.len() >= MULTIPART_UPLOAD_THRESHOLD {
+            return self.put_multipart(path, obj).await;
+        }
         self.client
             .put_object()
             .bucket(&self.bucket)
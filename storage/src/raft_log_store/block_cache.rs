@@ -98,4 +98,14 @@ impl BlockCache {
 
         Ok(result)
     }
+
+    /// Evict every cached block that belongs to the given log file.
+    ///
+    /// Used after a log file has been removed by GC so stale blocks are never served.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub fn invalidate_file(&self, file_id: u64) {
+        self.inner
+            .invalidate_entries_if(move |k, _v| k.file_id == file_id)
+            .ok();
+    }
 }
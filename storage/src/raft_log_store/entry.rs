@@ -4,7 +4,7 @@ use bytes::{Buf, BufMut};
 use lz4::Decoder;
 use runkv_common::coding::CompressionAlgorithm;
 
-use super::DEFAULT_LOG_BATCH_SIZE;
+use super::{DEFAULT_COMPRESSION_THRESHOLD, DEFAULT_LOG_BATCH_SIZE};
 use crate::error::Result;
 use crate::raft_log_store::error::RaftLogStoreError;
 use crate::utils::{
@@ -173,27 +173,36 @@ impl RaftLogBatch {
 
     /// Convert raw data to encoded data.
     ///
+    /// Batches whose raw data segment is smaller than `compression_threshold` are stored
+    /// uncompressed: LZ4 framing overhead can otherwise make a small batch (e.g. a lone
+    /// config-change or no-op entry) larger on disk than the raw bytes themselves.
+    ///
     /// Format:
     ///
     /// ```plain
-    /// | data block (compressed) | compression algorithm (1B) | crc32sum (4B) |
+    /// | data block (maybe compressed) | compression algorithm (1B) | crc32sum (4B) |
     /// ```
-    fn encode_data(&mut self) {
-        let mut buf = {
-            let mut encoder = lz4::EncoderBuilder::new()
-                .level(4)
-                .build(Vec::with_capacity(self.raw.len()).writer())
-                .map_err(RaftLogStoreError::encode_error)
-                .unwrap();
-            encoder
-                .write(&self.raw[..])
-                .map_err(RaftLogStoreError::encode_error)
-                .unwrap();
-            let (writer, result) = encoder.finish();
-            result.map_err(RaftLogStoreError::encode_error).unwrap();
-            writer.into_inner()
+    fn encode_data(&mut self, compression_threshold: usize) {
+        let (mut buf, algorithm) = if self.raw.len() < compression_threshold {
+            (self.raw.clone(), CompressionAlgorithm::None)
+        } else {
+            let buf = {
+                let mut encoder = lz4::EncoderBuilder::new()
+                    .level(4)
+                    .build(Vec::with_capacity(self.raw.len()).writer())
+                    .map_err(RaftLogStoreError::encode_error)
+                    .unwrap();
+                encoder
+                    .write(&self.raw[..])
+                    .map_err(RaftLogStoreError::encode_error)
+                    .unwrap();
+                let (writer, result) = encoder.finish();
+                result.map_err(RaftLogStoreError::encode_error).unwrap();
+                writer.into_inner()
+            };
+            (buf, CompressionAlgorithm::Lz4)
         };
-        CompressionAlgorithm::Lz4.encode(&mut buf);
+        algorithm.encode(&mut buf);
         let checksum = crc32sum(&buf);
         buf.put_u32_le(checksum);
         self.data = buf;
@@ -282,18 +291,41 @@ impl RaftLogBatch {
                     .unwrap();
                 decoded
             }
+            CompressionAlgorithm::Zstd => {
+                return Err(RaftLogStoreError::decode_error(
+                    "zstd compression is not supported for raft log entries",
+                )
+                .into())
+            }
         };
         Ok(buf)
     }
 }
 
-#[derive(Default)]
 pub struct RaftLogBatchBuilder {
     pub current: RaftLogBatch,
     pub batches: Vec<RaftLogBatch>,
+    /// Forwarded to [`RaftLogBatch::encode_data`] for every batch produced by [`Self::build`].
+    /// Defaults to [`DEFAULT_COMPRESSION_THRESHOLD`]; use [`Self::new`] to override it, e.g. with
+    /// [`crate::raft_log_store::store::RaftLogStoreOptions::compression_threshold`].
+    pub compression_threshold: usize,
+}
+
+impl Default for RaftLogBatchBuilder {
+    fn default() -> Self {
+        Self::new(DEFAULT_COMPRESSION_THRESHOLD)
+    }
 }
 
 impl RaftLogBatchBuilder {
+    pub fn new(compression_threshold: usize) -> Self {
+        Self {
+            current: RaftLogBatch::default(),
+            batches: vec![],
+            compression_threshold,
+        }
+    }
+
     pub fn add(&mut self, group: u64, term: u64, index: u64, ctx: &[u8], data: &[u8]) {
         // TODO: For adaptation with openraft, which test suits has log entry with both term and
         // index equals 0.
@@ -317,7 +349,7 @@ impl RaftLogBatchBuilder {
     pub fn build(mut self) -> Vec<RaftLogBatch> {
         self.may_rotate(0, 0, 0);
         for batch in self.batches.iter_mut() {
-            batch.encode_data();
+            batch.encode_data(self.compression_threshold);
         }
         self.batches
     }
@@ -495,4 +527,36 @@ mod tests {
         }
         assert_eq!(decoded_logs, logs);
     }
+
+    #[test]
+    fn test_compression_threshold() {
+        let compressible = vec![b'x'; 4 << 10];
+
+        // Above the threshold: compressed, and the compressed data segment is smaller than the
+        // raw payload.
+        let mut builder = RaftLogBatchBuilder::new(compressible.len() / 2);
+        builder.add(1, 1, 1, b"", &compressible);
+        let mut batches = builder.build();
+        assert_eq!(batches.len(), 1);
+        let batch = batches.remove(0);
+        assert!(batch.data.len() < compressible.len());
+        assert_eq!(
+            RaftLogBatch::extract_data_segment(&batch.data).unwrap(),
+            compressible
+        );
+
+        // Below the threshold: left uncompressed, so the data segment is the raw payload plus
+        // just the trailing compression-algorithm tag and checksum.
+        let small = b"vote-term-bump".to_vec();
+        let mut builder = RaftLogBatchBuilder::new(small.len() + 1);
+        builder.add(1, 1, 1, b"", &small);
+        let mut batches = builder.build();
+        assert_eq!(batches.len(), 1);
+        let batch = batches.remove(0);
+        assert_eq!(batch.data.len(), small.len() + 1 /* algorithm */ + 4 /* crc32 */);
+        assert_eq!(
+            RaftLogBatch::extract_data_segment(&batch.data).unwrap(),
+            small
+        );
+    }
 }
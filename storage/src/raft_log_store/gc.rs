@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use runkv_common::Worker;
+use tracing::warn;
+
+use super::store::RaftLogStore;
+
+/// Default interval between [`RaftLogStoreGcWorker`] runs. Frozen segments only ever become
+/// obsolete as a side effect of [`RaftLogStore::compact`], which isn't latency-sensitive, so this
+/// favors batching reclaims over reacting quickly.
+pub const DEFAULT_GC_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct RaftLogStoreGcWorkerOptions {
+    pub store: RaftLogStore,
+    pub gc_interval: Duration,
+}
+
+/// [`RaftLogStoreGcWorker`] periodically reclaims frozen log segments that have become fully
+/// obsolete due to compaction, so their disk space can be returned to the OS.
+pub struct RaftLogStoreGcWorker {
+    store: RaftLogStore,
+    gc_interval: Duration,
+}
+
+impl RaftLogStoreGcWorker {
+    pub fn new(options: RaftLogStoreGcWorkerOptions) -> Self {
+        Self {
+            store: options.store,
+            gc_interval: options.gc_interval,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for RaftLogStoreGcWorker {
+    async fn run(&mut self) -> anyhow::Result<()> {
+        // TODO: Gracefully kill.
+        loop {
+            tokio::time::sleep(self.gc_interval).await;
+            match self.store.gc().await {
+                Ok(n) if n > 0 => tracing::trace!("gc'ed {} raft log segments", n),
+                Ok(_) => {}
+                Err(e) => warn!("error occur when raft log store gc running: {}", e),
+            }
+        }
+    }
+}
@@ -43,6 +43,18 @@ fn filename(id: u64) -> String {
     format!("{:08}", id)
 }
 
+/// Best-effort extraction of a human-readable message from a [`std::panic::catch_unwind`]
+/// payload, for panics whose payload is the usual `&str`/`String` (e.g. from `unreachable!()`).
+fn panic_message(payload: &Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
+}
+
 async fn new_active_file(path: impl AsRef<Path>, active_file_id: u64) -> Result<File> {
     let mut active_file_open_options = OpenOptions::new();
     active_file_open_options.create(true);
@@ -78,6 +90,9 @@ pub struct LogOptions {
     pub path: String,
     pub log_file_capacity: usize,
     pub persist: Persist,
+    /// When `true`, `Log::open` returns an error on a torn trailing record instead of repairing
+    /// it by truncation.
+    pub strict_repair: bool,
 
     pub metrics: RaftLogStoreMetricsRef,
 }
@@ -152,6 +167,14 @@ impl Log {
                 )
             }
         };
+        let frozen_files = Self::repair(
+            &options.path,
+            frozen_files,
+            first_log_file_id,
+            options.strict_repair,
+        )
+        .await?;
+
         let active_file_id = first_log_file_id + frozen_files.len() as u64;
         let active_file = new_active_file(&options.path, active_file_id).await?;
 
@@ -173,11 +196,123 @@ impl Log {
         })
     }
 
+    /// Check the newest frozen file (the one most recently written before open, if any) for a
+    /// torn trailing record left by a crash mid-write, and truncate it to the last fully-written
+    /// entry.
+    ///
+    /// Every fully-decodable entry before the tear is preserved, so raft's hard state stays
+    /// consistent with what was actually synced. In `strict` mode, a torn tail is reported as an
+    /// error instead of being repaired.
+    ///
+    /// A record that fails to decode for a reason other than running out of bytes (e.g. an
+    /// unrecognized entry-type tag) is never treated as a torn tail regardless of `strict`: unlike
+    /// a truncated write, the bytes that are there were never going to decode, so silently
+    /// dropping them would hide real corruption rather than repair a crash artifact.
+    async fn repair(
+        path: &str,
+        frozen_files: Vec<File>,
+        first_log_file_id: u64,
+        strict: bool,
+    ) -> Result<Vec<File>> {
+        if frozen_files.is_empty() {
+            return Ok(frozen_files);
+        }
+        // An open with no write after it leaves an empty file that the next open lists as the
+        // newest frozen file; such files are skipped to reach the one most recently written.
+        let mut last_file_id = frozen_files.len() - 1;
+        let mut buf = Vec::new();
+        loop {
+            let file_id = first_log_file_id + last_file_id as u64;
+            let mut file = File::open(Path::new(path).join(filename(file_id))).await?;
+            file.read_to_end(&mut buf).await?;
+            if !buf.is_empty() || last_file_id == 0 {
+                break;
+            }
+            last_file_id -= 1;
+        }
+        let file_id = first_log_file_id + last_file_id as u64;
+
+        // `Entry::decode` panics on a torn/partial record (not enough bytes for a field), so the
+        // tail is detected by catching that panic rather than pre-validating lengths for every
+        // variant. A torn tail is expected on every ordinary crash-recovery, so the default panic
+        // hook (which would print a full backtrace to stderr and look like a process crash to
+        // anything scraping it) is swapped out for the duration of the scan.
+        let mut good_len = 0;
+        let mut corrupt: Option<String> = None;
+        let cursor = &mut &buf[..];
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|_| {}));
+        while !cursor.is_empty() {
+            let decoded = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                Entry::decode(cursor)
+            }));
+            match decoded {
+                Ok(_) => good_len = buf.len() - cursor.len(),
+                // An unrecognized entry-type tag can't be the result of a torn write: the tag is
+                // the very first byte of the record, so if it's present at all, it's present in
+                // full. Unlike running out of bytes mid-record, this means the bytes that are
+                // there were never going to decode as a valid `Entry`, so it's surfaced as real
+                // corruption instead of being silently truncated away.
+                Err(panic) if panic_message(&panic).contains("entered unreachable code") => {
+                    corrupt = Some(panic_message(&panic));
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+        std::panic::set_hook(previous_hook);
+
+        if let Some(message) = corrupt {
+            return Err(RaftLogStoreError::Other(format!(
+                "log file {} is corrupt at byte {}: {}",
+                filename(file_id),
+                good_len,
+                message,
+            ))
+            .into());
+        }
+
+        let mut frozen_files = frozen_files;
+        if good_len < buf.len() {
+            if strict {
+                return Err(RaftLogStoreError::Other(format!(
+                    "log file {} has a torn trailing record: {} of {} bytes valid",
+                    filename(file_id),
+                    good_len,
+                    buf.len()
+                ))
+                .into());
+            }
+
+            tracing::warn!(
+                "repairing log file {}: truncating torn trailing record, {} of {} bytes valid",
+                filename(file_id),
+                good_len,
+                buf.len()
+            );
+
+            let file_path = Path::new(path).join(filename(file_id));
+            let truncated = OpenOptions::new().write(true).open(&file_path).await?;
+            truncated.set_len(good_len as u64).await?;
+            truncated.sync_all().await?;
+
+            frozen_files[last_file_id] = File::open(&file_path).await?;
+        }
+
+        Ok(frozen_files)
+    }
+
     pub async fn frozen_file_count(&self) -> usize {
         self.core.frozen_files.read().await.len()
     }
 
     /// Append [`entries`] to log file.
+    ///
+    /// Concurrent callers (e.g. different raft groups on the same wheel) that call this at
+    /// roughly the same time are coalesced: the first caller to arrive becomes the "leader" and
+    /// flushes/fsyncs the whole queue of writers that accumulated while it acquired the active
+    /// file lock, so many groups' appends share a single syscall instead of paying for one each.
+    /// Every writer still gets its own completion via its [`WriteHandle`].
     #[tracing::instrument(level = "trace")]
     pub async fn append(&self, entries: Vec<Entry>) -> Result<Vec<WriteHandle>> {
         let start = Instant::now();
@@ -377,6 +512,38 @@ impl Log {
         self.core.active_file.read().await.sync_all().await?;
         Ok(())
     }
+
+    /// Remove frozen log files strictly before `safe_file_id` from both disk and the in-memory
+    /// file list.
+    ///
+    /// Returns the ids of the files that were actually removed, in ascending order.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub async fn gc(&self, safe_file_id: u64) -> Result<Vec<u64>> {
+        let mut frozen_files = self.core.frozen_files.write().await;
+        let first_log_file_id = self.core.first_log_file_id.load(Ordering::Acquire);
+
+        let remove_count = std::cmp::min(
+            safe_file_id.saturating_sub(first_log_file_id) as usize,
+            frozen_files.len(),
+        );
+        if remove_count == 0 {
+            return Ok(vec![]);
+        }
+
+        let removed_ids = (first_log_file_id..first_log_file_id + remove_count as u64).collect_vec();
+        frozen_files.drain(..remove_count);
+        self.core
+            .first_log_file_id
+            .store(first_log_file_id + remove_count as u64, Ordering::Release);
+        drop(frozen_files);
+
+        for file_id in &removed_ids {
+            tokio::fs::remove_file(Path::new(&self.path).join(filename(*file_id))).await?;
+        }
+
+        trace!("gc log files: {:?}", removed_ids);
+        Ok(removed_ids)
+    }
 }
 
 #[cfg(test)]
@@ -404,6 +571,7 @@ mod tests {
             log_file_capacity: 100,
             persist: Persist::Sync,
             metrics: Arc::new(RaftLogStoreMetrics::new(0)),
+            strict_repair: false,
         };
         let log = Log::open(options.clone()).await.unwrap();
         let entries = generate_entries(4, 16, vec![b'x'; 64]);
@@ -447,6 +615,93 @@ mod tests {
         assert_eq!(decoded_entries, entries);
     }
 
+    #[test(tokio::test)]
+    async fn test_repair_torn_tail() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let options = LogOptions {
+            node: 1,
+            path: tempdir.path().to_str().unwrap().to_string(),
+            // Large enough that nothing rotates: everything lands in file 1, which becomes a
+            // frozen file when the log is reopened below.
+            log_file_capacity: 1 << 20,
+            persist: Persist::Sync,
+            metrics: Arc::new(RaftLogStoreMetrics::new(0)),
+            strict_repair: false,
+        };
+
+        let log = Log::open(options.clone()).await.unwrap();
+        let entries = generate_entries(1, 4, vec![b'x'; 64]);
+        log.append(entries.clone()).await.unwrap();
+        // A second record after the first: the one whose tail is torn below.
+        log.append(generate_entries(1, 1, vec![b'y'; 8]))
+            .await
+            .unwrap();
+        log.close().await.unwrap();
+        drop(log);
+
+        // Corrupt the tail of the first frozen file to simulate a crash mid-write.
+        let path = Path::new(&options.path).join(filename(1));
+        let full_len = tokio::fs::metadata(&path).await.unwrap().len();
+        let file = OpenOptions::new().write(true).open(&path).await.unwrap();
+        file.set_len(full_len - 3).await.unwrap();
+        file.sync_all().await.unwrap();
+        drop(file);
+
+        let log = Log::open(options.clone()).await.unwrap();
+        let mut buf = vec![];
+        log.core.frozen_files.write().await[0]
+            .read_to_end(&mut buf)
+            .await
+            .unwrap();
+        let mut cursor = &buf[..];
+        let decoded = Entry::decode(&mut cursor);
+        assert_eq!(decoded, entries[0]);
+        assert!(cursor.is_empty());
+
+        // Strict mode must refuse to open instead of repairing.
+        let corrupted_len = tokio::fs::metadata(&path).await.unwrap().len() - 3;
+        let file = OpenOptions::new().write(true).open(&path).await.unwrap();
+        file.set_len(corrupted_len).await.unwrap();
+        file.sync_all().await.unwrap();
+        drop(file);
+        let mut strict_options = options;
+        strict_options.strict_repair = true;
+        assert!(Log::open(strict_options).await.is_err());
+    }
+
+    #[test(tokio::test)]
+    async fn test_repair_rejects_unrecognized_tag_even_non_strict() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let options = LogOptions {
+            node: 1,
+            path: tempdir.path().to_str().unwrap().to_string(),
+            log_file_capacity: 1 << 20,
+            persist: Persist::Sync,
+            metrics: Arc::new(RaftLogStoreMetrics::new(0)),
+            strict_repair: false,
+        };
+
+        let log = Log::open(options.clone()).await.unwrap();
+        let entries = generate_entries(1, 4, vec![b'x'; 64]);
+        log.append(entries).await.unwrap();
+        // A second record; file 1 becomes a frozen file when the log is reopened below.
+        log.append(generate_entries(1, 1, vec![b'y'; 8]))
+            .await
+            .unwrap();
+        log.close().await.unwrap();
+        drop(log);
+
+        // Corrupt the entry-type tag of the first record: unlike a torn tail, this isn't a
+        // truncated write, so it must never be silently repaired away, even in non-strict mode.
+        let path = Path::new(&options.path).join(filename(1));
+        let mut file = OpenOptions::new().write(true).open(&path).await.unwrap();
+        file.write_all(&[99]).await.unwrap();
+        file.sync_all().await.unwrap();
+        drop(file);
+
+        assert!(Log::open(options).await.is_err());
+    }
+
     fn generate_entries(groups: usize, group_size: usize, data: Vec<u8>) -> Vec<Entry> {
         let mut builder = RaftLogBatchBuilder::default();
 
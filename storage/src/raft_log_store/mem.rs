@@ -298,6 +298,32 @@ impl MemStates {
         Ok(())
     }
 
+    /// Seeds a never-before-written group's log to begin just after `index`/`term`, so a group
+    /// bootstrapped from an out-of-band snapshot can start raft there without replaying history it
+    /// never had. Unlike [`Self::compact`]'s own gap-jump case, the term to retain for matching is
+    /// given explicitly rather than guessed from whatever entries happen to be in this group's log
+    /// (there are none), since that term comes from the snapshot's own metadata instead.
+    ///
+    /// Only legal on a group that has no log entries yet; seeding a group with real history would
+    /// silently discard it, which a caller that actually means to do that should do via
+    /// [`Self::compact`] instead.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub async fn seed_snapshot_boundary(&self, group: u64, index: u64, term: u64) -> Result<()> {
+        state_mut!(self.states, group, guard, state);
+
+        if !state.indices.is_empty() || state.first_index != 1 {
+            return Err(RaftLogStoreError::Other(format!(
+                "group {} already has log history, refusing to seed a snapshot boundary over it",
+                group
+            ))
+            .into());
+        }
+
+        state.first_index = index + 1;
+        state.phantom_term = term;
+        Ok(())
+    }
+
     /// Mask any indices before the given index.
     ///
     /// Masked indices are not deleted from the state, they should not be accessed by raft
@@ -406,6 +432,29 @@ impl MemStates {
 
         Ok(state.kvs.get(&key).cloned())
     }
+
+    /// Returns the smallest log file id that is still referenced by any group's remaining
+    /// entries.
+    ///
+    /// Returns `None` when no group currently holds any entry, meaning there's no lower bound:
+    /// every already-frozen segment is safe to remove. Callers must not treat `None` as "skip
+    /// GC" — that would leak every frozen segment for good on a store whose groups have all been
+    /// fully compacted away, until some group happens to append a fresh entry.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub async fn min_pinned_file_id(&self) -> Option<u64> {
+        let guard = self.states.read().await;
+        let mut min_file_id = None;
+        for state in guard.values() {
+            let state = state.read().await;
+            if let Some(first) = state.indices.first() {
+                min_file_id = Some(match min_file_id {
+                    Some(min) => std::cmp::min(min, first.file_id),
+                    None => first.file_id,
+                });
+            }
+        }
+        min_file_id
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use super::entry::RaftLogBatch;
+use super::mem::{EntryIndex, MemStates};
+use super::store::Entry;
+use crate::error::Result;
+
+struct MemRaftLogStoreCore {
+    states: MemStates,
+    blocks: RwLock<HashMap<usize, Arc<Vec<u8>>>>,
+    next_block_id: AtomicUsize,
+}
+
+impl Default for MemRaftLogStoreCore {
+    fn default() -> Self {
+        Self {
+            states: MemStates::default(),
+            blocks: RwLock::new(HashMap::default()),
+            next_block_id: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Pure in-memory counterpart of [`RaftLogStore`](super::store::RaftLogStore), implementing the
+/// same truncate/compact/mask semantics on top of the same [`MemStates`] bookkeeping, but backing
+/// entry data with a `HashMap` instead of log files.
+///
+/// Intended for unit tests and truly ephemeral raft groups where paying for a tempdir and real
+/// fsyncs per test is slow and, under CI load, flaky.
+#[derive(Clone, Default)]
+pub struct MemRaftLogStore {
+    core: Arc<MemRaftLogStoreCore>,
+}
+
+impl MemRaftLogStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn add_group(&self, group: u64) -> Result<()> {
+        self.core.states.add_group(group).await
+    }
+
+    /// # Safety
+    ///
+    /// Removed group needs to be guaranteed never be used again.
+    pub async fn remove_group(&self, group: u64) -> Result<()> {
+        self.core.states.remove_group(group).await
+    }
+
+    pub async fn append(&self, batches: Vec<RaftLogBatch>) -> Result<()> {
+        for mut batch in batches {
+            let group = batch.group();
+            let term = batch.term();
+            let first_index = batch.first_index();
+
+            let mut indices = Vec::with_capacity(batch.len());
+            for i in 0..batch.len() {
+                let (offset, len) = batch.location(i);
+                indices.push(EntryIndex {
+                    term,
+                    ctx: batch.ctx(i).to_vec(),
+                    file_id: 0,
+                    block_offset: 0,
+                    block_len: 0,
+                    offset,
+                    len,
+                });
+            }
+
+            let raw = Arc::new(batch.take_raw());
+            let block_id = self.core.next_block_id.fetch_add(1, Ordering::SeqCst);
+            for index in indices.iter_mut() {
+                index.block_offset = block_id;
+                index.block_len = raw.len();
+            }
+            self.core.blocks.write().await.insert(block_id, raw);
+
+            self.core.states.append(group, first_index, indices).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn truncate(&self, group: u64, index: u64) -> Result<()> {
+        self.core.states.truncate(group, index).await
+    }
+
+    pub async fn compact(&self, group: u64, index: u64) -> Result<()> {
+        self.core.states.compact(group, index).await
+    }
+
+    pub async fn seed_snapshot_boundary(&self, group: u64, index: u64, term: u64) -> Result<()> {
+        self.core.states.seed_snapshot_boundary(group, index, term).await
+    }
+
+    pub async fn mask(&self, group: u64, index: u64) -> Result<()> {
+        self.core.states.mask(group, index).await
+    }
+
+    pub async fn may_entries(
+        &self,
+        group: u64,
+        index: u64,
+        max_len: usize,
+        unmask: bool,
+    ) -> Result<Vec<Entry>> {
+        let (first_index, indices) = self
+            .core
+            .states
+            .may_entries(group, index, max_len, unmask)
+            .await?;
+        self.to_entries(group, first_index, indices).await
+    }
+
+    pub async fn entries(&self, group: u64, index: u64, max_len: usize) -> Result<Vec<Entry>> {
+        let indices = self.core.states.entries(group, index, max_len).await?;
+        self.to_entries(group, index, indices).await
+    }
+
+    pub async fn term(&self, group: u64, index: u64) -> Result<Option<u64>> {
+        self.core.states.term(group, index).await
+    }
+
+    pub async fn ctx(&self, group: u64, index: u64) -> Result<Option<Vec<u8>>> {
+        self.core.states.ctx(group, index).await
+    }
+
+    pub async fn first_index(&self, group: u64) -> Result<u64> {
+        self.core.states.first_index(group).await
+    }
+
+    pub async fn last_index(&self, group: u64) -> Result<u64> {
+        self.core.states.last_index(group).await
+    }
+
+    pub async fn masked_first_index(&self, group: u64) -> Result<u64> {
+        self.core.states.masked_first_index(group).await
+    }
+
+    pub async fn masked_last_index(&self, group: u64) -> Result<u64> {
+        self.core.states.masked_last_index(group).await
+    }
+
+    pub async fn put(&self, group: u64, key: Vec<u8>, value: Vec<u8>) -> Result<()> {
+        self.core.states.put(group, key, value).await
+    }
+
+    pub async fn delete(&self, group: u64, key: Vec<u8>) -> Result<()> {
+        self.core.states.delete(group, key).await
+    }
+
+    pub async fn get(&self, group: u64, key: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        self.core.states.get(group, key).await
+    }
+
+    async fn to_entries(
+        &self,
+        group: u64,
+        first_index: u64,
+        indices: Vec<EntryIndex>,
+    ) -> Result<Vec<Entry>> {
+        let blocks = self.core.blocks.read().await;
+        let mut entries = Vec::with_capacity(indices.len());
+        for (i, ei) in indices.into_iter().enumerate() {
+            let block = blocks
+                .get(&ei.block_offset)
+                .expect("block referenced by a live index must exist");
+            let data = block[ei.offset..ei.offset + ei.len].to_vec();
+            entries.push(Entry {
+                group,
+                term: ei.term,
+                index: first_index + i as u64,
+                ctx: ei.ctx,
+                data,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use test_log::test;
+
+    use super::*;
+    use crate::raft_log_store::entry::RaftLogBatchBuilder;
+
+    fn is_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn ensure_send_sync() {
+        is_send_sync::<MemRaftLogStore>()
+    }
+
+    #[test(tokio::test)]
+    async fn test_mem_raft_log() {
+        let store = MemRaftLogStore::new();
+        store.add_group(1).await.unwrap();
+
+        let mut builder = RaftLogBatchBuilder::default();
+        for index in 1..=16 {
+            builder.add(1, 1, index, b"some-ctx", &data(1, index));
+        }
+        for batch in builder.build() {
+            store.append(vec![batch]).await.unwrap();
+        }
+
+        let entries = store.entries(1, 1, usize::MAX).await.unwrap();
+        assert_eq!(
+            entries.into_iter().map(|entry| entry.data).collect_vec(),
+            (1..=16).into_iter().map(|index| data(1, index)).collect_vec()
+        );
+
+        store.compact(1, 9).await.unwrap();
+        assert!(store.entries(1, 8, usize::MAX).await.is_err());
+        let entries = store.entries(1, 9, usize::MAX).await.unwrap();
+        assert_eq!(
+            entries.into_iter().map(|entry| entry.data).collect_vec(),
+            (9..=16).into_iter().map(|index| data(1, index)).collect_vec()
+        );
+
+        store.truncate(1, 11).await.unwrap();
+        let entries = store.entries(1, 9, usize::MAX).await.unwrap();
+        assert_eq!(
+            entries.into_iter().map(|entry| entry.data).collect_vec(),
+            (9..=10).into_iter().map(|index| data(1, index)).collect_vec()
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_mem_kv() {
+        let store = MemRaftLogStore::new();
+        store.add_group(1).await.unwrap();
+        store.put(1, b"k1".to_vec(), b"v1".to_vec()).await.unwrap();
+        assert_eq!(
+            store.get(1, b"k1".to_vec()).await.unwrap(),
+            Some(b"v1".to_vec())
+        );
+        store.delete(1, b"k1".to_vec()).await.unwrap();
+        assert_eq!(store.get(1, b"k1".to_vec()).await.unwrap(), None);
+    }
+
+    fn data(term: u64, index: u64) -> Vec<u8> {
+        format!("{:15}-{:32}", term, index).into()
+    }
+}
@@ -1,11 +1,20 @@
 pub mod block_cache;
 pub mod entry;
 pub mod error;
+pub mod gc;
 pub mod log;
 pub mod mem;
+pub mod mem_store;
 pub mod metrics;
 pub mod store;
 
 const DEFAULT_LOG_BATCH_SIZE: usize = 8 << 10;
 
+/// Default [`entry::RaftLogBatchBuilder`] compression threshold: batches whose raw data segment is
+/// smaller than this are left uncompressed, since LZ4 framing overhead can make small batches
+/// (e.g. a lone config-change or no-op entry) larger on disk than storing them raw.
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 256;
+
+pub use gc::{RaftLogStoreGcWorker, RaftLogStoreGcWorkerOptions, DEFAULT_GC_INTERVAL};
+pub use mem_store::MemRaftLogStore;
 pub use store::RaftLogStore;
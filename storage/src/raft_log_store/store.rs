@@ -5,10 +5,11 @@ use futures_async_stream::for_await;
 use tracing::trace;
 
 use super::block_cache::BlockCache;
-use super::entry::{Compact, Entry as LogEntry, Kv, Mask, RaftLogBatch, Truncate};
+use super::entry::{Compact, Entry as LogEntry, Kv, Mask, RaftLogBatch, RaftLogBatchBuilder, Truncate};
 use super::log::{Log, LogOptions, Persist};
 use super::mem::{EntryIndex, MemStates};
 use super::metrics::{RaftLogStoreMetrics, RaftLogStoreMetricsRef};
+use super::DEFAULT_COMPRESSION_THRESHOLD;
 use crate::error::Result;
 
 #[derive(Clone, Debug)]
@@ -27,6 +28,15 @@ pub struct RaftLogStoreOptions {
     pub log_file_capacity: usize,
     pub block_cache_capacity: usize,
     pub persist: Persist,
+    /// When `true`, a torn trailing record on open is reported as an error instead of being
+    /// repaired by truncation.
+    pub strict_repair: bool,
+    /// Forwarded to every [`RaftLogBatchBuilder`] handed out by [`RaftLogStore::batch_builder`]:
+    /// batches whose raw data is smaller than this are persisted uncompressed. Raise this for
+    /// groups dominated by small control entries (votes, config changes) to skip paying LZ4
+    /// framing overhead on payloads too small to benefit from it; lower it for write-heavy groups
+    /// with large proposals to compress more aggressively and save disk space.
+    pub compression_threshold: usize,
 }
 
 struct AppendContext {
@@ -42,6 +52,7 @@ struct RaftLogStoreCore {
     log: Log,
     states: MemStates,
     block_cache: BlockCache,
+    compression_threshold: usize,
 
     metrics: RaftLogStoreMetricsRef,
 }
@@ -69,6 +80,7 @@ impl RaftLogStore {
             path: options.log_dir_path,
             log_file_capacity: options.log_file_capacity,
             persist: options.persist,
+            strict_repair: options.strict_repair,
 
             metrics: metrics.clone(),
         };
@@ -131,6 +143,7 @@ impl RaftLogStore {
                 log,
                 states,
                 block_cache: BlockCache::new(options.block_cache_capacity, metrics.clone()),
+                compression_threshold: options.compression_threshold,
 
                 metrics,
             }),
@@ -149,6 +162,13 @@ impl RaftLogStore {
         self.core.states.remove_group(group).await
     }
 
+    /// Returns a [`RaftLogBatchBuilder`] pre-configured with this store's
+    /// [`RaftLogStoreOptions::compression_threshold`], ready for callers to [`append`][Self::append]
+    /// its output to this same store.
+    pub fn batch_builder(&self) -> RaftLogBatchBuilder {
+        RaftLogBatchBuilder::new(self.core.compression_threshold)
+    }
+
     /// Append raft log batch to [`RaftLogStore`].
     pub async fn append(&self, batches: Vec<RaftLogBatch>) -> Result<()> {
         let start = Instant::now();
@@ -205,6 +225,9 @@ impl RaftLogStore {
                 index.block_len = block_len;
             }
 
+            let group_start = Instant::now();
+            let group_bytes = handle.len as f64;
+
             // Fill block cache.
             self.core
                 .block_cache
@@ -216,6 +239,15 @@ impl RaftLogStore {
                 .states
                 .append(ctx.group, ctx.first_index, ctx.indices)
                 .await?;
+
+            self.core
+                .metrics
+                .group_append_latency_histogram(ctx.group)
+                .observe(group_start.elapsed().as_secs_f64());
+            self.core
+                .metrics
+                .group_bytes_gauge(ctx.group)
+                .add(group_bytes);
         }
 
         self.core
@@ -246,6 +278,18 @@ impl RaftLogStore {
         Ok(())
     }
 
+    /// Seeds a never-before-written group's log to begin just after a bootstrap snapshot's
+    /// `index`/`term`. See [`MemStates::seed_snapshot_boundary`] for the full contract.
+    ///
+    /// Note: unlike [`Self::compact`], this isn't yet recorded in the write-ahead log, so a crash
+    /// right after seeding and before this group accumulates real entries of its own would lose
+    /// the seeded boundary on recovery. Bootstrapping a node is expected to retry from scratch
+    /// (fetching a fresh snapshot) in that case, the same way it would if the crash happened one
+    /// step earlier, before seeding.
+    pub async fn seed_snapshot_boundary(&self, group: u64, index: u64, term: u64) -> Result<()> {
+        self.core.states.seed_snapshot_boundary(group, index, term).await
+    }
+
     /// Mask any indices before the given index.
     ///
     /// Masked indices are not deleted from the state, but can only be accessed with `unmask` set to
@@ -276,7 +320,7 @@ impl RaftLogStore {
             .await?;
         let mut entries = Vec::with_capacity(indices.len());
         for (i, ei) in indices.into_iter().enumerate() {
-            let data = self.entry_data(&ei).await?;
+            let data = self.entry_data(group, &ei).await?;
             let entry = Entry {
                 group,
                 term: ei.term,
@@ -296,7 +340,7 @@ impl RaftLogStore {
         let indices = self.core.states.entries(group, index, max_len).await?;
         let mut entries = Vec::with_capacity(indices.len());
         for (i, ei) in indices.into_iter().enumerate() {
-            let data = self.entry_data(&ei).await?;
+            let data = self.entry_data(group, &ei).await?;
             let entry = Entry {
                 group,
                 term: ei.term,
@@ -361,11 +405,41 @@ impl RaftLogStore {
     pub async fn get(&self, group: u64, key: Vec<u8>) -> Result<Option<Vec<u8>>> {
         self.core.states.get(group, key).await
     }
+
+    /// Remove frozen log segments that no group references any more.
+    ///
+    /// This is safe to call concurrently with appends/reads: only segments strictly before the
+    /// smallest file id still pinned by some group's remaining entries are removed, and the
+    /// block cache is invalidated for each removed segment so no stale block can be served.
+    ///
+    /// Returns the number of segments removed.
+    pub async fn gc(&self) -> Result<usize> {
+        let safe_file_id = match self.core.states.min_pinned_file_id().await {
+            Some(id) => id,
+            // No group currently holds any entry, so there's no lower bound on what's safe to
+            // remove: every already-frozen segment qualifies. `Log::gc` only ever removes frozen
+            // segments (never the active one it's still writing to), so handing it `u64::MAX`
+            // here is safe even though no real file will ever reach that id.
+            None => u64::MAX,
+        };
+        let removed = self.core.log.gc(safe_file_id).await?;
+        for file_id in &removed {
+            self.core.block_cache.invalidate_file(*file_id);
+        }
+        Ok(removed.len())
+    }
 }
 
 impl RaftLogStore {
-    async fn entry_data(&self, index: &EntryIndex) -> Result<Vec<u8>> {
+    async fn entry_data(&self, group: u64, index: &EntryIndex) -> Result<Vec<u8>> {
         trace!("read entry: {:?}", index);
+
+        let cached = self.core.block_cache.get(index.file_id, index.block_offset);
+        self.core
+            .metrics
+            .group_cache_counter(group, cached.is_some())
+            .inc();
+
         let log = self.core.log.clone();
         let index_clone = index.clone();
         let read_file = async move {
@@ -426,6 +500,8 @@ mod tests {
             log_file_capacity: 100,
             block_cache_capacity: 1024,
             persist: Persist::Sync,
+            strict_repair: false,
+            compression_threshold: DEFAULT_COMPRESSION_THRESHOLD,
         };
 
         let store = RaftLogStore::open(options.clone()).await.unwrap();
@@ -536,6 +612,8 @@ mod tests {
             log_file_capacity: 100,
             block_cache_capacity: 1024,
             persist: Persist::Sync,
+            strict_repair: false,
+            compression_threshold: DEFAULT_COMPRESSION_THRESHOLD,
         };
 
         let store = RaftLogStore::open(options.clone()).await.unwrap();
@@ -599,4 +677,176 @@ mod tests {
     fn data(group: u64, term: u64, index: u64) -> Vec<u8> {
         format!("{:15}-{:15}-{:32}", group, term, index).into()
     }
+
+    #[test(tokio::test)]
+    async fn test_gc() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let options = RaftLogStoreOptions {
+            node: 0,
+            log_dir_path: tempdir.path().to_str().unwrap().to_string(),
+            // Estimated size of each compressed entry is 111.
+            log_file_capacity: 100,
+            block_cache_capacity: 1024,
+            persist: Persist::Sync,
+            strict_repair: false,
+            compression_threshold: DEFAULT_COMPRESSION_THRESHOLD,
+        };
+
+        let store = RaftLogStore::open(options.clone()).await.unwrap();
+        store.add_group(1).await.unwrap();
+
+        // Consecutive entries form a single batch, so append 4 batches of 4 entries: each append
+        // fills a segment past `log_file_capacity` and rolls a new one.
+        for first in (1..=16).step_by(4) {
+            let mut builder = RaftLogBatchBuilder::default();
+            for index in first..first + 4 {
+                builder.add(1, 1, index, b"some-ctx", &data(1, 1, index));
+            }
+            for batch in builder.build() {
+                store.append(vec![batch]).await.unwrap();
+            }
+        }
+        assert_eq!(store.core.log.frozen_file_count().await, 4);
+
+        // Nothing is obsolete yet.
+        assert_eq!(store.gc().await.unwrap(), 0);
+
+        // Compact past the first few segments.
+        store.compact(1, 9).await.unwrap();
+        let removed = store.gc().await.unwrap();
+        assert!(removed > 0);
+
+        // Remaining entries are still readable after GC.
+        let entries = store.entries(1, 9, usize::MAX).await.unwrap();
+        assert_eq!(
+            entries.into_iter().map(|entry| entry.data).collect_vec(),
+            (9..=16).into_iter().map(|index| data(1, 1, index)).collect_vec()
+        );
+
+        // Reopening must not choke on the missing leading segments.
+        drop(store);
+        let store = RaftLogStore::open(options).await.unwrap();
+        let entries = store.entries(1, 9, usize::MAX).await.unwrap();
+        assert_eq!(
+            entries.into_iter().map(|entry| entry.data).collect_vec(),
+            (9..=16).into_iter().map(|index| data(1, 1, index)).collect_vec()
+        );
+    }
+
+    /// Once every group's entries have been compacted away entirely, `min_pinned_file_id` has no
+    /// lower bound to report, so every already-frozen segment must still be reclaimable rather
+    /// than GC silently stopping until some group appends a fresh entry.
+    #[test(tokio::test)]
+    async fn test_gc_reclaims_all_frozen_segments_once_group_fully_compacted() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let options = RaftLogStoreOptions {
+            node: 0,
+            log_dir_path: tempdir.path().to_str().unwrap().to_string(),
+            // Estimated size of each compressed entry is 111.
+            log_file_capacity: 100,
+            block_cache_capacity: 1024,
+            persist: Persist::Sync,
+            strict_repair: false,
+            compression_threshold: DEFAULT_COMPRESSION_THRESHOLD,
+        };
+
+        let store = RaftLogStore::open(options).await.unwrap();
+        store.add_group(1).await.unwrap();
+
+        // Consecutive entries form a single batch, so append 4 batches of 4 entries: each append
+        // fills a segment past `log_file_capacity` and rolls a new one.
+        for first in (1..=16).step_by(4) {
+            let mut builder = RaftLogBatchBuilder::default();
+            for index in first..first + 4 {
+                builder.add(1, 1, index, b"some-ctx", &data(1, 1, index));
+            }
+            for batch in builder.build() {
+                store.append(vec![batch]).await.unwrap();
+            }
+        }
+        assert_eq!(store.core.log.frozen_file_count().await, 4);
+
+        // Compact past the last index this group ever held, so it pins nothing at all.
+        store.compact(1, 17).await.unwrap();
+        assert_eq!(store.core.states.min_pinned_file_id().await, None);
+
+        let removed = store.gc().await.unwrap();
+        assert_eq!(removed, 4);
+        assert_eq!(store.core.log.frozen_file_count().await, 0);
+    }
+
+    /// Concurrent appends from independent groups should share a single `Log` flush/fsync
+    /// rather than each paying for its own, since [`Log::append`] queues concurrent writers and
+    /// flushes them together.
+    #[test(tokio::test)]
+    async fn test_concurrent_group_appends_share_fsync() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let options = RaftLogStoreOptions {
+            // A node id no other test uses, so the sync histogram counts only this test's syncs.
+            node: 8,
+            log_dir_path: tempdir.path().to_str().unwrap().to_string(),
+            log_file_capacity: 1 << 20,
+            block_cache_capacity: 1024,
+            persist: Persist::Sync,
+            strict_repair: false,
+            compression_threshold: DEFAULT_COMPRESSION_THRESHOLD,
+        };
+
+        let store = RaftLogStore::open(options).await.unwrap();
+        for group in 1..=8u64 {
+            store.add_group(group).await.unwrap();
+        }
+
+        let before = store.core.metrics.sync_latency_histogram.get_sample_count();
+
+        let mut handles = Vec::new();
+        for group in 1..=8u64 {
+            let store = store.clone();
+            handles.push(tokio::spawn(async move {
+                let mut builder = RaftLogBatchBuilder::default();
+                builder.add(group, 1, 1, b"some-ctx", &data(group, 1, 1));
+                for batch in builder.build() {
+                    store.append(vec![batch]).await.unwrap();
+                }
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let after = store.core.metrics.sync_latency_histogram.get_sample_count();
+        // 8 concurrent appends share far fewer fsyncs than one per append.
+        assert!(after - before < 8);
+    }
+
+    #[test(tokio::test)]
+    async fn test_group_metrics() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let options = RaftLogStoreOptions {
+            node: 7,
+            log_dir_path: tempdir.path().to_str().unwrap().to_string(),
+            log_file_capacity: 1 << 20,
+            block_cache_capacity: 1024,
+            persist: Persist::Sync,
+            strict_repair: false,
+            compression_threshold: DEFAULT_COMPRESSION_THRESHOLD,
+        };
+
+        let store = RaftLogStore::open(options).await.unwrap();
+        store.add_group(1).await.unwrap();
+
+        let mut builder = RaftLogBatchBuilder::default();
+        builder.add(1, 1, 1, b"some-ctx", &data(1, 1, 1));
+        for batch in builder.build() {
+            store.append(vec![batch]).await.unwrap();
+        }
+
+        assert_eq!(store.core.metrics.group_append_latency_histogram(1).get_sample_count(), 1);
+        assert!(store.core.metrics.group_bytes_gauge(1).get() > 0.0);
+
+        store.entries(1, 1, usize::MAX).await.unwrap();
+        let hits = store.core.metrics.group_cache_counter(1, true).get();
+        let misses = store.core.metrics.group_cache_counter(1, false).get();
+        assert_eq!(hits + misses, 1.0);
+    }
 }
@@ -64,16 +64,73 @@ impl<'a> Bloom<'a> {
 
     /// Build bloom filter from key hashes
     pub fn build_from_key_hashes(keys: &[u32], bits_per_key: usize) -> Vec<u8> {
+        let (nbits, k) = Self::params(keys.len(), bits_per_key);
+        let mut filter = Self::set_bits(keys, nbits, k);
+        filter.put_u8(k as u8);
+        filter
+    }
+
+    /// Number of key hashes below which [`Self::build_from_key_hashes_parallel`] falls back to
+    /// building on the calling thread: below this, spinning up the thread pool costs more than
+    /// it saves.
+    const PARALLEL_BUILD_MIN_KEYS: usize = 4096;
+
+    /// Number of shards [`Self::build_from_key_hashes_parallel`] partitions `keys` into. Kept
+    /// small and fixed rather than scaled to `num_cpus`, since bloom construction is one of many
+    /// things competing for cores during a compaction.
+    const PARALLEL_BUILD_SHARDS: usize = 4;
+
+    /// Like [`Self::build_from_key_hashes`], but once `keys` is large enough to be worth it,
+    /// partitions it across [`Self::PARALLEL_BUILD_SHARDS`] threads that each set bits for their
+    /// own shard into an independent bitset, then ORs the shards' bitsets together. Every shard
+    /// is sized against the *total* key count (`params` below), so the OR of the shards' bitsets
+    /// is bit-for-bit identical to what [`Self::build_from_key_hashes`] would have produced -
+    /// setting a bit twice (once per shard, if two shards happen to hash to the same position) is
+    /// idempotent under OR.
+    pub fn build_from_key_hashes_parallel(keys: &[u32], bits_per_key: usize) -> Vec<u8> {
+        if keys.len() < Self::PARALLEL_BUILD_MIN_KEYS {
+            return Self::build_from_key_hashes(keys, bits_per_key);
+        }
+        let (nbits, k) = Self::params(keys.len(), bits_per_key);
+        let shard_size =
+            (keys.len() + Self::PARALLEL_BUILD_SHARDS - 1) / Self::PARALLEL_BUILD_SHARDS;
+        let shards: Vec<Vec<u8>> = std::thread::scope(|scope| {
+            keys.chunks(shard_size.max(1))
+                .map(|chunk| scope.spawn(move || Self::set_bits(chunk, nbits, k)))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        let mut filter = shards[0].clone();
+        for shard in &shards[1..] {
+            for (byte, shard_byte) in filter.iter_mut().zip(shard.iter()) {
+                *byte |= shard_byte;
+            }
+        }
+        filter.put_u8(k as u8);
+        filter
+    }
+
+    /// Derives the bitset size (`nbits`, always a multiple of 8) and number of hash functions
+    /// (`k`) a bloom filter over `key_count` keys at `bits_per_key` should use. Shared by the
+    /// sequential and parallel builders so they agree byte-for-byte on layout.
+    fn params(key_count: usize, bits_per_key: usize) -> (usize, u32) {
         // 0.69 is approximately ln(2)
         let k = ((bits_per_key as f64) * 0.69) as u32;
         // limit k in [1, 30]
         let k = k.min(30).max(1);
         // For small len(keys), we set a minimum bloom filter length to avoid high FPR
-        let nbits = (keys.len() * bits_per_key).max(64);
+        let nbits = (key_count * bits_per_key).max(64);
         let nbytes = (nbits + 7) / 8;
         // nbits is always multiplication of 8
-        let nbits = nbytes * 8;
-        let mut filter = Vec::with_capacity(nbytes + 1);
+        (nbytes * 8, k)
+    }
+
+    /// Sets the `k`-hash-function bits for each of `keys` into a fresh `nbits`-sized bitset.
+    fn set_bits(keys: &[u32], nbits: usize, k: u32) -> Vec<u8> {
+        let nbytes = nbits / 8;
+        let mut filter = Vec::with_capacity(nbytes);
         filter.resize(nbytes, 0);
         for h in keys {
             let mut h = *h;
@@ -84,10 +141,14 @@ impl<'a> Bloom<'a> {
                 h = h.wrapping_add(delta);
             }
         }
-        filter.put_u8(k as u8);
         filter
     }
 
+    /// Number of hash functions the filter was built with.
+    pub fn num_hashes(&self) -> u8 {
+        self.k
+    }
+
     /// Check if a bloom filter may contain some data
     pub fn may_contain(&self, mut h: u32) -> bool {
         if self.k > 30 {
@@ -140,4 +201,23 @@ mod tests {
         assert!(!f.may_contain(check_hash[2]));
         assert!(!f.may_contain(check_hash[3]));
     }
+
+    #[test]
+    fn test_parallel_build_matches_sequential_build() {
+        let hashes: Vec<u32> = (0..(Bloom::PARALLEL_BUILD_MIN_KEYS as u32 * 3))
+            .map(|i| farmhash::fingerprint32(&i.to_le_bytes()))
+            .collect();
+        let sequential = Bloom::build_from_key_hashes(&hashes, 10);
+        let parallel = Bloom::build_from_key_hashes_parallel(&hashes, 10);
+        assert_eq!(sequential, parallel);
+
+        let check_hashes: Vec<u32> = (0..100)
+            .map(|i| farmhash::fingerprint32(format!("not-a-member-{}", i).as_bytes()))
+            .collect();
+        let f_sequential = Bloom::new(&sequential);
+        let f_parallel = Bloom::new(&parallel);
+        for h in hashes.iter().chain(check_hashes.iter()) {
+            assert_eq!(f_sequential.may_contain(*h), f_parallel.may_contain(*h));
+        }
+    }
 }
@@ -1,9 +1,12 @@
 use std::io::Read;
+use std::ops::RangeInclusive;
 use std::{cmp, ptr};
 
 use bytes::{Buf, BufMut};
+use runkv_common::coding::CompressionAlgorithm;
 
-use crate::components::KeyComparator;
+use crate::components::{BlobRef, KeyComparator};
+use crate::{Error, Result};
 
 const MASK: u32 = 128;
 
@@ -171,6 +174,14 @@ pub fn key_diff<'a, 'b>(base: &'a [u8], target: &'b [u8]) -> &'b [u8] {
     bytes_diff(base, target)
 }
 
+/// Raw on-disk tag scheme for an entry's value, shared by every block regardless of whether the
+/// entry was put inline ([`raw_value`]) or separated into a blob object ([`raw_blob_ref`]):
+///
+/// ```plain
+/// tag 0: delete
+/// tag 1: put, value stored inline right after the tag
+/// tag 2: put, value separated into a blob object; a `BlobRef` pointer stored after the tag
+/// ```
 pub fn raw_value(v: Option<&[u8]>) -> Vec<u8> {
     match v {
         None => vec![0],
@@ -178,10 +189,45 @@ pub fn raw_value(v: Option<&[u8]>) -> Vec<u8> {
     }
 }
 
+/// Encodes a separated value's pointer as a tag-2 raw entry, the [`BlobRef`] counterpart of
+/// [`raw_value`]. See [`decode_entry`] for the matching decoder.
+pub fn raw_blob_ref(blob_ref: &BlobRef) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(1 + BlobRef::ENCODED_LEN);
+    buf.put_u8(2);
+    blob_ref.encode(&mut buf);
+    buf
+}
+
+/// Decodes a raw entry's value, tolerating both tags a plain put can have: inline (tag 1) and
+/// blob-separated (tag 2). Returns the still-tagged-away payload bytes for either, so a
+/// tombstone-detection call site that only cares about `is_none()` keeps working unchanged
+/// whether or not the entry it's looking at happens to be blob-separated. Callers that need to
+/// tell the two tags apart (e.g. compaction deciding whether to copy a pointer forward) should use
+/// [`decode_entry`] instead.
 pub fn value(raw: &[u8]) -> Option<&[u8]> {
     match raw[0] {
         0 => None,
-        1 => Some(&raw[1..]),
+        1 | 2 => Some(&raw[1..]),
+        _ => unreachable!(),
+    }
+}
+
+/// Decoded form of a raw entry's value, distinguishing a blob-separated pointer from an inline
+/// put, unlike [`value`]. Produced by [`decode_entry`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RawValue<'a> {
+    Delete,
+    Put(&'a [u8]),
+    BlobRef(BlobRef),
+}
+
+/// Decodes a raw entry's value into its tagged form. See [`raw_value`] and [`raw_blob_ref`] for
+/// the encoders.
+pub fn decode_entry(raw: &[u8]) -> RawValue<'_> {
+    match raw[0] {
+        0 => RawValue::Delete,
+        1 => RawValue::Put(&raw[1..]),
+        2 => RawValue::BlobRef(BlobRef::decode(&mut &raw[1..])),
         _ => unreachable!(),
     }
 }
@@ -195,6 +241,42 @@ pub fn compare_full_key(lhs: &[u8], rhs: &[u8]) -> std::cmp::Ordering {
     lkey.cmp(rkey).then_with(|| lts.cmp(rts))
 }
 
+/// Legal compression level range for `algorithm`. `0` means "use the codec's own default" for
+/// both [`CompressionAlgorithm::Lz4`] and [`CompressionAlgorithm::Zstd`], matching the underlying
+/// `lz4`/`zstd` crates' own conventions. [`CompressionAlgorithm::None`] has no concept of a level,
+/// so `0` is the only legal value.
+pub fn compression_level_range(algorithm: CompressionAlgorithm) -> RangeInclusive<i32> {
+    match algorithm {
+        CompressionAlgorithm::None => 0..=0,
+        CompressionAlgorithm::Lz4 => 0..=16,
+        CompressionAlgorithm::Zstd => zstd::compression_level_range(),
+    }
+}
+
+/// Validates `level` against `algorithm`'s legal range, returning a typed error instead of
+/// silently clamping an out-of-range level into something the codec happens to accept.
+pub fn validate_compression_level(algorithm: CompressionAlgorithm, level: i32) -> Result<()> {
+    let range = compression_level_range(algorithm);
+    if !range.contains(&level) {
+        return Err(Error::invalid_compression_level(format!(
+            "level {} not in legal range {:?} for {:?}",
+            level, range, algorithm
+        )));
+    }
+    Ok(())
+}
+
+/// Trains a zstd dictionary from a sample of values, capped at `max_size` bytes. Returns an empty
+/// dictionary (meaning "no dictionary") if there are too few samples to train on or zstd fails to
+/// find shared patterns, since an untrained dictionary would only add overhead.
+pub fn train_dictionary(samples: &[&[u8]], max_size: usize) -> Vec<u8> {
+    if samples.len() < 8 {
+        return vec![];
+    }
+    let samples: Vec<Vec<u8>> = samples.iter().map(|s| s.to_vec()).collect();
+    zstd::dict::from_samples(&samples, max_size).unwrap_or_default()
+}
+
 #[derive(Clone)]
 pub struct FullKeyComparator;
 
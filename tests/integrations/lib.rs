@@ -2,6 +2,7 @@ use std::fs::read_to_string;
 
 mod test_concurrent_put_get;
 mod test_multi_raft_group_concurrent_put_get;
+mod test_read_only_wheel;
 
 const PORT_CONFIG_PATH: &str = "etc/port.toml";
 
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use runkv_exhauster::config::ExhausterConfig;
+use runkv_exhauster::{bootstrap_exhauster, build_exhauster_with_object_store};
+use runkv_proto::common::Endpoint;
+use runkv_proto::kv::kv_service_client::KvServiceClient;
+use runkv_proto::kv::{GetRequest, PutRequest};
+use runkv_proto::meta::KeyRange;
+use runkv_proto::wheel::wheel_service_client::WheelServiceClient;
+use runkv_proto::wheel::{AddEndpointsRequest, AddKeyRangeRequest};
+use runkv_rudder::config::RudderConfig;
+use runkv_rudder::{bootstrap_rudder, build_rudder_with_object_store};
+use runkv_storage::MemObjectStore;
+use runkv_wheel::config::WheelConfig;
+use runkv_wheel::{bootstrap_wheel, build_wheel_with_object_store};
+use test_log::test;
+use tonic::Request;
+
+use crate::concat_toml;
+
+const RUDDER_CONFIG_PATH: &str = "etc/rudder.toml";
+const WHEEL_CONFIG_PATH: &str = "etc/wheel.toml";
+const EXHAUSTER_CONFIG_PATH: &str = "etc/exhauster.toml";
+const LSM_TREE_CONFIG_PATH: &str = "etc/lsm_tree.toml";
+
+#[test(tokio::test)]
+async fn test_read_only_wheel() {
+    let mut port = crate::port("test_read_only_wheel");
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let raft_log_dir_path = Path::new(tempdir.path())
+        .join("raft")
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let rudder_config: RudderConfig = {
+        let mut config: RudderConfig =
+            toml::from_str(&concat_toml(RUDDER_CONFIG_PATH, LSM_TREE_CONFIG_PATH)).unwrap();
+        port += 1;
+        config.port = port;
+        config
+    };
+    let wheel_config: WheelConfig = {
+        let mut config: WheelConfig =
+            toml::from_str(&concat_toml(WHEEL_CONFIG_PATH, LSM_TREE_CONFIG_PATH)).unwrap();
+        config.raft_log_store.log_dir_path = raft_log_dir_path;
+        port += 1;
+        config.port = port;
+        config.rudder.port = rudder_config.port;
+        config.read_only = true;
+        config
+    };
+    let exhauster_config: ExhausterConfig = {
+        let mut config: ExhausterConfig =
+            toml::from_str(&read_to_string(EXHAUSTER_CONFIG_PATH).unwrap()).unwrap();
+        port += 1;
+        config.port = port;
+        config.rudder.port = rudder_config.port;
+        config
+    };
+
+    let object_store = Arc::new(MemObjectStore::default());
+
+    let (rudder, rudder_workers) =
+        build_rudder_with_object_store(&rudder_config, object_store.clone())
+            .await
+            .unwrap();
+
+    let (wheel, wheel_workers) = build_wheel_with_object_store(&wheel_config, object_store.clone())
+        .await
+        .unwrap();
+
+    let (exhuaster, exhauster_workers) =
+        build_exhauster_with_object_store(&exhauster_config, object_store)
+            .await
+            .unwrap();
+
+    tokio::spawn(async move { bootstrap_rudder(&rudder_config, rudder, rudder_workers).await });
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    tokio::spawn(async move {
+        bootstrap_exhauster(&exhauster_config, exhuaster, exhauster_workers).await
+    });
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let wheel_config_clone = wheel_config.clone();
+    tokio::spawn(async move { bootstrap_wheel(&wheel_config_clone, wheel, wheel_workers).await });
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let mut wheel_client = WheelServiceClient::connect(format!(
+        "http://{}:{}",
+        wheel_config.host, wheel_config.port
+    ))
+    .await
+    .unwrap();
+    wheel_client
+        .add_endpoints(AddEndpointsRequest {
+            endpoints: HashMap::from_iter([(
+                wheel_config.id,
+                Endpoint {
+                    host: wheel_config.host.to_owned(),
+                    port: wheel_config.port as u32,
+                },
+            )]),
+        })
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    wheel_client
+        .add_key_range(Request::new(AddKeyRangeRequest {
+            key_range: Some(KeyRange {
+                start_key: b"k0".to_vec(),
+                end_key: b"kz".to_vec(),
+            }),
+            group: 10,
+            raft_nodes: vec![11, 12, 13],
+            nodes: HashMap::from_iter([(11, wheel_config.id), (12, wheel_config.id), (13, wheel_config.id)]),
+        }))
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_secs(10)).await;
+
+    let mut kv_client = KvServiceClient::connect(format!(
+        "http://{}:{}",
+        wheel_config.host, wheel_config.port
+    ))
+    .await
+    .unwrap();
+
+    // A read-only node never proposes, so writes are rejected outright.
+    let put_err = kv_client
+        .put(Request::new(PutRequest {
+            key: b"k0".to_vec(),
+            value: b"v0".to_vec(),
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(put_err.code(), tonic::Code::Internal);
+    assert!(put_err.message().contains("read-only"));
+
+    // Reads still go through, served from this node's locally applied state rather than being
+    // rejected alongside writes.
+    let value = kv_client
+        .get(Request::new(GetRequest {
+            key: b"k0".to_vec(),
+            sequence: 0,
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .value;
+    assert!(value.is_empty());
+}
@@ -0,0 +1,60 @@
+//! Microbenchmark for the per-peer grouping step `RaftWorker::send_messages` does on every
+//! `handle_ready` call: splitting a flat `Vec<Message>` into per-`to` batches. Compares
+//! reallocating the grouping `HashMap`/`Vec`s from scratch every call (the old behavior) against
+//! reusing a scratch map across calls and `clear()`ing its buckets instead (what
+//! `RaftWorker::raft_node_msgs_scratch` now does), to show the allocations the reuse removes.
+
+use std::collections::HashMap;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+#[derive(Clone)]
+struct FakeMessage {
+    to: u64,
+}
+
+const PEERS: u64 = 3;
+const MESSAGES_PER_READY: u64 = 64;
+
+fn fake_messages() -> Vec<FakeMessage> {
+    (0..MESSAGES_PER_READY)
+        .map(|i| FakeMessage { to: i % PEERS })
+        .collect()
+}
+
+fn group_fresh(messages: Vec<FakeMessage>) -> HashMap<u64, Vec<FakeMessage>> {
+    let mut grouped = HashMap::new();
+    for msg in messages {
+        grouped
+            .entry(msg.to)
+            .or_insert_with(|| Vec::with_capacity(16))
+            .push(msg);
+    }
+    grouped
+}
+
+fn group_reused(messages: Vec<FakeMessage>, scratch: &mut HashMap<u64, Vec<FakeMessage>>) {
+    for msgs in scratch.values_mut() {
+        msgs.clear();
+    }
+    for msg in messages {
+        scratch
+            .entry(msg.to)
+            .or_insert_with(|| Vec::with_capacity(16))
+            .push(msg);
+    }
+}
+
+fn bench_raft_send_messages_grouping(c: &mut Criterion) {
+    c.bench_function("send_messages grouping - fresh map per call", |b| {
+        b.iter(|| group_fresh(fake_messages()));
+    });
+
+    let mut scratch = HashMap::new();
+    c.bench_function("send_messages grouping - reused scratch map", |b| {
+        b.iter(|| group_reused(fake_messages(), &mut scratch));
+    });
+}
+
+criterion_group!(benches, bench_raft_send_messages_grouping);
+criterion_main!(benches);
@@ -0,0 +1,129 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use tokio::sync::Notify;
+
+lazy_static! {
+    /// Anchor for [`RealClock::now`]. [`std::time::Instant`] itself can't be constructed from an
+    /// arbitrary value, which is what a mock clock would need to do, so [`Clock::now`] returns a
+    /// [`Duration`] since this fixed point instead.
+    static ref PROCESS_START: Instant = Instant::now();
+}
+
+/// Abstracts wall-clock time behind a trait so [`crate::worker::raft::RaftWorker`]'s tick/backoff
+/// loop can be driven by a [`MockClock`] in tests instead of real sleeps, making election and
+/// heartbeat timing deterministic and fast to test.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    /// Monotonically non-decreasing; only meaningful as a difference against another call on the
+    /// same `Clock`, like [`std::time::Instant::elapsed`].
+    fn now(&self) -> Duration;
+
+    /// Resolves once at least `duration` of this clock's time has passed.
+    async fn sleep(&self, duration: Duration);
+}
+
+pub type ClockRef = Arc<dyn Clock>;
+
+/// [`Clock`] backed by the real wall clock and [`tokio::time`].
+#[derive(Debug, Default)]
+pub struct RealClock;
+
+#[async_trait]
+impl Clock for RealClock {
+    fn now(&self) -> Duration {
+        PROCESS_START.elapsed()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// [`Clock`] whose time only moves when [`MockClock::advance`] is called, so tests can drive
+/// raft's tick/election timing deterministically instead of waiting out real sleeps.
+#[derive(Clone, Default)]
+pub struct MockClock(Arc<MockClockState>);
+
+#[derive(Default)]
+struct MockClockState {
+    now: AtomicU64,
+    notify: Notify,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances virtual time by `duration`, waking any [`Clock::sleep`] call whose deadline has
+    /// now passed.
+    pub fn advance(&self, duration: Duration) {
+        self.0
+            .now
+            .fetch_add(duration.as_nanos() as u64, Ordering::SeqCst);
+        self.0.notify.notify_waiters();
+    }
+}
+
+#[async_trait]
+impl Clock for MockClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.0.now.load(Ordering::SeqCst))
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        let deadline = self.now() + duration;
+        while self.now() < deadline {
+            // Subscribe before re-checking the deadline, so an `advance` landing between the
+            // check and the subscription isn't missed.
+            let notified = self.0.notify.notified();
+            if self.now() >= deadline {
+                break;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_mock_clock_sleep_waits_for_advance() {
+        let clock = MockClock::new();
+        let sleeping = {
+            let clock = clock.clone();
+            tokio::spawn(async move {
+                clock.sleep(Duration::from_secs(10)).await;
+            })
+        };
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!sleeping.is_finished());
+
+        clock.advance(Duration::from_secs(5));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!sleeping.is_finished(), "should still be short of the 10s deadline");
+
+        clock.advance(Duration::from_secs(5));
+        tokio::time::timeout(Duration::from_secs(1), sleeping)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn test_real_clock_sleep_elapses_real_time() {
+        let clock = RealClock;
+        let start = clock.now();
+        clock.sleep(Duration::from_millis(50)).await;
+        assert!(clock.now() - start >= Duration::from_millis(50));
+    }
+}
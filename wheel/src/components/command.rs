@@ -1,10 +1,14 @@
 use std::ops::Range;
 
 use runkv_common::coding::BytesSerde;
+use runkv_common::context::Context;
 use runkv_proto::kv::TxnRequest;
 use serde::{Deserialize, Serialize};
 use tokio::sync::oneshot;
 
+use crate::error::{Error, Result};
+use crate::worker::raft::Proposal;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Command {
     TxnRequest {
@@ -16,15 +20,40 @@ pub enum Command {
         index: u64,
         sequence: u64,
     },
+    /// Multiple commands proposed together as a single raft log entry, so they cost one raft
+    /// round trip and commit atomically as a unit instead of each needing its own proposal. Each
+    /// nested command keeps its own fields (e.g. `request_id`) for individual notification; only
+    /// the entry's own [`runkv_common::context::Context`] (tracing, dedup) is shared.
+    Batch(Vec<Command>),
 }
 
 impl<'de> BytesSerde<'de> for Command {}
 
+/// Packs `commands` into a single [`Command::Batch`] under one shared `context`, so a caller that
+/// would otherwise propose each command individually (e.g. a bulk load writing many keys) pays
+/// for one raft round trip and one committed log entry instead of one per command.
+pub struct BatchProposal {
+    pub commands: Vec<Command>,
+    pub context: Context,
+}
+
+impl BatchProposal {
+    pub fn encode(self) -> Result<Proposal> {
+        Ok(Proposal {
+            data: Command::Batch(self.commands)
+                .encode_to_vec()
+                .map_err(Error::serde_err)?,
+            context: self.context.encode_to_vec().map_err(Error::serde_err)?,
+        })
+    }
+}
+
 #[derive(Debug)]
 pub enum GearCommand {
     Apply {
         group: u64,
         range: Range<u64>,
+        is_leader: bool,
     },
     BuildSnapshot {
         group: u64,
@@ -37,4 +66,68 @@ pub enum GearCommand {
         snapshot: Vec<u8>,
         notifier: oneshot::Sender<()>,
     },
+    /// Asks the receiving [`crate::worker::gear::Gear`] to stop after it finishes whatever's
+    /// already queued ahead of this command. Queued on the same channel as [`Self::Apply`], so
+    /// FIFO delivery is what guarantees every already-accepted apply range is drained before
+    /// `notifier` fires — no separate draining step is needed.
+    Shutdown { notifier: oneshot::Sender<()> },
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn test_txn_request_round_trip() {
+        let command = Command::TxnRequest {
+            request_id: 1,
+            sequence: 2,
+            request: TxnRequest { ops: vec![] },
+        };
+        let decoded = Command::decode(&command.encode_to_vec().unwrap()).unwrap();
+        assert_matches!(decoded, Command::TxnRequest {
+            request_id: 1,
+            sequence: 2,
+            request: TxnRequest { ops },
+        } if ops.is_empty());
+    }
+
+    #[test]
+    fn test_compact_raft_log_round_trip() {
+        let command = Command::CompactRaftLog {
+            index: 42,
+            sequence: 7,
+        };
+        let decoded = Command::decode(&command.encode_to_vec().unwrap()).unwrap();
+        assert_matches!(
+            decoded,
+            Command::CompactRaftLog {
+                index: 42,
+                sequence: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_empty_payload_fails() {
+        assert!(Command::decode(&[]).is_err());
+    }
+
+    #[test]
+    fn test_batch_round_trip() {
+        let command = Command::Batch(vec![
+            Command::CompactRaftLog {
+                index: 1,
+                sequence: 1,
+            },
+            Command::CompactRaftLog {
+                index: 2,
+                sequence: 2,
+            },
+        ]);
+        let decoded = Command::decode(&command.encode_to_vec().unwrap()).unwrap();
+        assert_matches!(decoded, Command::Batch(commands) if commands.len() == 2);
+    }
 }
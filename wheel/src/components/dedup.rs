@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+
+use super::raft_log_store::RaftGroupLogStore;
+use crate::error::{Error, Result};
+
+const DEDUP_CACHE_KEY: &[u8] = b"dedup_cache";
+
+/// Bounds how many recent log indexes [`DedupCache`] retains. Large enough to absorb bursts of
+/// in-flight retries, small enough that the persisted blob stays cheap to load and store on every
+/// apply.
+pub const DEFAULT_DEDUP_CACHE_CAPACITY: usize = 4096;
+
+/// Restart-recoverable, bounded cache of recently applied raft log indexes, used to make
+/// re-applying the same entry (e.g. if a restart leaves the FSM's own applied-index bookkeeping
+/// lagging raft's) a no-op. Backed by `raft_log_store` so the window survives a restart exactly
+/// like [`super::fsm::ObjectLsmTreeFsm`]'s applied-index bookkeeping.
+///
+/// Deliberately keyed on the entry's raft log index rather than [`runkv_common::context::Context`]'s
+/// `request_id`: `request_id` is minted by an in-memory, process-lifetime counter
+/// (`WheelInner::request_id`) that restarts from zero on every process restart, so a persisted
+/// window keyed on it would start colliding with brand-new, unrelated requests as soon as the
+/// counter produces ids that are still sitting in the window from before the restart. A log
+/// index never resets and never repeats within a group, so it stays a valid dedup key across
+/// restarts.
+#[derive(Clone)]
+pub struct DedupCache {
+    raft_log_store: RaftGroupLogStore,
+    capacity: usize,
+}
+
+impl DedupCache {
+    pub fn new(raft_log_store: RaftGroupLogStore, capacity: usize) -> Self {
+        Self {
+            raft_log_store,
+            capacity,
+        }
+    }
+
+    async fn load(&self) -> Result<VecDeque<u64>> {
+        match self.raft_log_store.get(DEDUP_CACHE_KEY.to_vec()).await? {
+            None => Ok(VecDeque::new()),
+            Some(buf) => bincode::deserialize(&buf).map_err(Error::serde_err),
+        }
+    }
+
+    async fn store(&self, indexes: &VecDeque<u64>) -> Result<()> {
+        let buf = bincode::serialize(indexes).map_err(Error::serde_err)?;
+        self.raft_log_store.put(DEDUP_CACHE_KEY.to_vec(), buf).await
+    }
+
+    /// Checks whether `index` has already been applied within the bounded dedup window and, if
+    /// not, records it. Returns `true` if the caller should skip the entry as a duplicate.
+    ///
+    /// Does a full `raft_log_store` get+deserialize+serialize+put round trip every call, which on
+    /// a `Persist::Sync` store means an fsync on every normal apply — accepted for now since it's
+    /// what buys the restart-recoverable window; revisit (e.g. batching the persist, or trading
+    /// some recoverability for a periodic flush) if this shows up in apply-path latency.
+    pub async fn check_and_insert(&self, index: u64) -> Result<bool> {
+        let mut indexes = self.load().await?;
+        if indexes.contains(&index) {
+            return Ok(true);
+        }
+        indexes.push_back(index);
+        if indexes.len() > self.capacity {
+            indexes.pop_front();
+        }
+        self.store(&indexes).await?;
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use runkv_storage::raft_log_store::log::Persist;
+    use runkv_storage::raft_log_store::store::RaftLogStoreOptions;
+    use runkv_storage::raft_log_store::RaftLogStore;
+    use test_log::test;
+
+    use super::*;
+
+    async fn build_raft_log_store(path: &str) -> RaftLogStore {
+        let options = RaftLogStoreOptions {
+            node: 0,
+            log_dir_path: path.to_string(),
+            log_file_capacity: 64 << 20,
+            block_cache_capacity: 64 << 20,
+            persist: Persist::Sync,
+            strict_repair: false,
+            compression_threshold: runkv_storage::raft_log_store::DEFAULT_COMPRESSION_THRESHOLD,
+        };
+        RaftLogStore::open(options).await.unwrap()
+    }
+
+    #[test(tokio::test)]
+    async fn test_duplicate_index_detected() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store);
+
+        let dedup_cache = DedupCache::new(group_raft_log_store, DEFAULT_DEDUP_CACHE_CAPACITY);
+
+        assert!(!dedup_cache.check_and_insert(42).await.unwrap());
+        assert!(dedup_cache.check_and_insert(42).await.unwrap());
+        assert!(dedup_cache.check_and_insert(42).await.unwrap());
+        assert!(!dedup_cache.check_and_insert(43).await.unwrap());
+    }
+
+    #[test(tokio::test)]
+    async fn test_dedup_cache_survives_restart() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+
+        let dedup_cache = DedupCache::new(group_raft_log_store, DEFAULT_DEDUP_CACHE_CAPACITY);
+        assert!(!dedup_cache.check_and_insert(7).await.unwrap());
+
+        // Simulate a restart: a fresh `DedupCache` built atop the same `raft_log_store` must still
+        // recognize `7` as already seen.
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store);
+        let restarted = DedupCache::new(group_raft_log_store, DEFAULT_DEDUP_CACHE_CAPACITY);
+        assert!(restarted.check_and_insert(7).await.unwrap());
+    }
+
+    #[test(tokio::test)]
+    async fn test_dedup_cache_evicts_oldest_once_capacity_exceeded() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store);
+
+        const CAPACITY: usize = 2;
+        let dedup_cache = DedupCache::new(group_raft_log_store, CAPACITY);
+
+        assert!(!dedup_cache.check_and_insert(1).await.unwrap());
+        assert!(!dedup_cache.check_and_insert(2).await.unwrap());
+        assert!(!dedup_cache.check_and_insert(3).await.unwrap());
+
+        // `1` has been evicted to make room for `3`, so it's treated as new again.
+        assert!(!dedup_cache.check_and_insert(1).await.unwrap());
+        // Re-inserting `1` evicted `2`; `3` is still within the window.
+        assert!(dedup_cache.check_and_insert(3).await.unwrap());
+    }
+}
@@ -1,7 +1,10 @@
 use std::ops::Range;
+use std::time::{Duration, Instant};
 
+use async_recursion::async_recursion;
 use async_trait::async_trait;
 use bytes::Bytes;
+use runkv_common::coding::BytesSerde;
 use runkv_common::context::Context;
 use runkv_common::notify_pool::NotifyPool;
 use runkv_proto::kv::{
@@ -13,18 +16,23 @@ use runkv_storage::raft_log_store::error::RaftLogStoreError;
 use tracing::error;
 
 use super::command::Command;
+use super::dedup::{DedupCache, DEFAULT_DEDUP_CACHE_CAPACITY};
 use super::lsm_tree::ObjectStoreLsmTree;
 use super::raft_log_store::RaftGroupLogStore;
 use crate::error::{Error, Result};
 
 #[async_trait]
 pub trait Fsm: Send + Sync + Clone + 'static {
+    /// Applies `entries` to the state machine and returns the portion of the call spent handing
+    /// results off to whatever is downstream of the FSM (e.g. notifying a waiting proposer).
+    /// Callers subtract this from the total call latency so a backed-up downstream consumer shows
+    /// up as handoff latency instead of masquerading as slow FSM processing.
     async fn apply(
         &self,
         group: u64,
         is_leader: bool,
         entries: Vec<raft::prelude::Entry>,
-    ) -> Result<()>;
+    ) -> Result<Duration>;
 
     /// Load raft applied index, used for initializing or restarting raft node.
     async fn raft_applied_index(&self) -> Result<u64>;
@@ -81,6 +89,7 @@ pub struct ObjectLsmTreeFsm {
     raft_log_store: RaftGroupLogStore,
     lsm_tree: ObjectStoreLsmTree,
     txn_notify_pool: NotifyPool<u64, Result<TxnResponse>>,
+    dedup_cache: DedupCache,
 }
 
 impl std::fmt::Debug for ObjectLsmTreeFsm {
@@ -95,6 +104,10 @@ impl std::fmt::Debug for ObjectLsmTreeFsm {
 
 impl ObjectLsmTreeFsm {
     pub fn new(options: ObjectLsmTreeFsmOptions) -> Self {
+        let dedup_cache = DedupCache::new(
+            options.raft_log_store.clone(),
+            DEFAULT_DEDUP_CACHE_CAPACITY,
+        );
         Self {
             node: options.node,
             group: options.group,
@@ -103,6 +116,7 @@ impl ObjectLsmTreeFsm {
             raft_log_store: options.raft_log_store,
             lsm_tree: options.lsm_tree,
             txn_notify_pool: options.txn_notify_pool,
+            dedup_cache,
         }
     }
 
@@ -123,40 +137,73 @@ impl ObjectLsmTreeFsm {
         Ok(index)
     }
 
-    async fn apply_entry(&self, entry: raft::prelude::Entry) -> Result<()> {
+    /// Applies a single entry, returning the time spent notifying whoever is waiting on its
+    /// result (zero for entries that don't have a waiter, e.g. `CompactRaftLog`).
+    async fn apply_entry(&self, entry: raft::prelude::Entry) -> Result<Duration> {
         match entry.entry_type() {
             raft::prelude::EntryType::EntryNormal => self.apply_normal(entry).await,
             _ => todo!(),
         }
     }
 
-    async fn apply_normal(&self, entry: raft::prelude::Entry) -> Result<()> {
+    async fn apply_normal(&self, entry: raft::prelude::Entry) -> Result<Duration> {
         if entry.data.is_empty() {
-            return Ok(());
+            return Ok(Duration::ZERO);
         }
-        if cfg!(feature = "tracing") && let raft::prelude::EntryType::EntryNormal = entry.entry_type() && !entry.data.is_empty() {
+
+        let ctx = Context::decode(&entry.context).map_err(|e| Error::serde_err(e.to_string()))?;
+
+        if cfg!(feature = "tracing") {
             let span = tracing::Span::current();
-            let ctx: Context = bincode::deserialize(&entry.context).map_err(Error::serde_err)?;
             span.follows_from(tracing::Id::from_u64(ctx.span_id));
             span.record("request_id", &ctx.request_id);
         }
-        let cmd = bincode::deserialize(&entry.data).map_err(Error::serde_err)?;
+
+        // Re-applying an already-applied log entry (e.g. if a restart leaves raft replaying past
+        // what this FSM had actually applied) must be a no-op, since the underlying ops (puts,
+        // deletes) aren't themselves idempotent. Keyed on the entry's log index rather than
+        // `ctx.request_id`, since `request_id` is only unique for this process's lifetime; see
+        // `DedupCache`'s doc comment.
+        if self.dedup_cache.check_and_insert(entry.index).await? {
+            return Ok(Duration::ZERO);
+        }
+
+        let cmd = Command::decode(&entry.data).map_err(|e| Error::serde_err(e.to_string()))?;
+        self.apply_command(cmd, entry.index).await
+    }
+
+    /// Applies a single decoded [`Command`], recursing into [`Command::Batch`]'s nested commands
+    /// so a batch proposed under one [`Context`] applies as part of the same [`apply_normal`]
+    /// call as any other command: an error partway through stops the FSM apply pipeline the same
+    /// way a non-batched command's error would, rather than silently skipping the rest.
+    ///
+    /// [`apply_normal`]: Self::apply_normal
+    #[async_recursion]
+    async fn apply_command(&self, cmd: Command, index: u64) -> Result<Duration> {
+        let mut notify_elapsed = Duration::ZERO;
         match cmd {
             Command::TxnRequest {
                 request_id,
                 sequence,
                 request,
             } => {
-                let response = self.txn(request, sequence, entry.index).await;
+                let response = self.txn(request, sequence, index).await;
+                let start = Instant::now();
                 if let Err(e) = self.txn_notify_pool.notify(request_id, response) {
                     error!(request_id = request_id, "notify txn result error: {}", e);
                 }
+                notify_elapsed = start.elapsed();
             }
             Command::CompactRaftLog { index, sequence } => {
                 self.compact_raft_log(index, sequence).await?;
             }
+            Command::Batch(commands) => {
+                for command in commands {
+                    notify_elapsed += self.apply_command(command, index).await?;
+                }
+            }
         }
-        Ok(())
+        Ok(notify_elapsed)
     }
 
     #[tracing::instrument(level = "trace")]
@@ -230,7 +277,7 @@ impl Fsm for ObjectLsmTreeFsm {
         _group: u64,
         is_leader: bool,
         entries: Vec<raft::prelude::Entry>,
-    ) -> Result<()> {
+    ) -> Result<Duration> {
         // Update `available index`.
         let mut available_index = None;
         if let Some(last_entry) = entries.last() {
@@ -241,9 +288,11 @@ impl Fsm for ObjectLsmTreeFsm {
 
         // If current `FSM` does not belong to the raft leader, `FSM` won't actually apply entries.
         if !is_leader {
-            return Ok(());
+            return Ok(Duration::ZERO);
         }
 
+        let mut notify_elapsed = Duration::ZERO;
+
         // Get apply progress.
         let avaiable_index = match available_index {
             Some(index) => index,
@@ -269,13 +318,13 @@ impl Fsm for ObjectLsmTreeFsm {
                 .await?;
             check_log_gap(&entries, first_apply_index..first_carried_index)?;
             for entry in loaded_entries {
-                self.apply_entry(entry).await?;
+                notify_elapsed += self.apply_entry(entry).await?;
             }
         }
 
         // Apply carried entries.
         for entry in entries {
-            self.apply_entry(entry).await?;
+            notify_elapsed += self.apply_entry(entry).await?;
         }
 
         // Update `done index`.
@@ -284,7 +333,7 @@ impl Fsm for ObjectLsmTreeFsm {
             self.store_index(DONE_INDEX_KEY, done_index).await?;
         }
 
-        Ok(())
+        Ok(notify_elapsed)
     }
 
     #[tracing::instrument(level = "trace")]
@@ -320,13 +369,13 @@ pub mod tests {
             _group: u64,
             is_leader: bool,
             entries: Vec<raft::prelude::Entry>,
-        ) -> Result<()> {
+        ) -> Result<Duration> {
             if !self.leader_apply || is_leader {
                 for entry in entries {
                     self.tx.send(entry).unwrap()
                 }
             }
-            Ok(())
+            Ok(Duration::ZERO)
         }
 
         async fn raft_applied_index(&self) -> Result<u64> {
@@ -1,5 +1,6 @@
 use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use bytes::Bytes;
 use parking_lot::RwLock;
@@ -20,16 +21,29 @@ pub struct ObjectStoreLsmTreeOptions {
     pub sstable_store: SstableStoreRef,
     /// Memtable capacity.
     pub write_buffer_capacity: usize,
+    /// Max time a memtable may stay active before it's sealed regardless of size, so a group that
+    /// goes idle after a few writes still flushes instead of holding them in memory forever.
+    pub max_memtable_age: Duration,
+    /// Max number of sealed, not-yet-uploaded memtables allowed to queue up. Once reached,
+    /// `write` stalls (retrying on a short backoff) instead of sealing another one, applying
+    /// backpressure until [`crate::worker::sstable_uploader::SstableUploader`] drains the queue.
+    pub max_immutable_memtables: usize,
     /// Local version manager.
     pub version_manager: VersionManager,
 
     pub metrics: LsmTreeMetricsRef,
 }
 
+/// Backoff between retries while `write` is stalled on a full immutable memtable queue.
+const WRITE_STALL_BACKOFF: Duration = Duration::from_millis(1);
+
 pub struct MemtableWithCtx {
     pub table: Memtable,
     pub max_applied_index: u64,
     pub max_sequence: u64,
+    /// When this memtable became the active one. Used to force a time-based rotation of an
+    /// active memtable that never fills up.
+    created_at: Instant,
 }
 
 impl MemtableWithCtx {
@@ -38,6 +52,7 @@ impl MemtableWithCtx {
             table: Memtable::new(capacity),
             max_applied_index: 0,
             max_sequence: 0,
+            created_at: Instant::now(),
         }
     }
 }
@@ -75,7 +90,7 @@ impl ObjectStoreLsmTreeCore {
 
             memtables: RwLock::new(Memtables {
                 memtable: MemtableWithCtx::new(options.write_buffer_capacity),
-                immutable_memtables: VecDeque::with_capacity(32),
+                immutable_memtables: VecDeque::with_capacity(options.max_immutable_memtables),
             }),
 
             _metrics: options.metrics.clone(),
@@ -196,32 +211,68 @@ impl ObjectStoreLsmTreeCore {
             + 4 * SKIPLIST_NODE_TOWER_MAX_HEIGHT
             + 8;
 
+        loop {
+            let mut guard = self.memtables.write();
+            // Rotate memtable if needed.
+            if guard.memtable.table.mem_remain() < approximate_size {
+                if guard.immutable_memtables.len() >= self.options.max_immutable_memtables {
+                    // Immutable queue is already at capacity: stall instead of growing it further
+                    // unbounded, applying backpressure until the uploader drains a memtable.
+                    drop(guard);
+                    trace!("write stalled: immutable memtable queue full");
+                    tokio::time::sleep(WRITE_STALL_BACKOFF).await;
+                    continue;
+                }
+                debug!("rotate memtable: full");
+                self.rotate_memtable(&mut guard);
+            }
+            guard.memtable.table.put(key, value, sequence);
+            assert!(
+                apply_index > guard.memtable.max_applied_index,
+                "apply index: {}, max applied index: {}",
+                apply_index,
+                guard.memtable.max_applied_index,
+            );
+            guard.memtable.max_applied_index = apply_index;
+            assert!(
+                sequence > guard.memtable.max_sequence,
+                "sequence: {}, max sequence: {}",
+                sequence,
+                guard.memtable.max_sequence,
+            );
+            guard.memtable.max_sequence = sequence;
+            drop(guard);
+
+            return Ok(());
+        }
+    }
+
+    /// Swaps in a fresh active memtable and pushes the sealed one onto the immutable queue.
+    /// Caller must already hold the write lock.
+    fn rotate_memtable(&self, guard: &mut Memtables) {
+        let mut imm = MemtableWithCtx::new(self.options.write_buffer_capacity);
+        std::mem::swap(&mut imm, &mut guard.memtable);
+        guard.immutable_memtables.push_front(imm);
+    }
+
+    /// Seals the active memtable if it's non-empty and has been active for longer than
+    /// `max_memtable_age`, even though it isn't full, as long as the immutable queue is below
+    /// `max_immutable_memtables`. Returns whether a rotation happened.
+    ///
+    /// Meant to be polled periodically (e.g. by [`crate::worker::sstable_uploader::SstableUploader`])
+    /// so a group that receives only a trickle of writes still gets flushed instead of holding
+    /// them in memory indefinitely.
+    fn maybe_rotate_stale_memtable(&self) -> bool {
         let mut guard = self.memtables.write();
-        // Rotate memtable if needed.
-        if guard.memtable.table.mem_remain() < approximate_size {
-            debug!("rotate memtable");
-            let mut imm = MemtableWithCtx::new(self.options.write_buffer_capacity);
-            std::mem::swap(&mut imm, &mut guard.memtable);
-            guard.immutable_memtables.push_front(imm);
+        if guard.memtable.table.is_empty()
+            || guard.memtable.created_at.elapsed() < self.options.max_memtable_age
+            || guard.immutable_memtables.len() >= self.options.max_immutable_memtables
+        {
+            return false;
         }
-        guard.memtable.table.put(key, value, sequence);
-        assert!(
-            apply_index > guard.memtable.max_applied_index,
-            "apply index: {}, max applied index: {}",
-            apply_index,
-            guard.memtable.max_applied_index,
-        );
-        guard.memtable.max_applied_index = apply_index;
-        assert!(
-            sequence > guard.memtable.max_sequence,
-            "sequence: {}, max sequence: {}",
-            sequence,
-            guard.memtable.max_sequence,
-        );
-        guard.memtable.max_sequence = sequence;
-        drop(guard);
-
-        Ok(())
+        debug!("rotate memtable: stale");
+        self.rotate_memtable(&mut guard);
+        true
     }
 
     fn get_oldest_immutable_memtable(&self) -> Option<Memtable> {
@@ -323,4 +374,127 @@ impl ObjectStoreLsmTree {
     pub fn drop_oldest_immutable_memtable(&self) -> MemtableWithCtx {
         self.core.drop_oldest_immutable_memtable()
     }
+
+    /// Seals the active memtable if it's gone stale (see [`ObjectStoreLsmTreeOptions::max_memtable_age`]).
+    /// Returns whether a rotation happened.
+    #[tracing::instrument(level = "trace")]
+    pub fn maybe_rotate_stale_memtable(&self) -> bool {
+        self.core.maybe_rotate_stale_memtable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use runkv_storage::components::{
+        BlockCache, LsmTreeMetrics, SstableStore, SstableStoreOptions,
+    };
+    use runkv_storage::manifest::VersionManagerOptions;
+    use runkv_storage::MemObjectStore;
+    use test_log::test;
+
+    use super::*;
+
+    fn build_lsm_tree_for_test(
+        write_buffer_capacity: usize,
+        max_memtable_age: Duration,
+        max_immutable_memtables: usize,
+    ) -> ObjectStoreLsmTree {
+        let metrics = Arc::new(LsmTreeMetrics::new(0));
+        let object_store = Arc::new(MemObjectStore::default());
+        let block_cache = BlockCache::new(65536, metrics.clone());
+        let sstable_store = Arc::new(SstableStore::new(SstableStoreOptions {
+            path: "path".to_string(),
+            object_store,
+            block_cache,
+            meta_cache_capacity: 1024,
+            enable_content_dedup: false,
+        }));
+        let version_manager = VersionManager::new(VersionManagerOptions {
+            levels_options: vec![runkv_common::config::LevelOptions {
+                compaction_strategy: LevelCompactionStrategy::Overlap,
+                compression_algorithm: runkv_common::coding::CompressionAlgorithm::None,
+            }],
+            levels: vec![vec![]],
+            sstable_store: sstable_store.clone(),
+        });
+        ObjectStoreLsmTree::new(ObjectStoreLsmTreeOptions {
+            raft_node: 1,
+            sstable_store,
+            write_buffer_capacity,
+            max_memtable_age,
+            max_immutable_memtables,
+            version_manager,
+            metrics,
+        })
+    }
+
+    #[test(tokio::test)]
+    async fn test_write_rotates_memtable_when_full() {
+        // Small enough that a single entry already exceeds `mem_remain`.
+        let lsm_tree = build_lsm_tree_for_test(1, Duration::from_secs(3600), 32);
+        assert!(lsm_tree.get_oldest_immutable_memtable().is_none());
+
+        lsm_tree
+            .put(&Bytes::from_static(b"k01"), &Bytes::from_static(b"v01"), 1, 1)
+            .await
+            .unwrap();
+        lsm_tree
+            .put(&Bytes::from_static(b"k02"), &Bytes::from_static(b"v02"), 2, 2)
+            .await
+            .unwrap();
+
+        assert!(lsm_tree.get_oldest_immutable_memtable().is_some());
+    }
+
+    #[test(tokio::test)]
+    async fn test_stale_memtable_rotates_under_size_cap() {
+        let lsm_tree = build_lsm_tree_for_test(1024 * 1024, Duration::from_millis(10), 32);
+        lsm_tree
+            .put(&Bytes::from_static(b"k01"), &Bytes::from_static(b"v01"), 1, 1)
+            .await
+            .unwrap();
+
+        // Far under `write_buffer_capacity`, so only the age check can trigger a rotation.
+        assert!(!lsm_tree.maybe_rotate_stale_memtable());
+        assert!(lsm_tree.get_oldest_immutable_memtable().is_none());
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert!(lsm_tree.maybe_rotate_stale_memtable());
+        assert!(lsm_tree.get_oldest_immutable_memtable().is_some());
+    }
+
+    #[test(tokio::test)]
+    async fn test_write_stalls_until_immutable_queue_has_room() {
+        // Every put overflows `mem_remain`, so each one wants to rotate the active memtable. The
+        // queue can hold only one, so the second put must stall until we drain it.
+        let lsm_tree = build_lsm_tree_for_test(1, Duration::from_secs(3600), 1);
+
+        lsm_tree
+            .put(&Bytes::from_static(b"k01"), &Bytes::from_static(b"v01"), 1, 1)
+            .await
+            .unwrap();
+        assert!(lsm_tree.get_oldest_immutable_memtable().is_some());
+
+        let stalled_lsm_tree = lsm_tree.clone();
+        let stalled_put = tokio::spawn(async move {
+            stalled_lsm_tree
+                .put(&Bytes::from_static(b"k02"), &Bytes::from_static(b"v02"), 2, 2)
+                .await
+        });
+
+        // Queue is full, so the put above must not complete yet.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!stalled_put.is_finished());
+
+        // Draining the queue unblocks the stalled write.
+        lsm_tree.drop_oldest_immutable_memtable();
+        tokio::time::timeout(Duration::from_secs(1), stalled_put)
+            .await
+            .expect("stalled write should complete once queue has room")
+            .unwrap()
+            .unwrap();
+    }
 }
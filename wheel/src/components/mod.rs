@@ -1,6 +1,9 @@
+pub mod clock;
 pub mod command;
+pub mod dedup;
 pub mod fsm;
 pub mod lsm_tree;
 pub mod raft_log_store;
 pub mod raft_manager;
 pub mod raft_network;
+pub mod raft_network_quic;
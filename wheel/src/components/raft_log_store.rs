@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use bytes::{Buf, BufMut};
 use itertools::Itertools;
-use runkv_storage::raft_log_store::entry::RaftLogBatch;
+use runkv_storage::raft_log_store::entry::{RaftLogBatch, RaftLogBatchBuilder};
 use runkv_storage::raft_log_store::RaftLogStore;
 
 use crate::error::{Error, Result};
@@ -52,6 +52,13 @@ impl RaftGroupLogStore {
         Self { group, core }
     }
 
+    /// Returns a [`RaftLogBatchBuilder`] configured with the underlying store's compression
+    /// threshold, so batches built for this group honor `RaftLogStoreOptions::compression_threshold`
+    /// instead of silently falling back to the default.
+    pub fn batch_builder(&self) -> RaftLogBatchBuilder {
+        self.core.batch_builder()
+    }
+
     pub async fn append(&self, batches: Vec<RaftLogBatch>) -> Result<()> {
         self.core.append(batches).await.map_err(Error::StorageError)
     }
@@ -105,6 +112,26 @@ impl RaftGroupLogStore {
         Ok(Some(hs))
     }
 
+    /// Drops raft log entries before `index`. Used by the raft worker's snapshot trigger policy
+    /// to reclaim log space once a snapshot has made those entries unnecessary for recovery.
+    pub async fn compact(&self, index: u64) -> Result<()> {
+        self.core
+            .compact(self.group, index)
+            .await
+            .map_err(Error::StorageError)
+    }
+
+    /// Seeds this group's log to begin just after a bootstrap snapshot's `index`/`term`, so
+    /// [`crate::worker::raft::RaftWorker::build`] can start raft there for a node that's joining
+    /// via [`crate::worker::raft::RaftStartMode::Bootstrap`] instead of replaying history it never
+    /// had. Only legal before this group has any log entries of its own.
+    pub async fn seed_snapshot_boundary(&self, index: u64, term: u64) -> Result<()> {
+        self.core
+            .seed_snapshot_boundary(self.group, index, term)
+            .await
+            .map_err(Error::StorageError)
+    }
+
     pub async fn entries(&self, index: u64, max_len: usize) -> Result<Vec<raft::prelude::Entry>> {
         let raw_entries = self.core.entries(self.group, index, max_len).await?;
         let entries = raw_entries
@@ -274,6 +301,8 @@ mod tests {
             log_file_capacity: 64 << 20,
             block_cache_capacity: 64 << 20,
             persist: Persist::Sync,
+            strict_repair: false,
+            compression_threshold: runkv_storage::raft_log_store::DEFAULT_COMPRESSION_THRESHOLD,
         };
         let raft_log_store = RaftLogStore::open(options).await.unwrap();
 
@@ -13,21 +13,31 @@ use runkv_proto::kv::TxnResponse;
 use runkv_storage::components::{LsmTreeMetricsRef, SstableStoreRef};
 use runkv_storage::manifest::VersionManager;
 use runkv_storage::raft_log_store::RaftLogStore;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, oneshot, RwLock, Semaphore};
 use tokio::task::JoinHandle;
 use tracing::error;
 
+use super::clock::RealClock;
 use super::fsm::{ObjectLsmTreeFsm, ObjectLsmTreeFsmOptions};
 use super::lsm_tree::{ObjectStoreLsmTree, ObjectStoreLsmTreeOptions};
 use super::raft_log_store::RaftGroupLogStore;
 use super::raft_network::{GrpcRaftNetwork, RaftNetwork};
 use crate::error::{Error, RaftManageError, Result};
-use crate::worker::raft::{Proposal, RaftStartMode, RaftWorker, RaftWorkerOptions};
+use crate::worker::gear::{Gear, GearOptions, NoopGearHook};
+use crate::worker::raft::{
+    Proposal, RaftStartMode, RaftStatus, RaftWorker, RaftWorkerControl, RaftWorkerOptions,
+    SnapshotPolicy,
+};
 use crate::worker::sstable_uploader::{SstableUploader, SstableUploaderOptions};
 
+/// Default for [`RaftManagerOptions::max_concurrent_snapshot_builds`].
+pub const DEFAULT_MAX_CONCURRENT_SNAPSHOT_BUILDS: usize = 2;
+
 #[derive(Clone)]
 pub struct LsmTreeOptions {
     pub write_buffer_capacity: usize,
+    pub max_memtable_age: Duration,
+    pub max_immutable_memtables: usize,
     pub sstable_capacity: usize,
     pub block_capacity: usize,
     pub restart_interval: usize,
@@ -47,13 +57,41 @@ pub struct RaftManagerOptions {
     pub sstable_store: SstableStoreRef,
     pub channel_pool: ChannelPool,
     pub lsm_tree_options: LsmTreeOptions,
+    pub snapshot_policy: SnapshotPolicy,
+    pub max_size_per_msg: u64,
+    pub max_inflight_msgs: usize,
+    pub min_loop_duration: Duration,
+    /// Forwarded to `RaftWorkerOptions::check_quorum` for every group this manager creates.
+    pub check_quorum: bool,
+    /// Forwarded to `RaftWorkerOptions::pre_vote` for every group this manager creates.
+    pub pre_vote: bool,
+    /// Forwarded to `RaftWorkerOptions::tick_jitter` for every group this manager creates.
+    pub tick_jitter: Duration,
+    /// Bound of the channel between a raft node's [`RaftWorker`] and its [`Gear`]. See
+    /// [`crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND`].
+    pub apply_channel_bound: usize,
+    /// Forwarded to `RaftWorkerOptions::metrics_enabled` for every group this manager creates.
+    pub metrics_enabled: bool,
+    /// Once this many groups are already running on the node, a newly created group's
+    /// `RaftWorkerOptions::metrics_cardinality_aggregated` is set, trading its per-group metric
+    /// detail for a bounded, node-level series instead. Groups created before the node crossed
+    /// the threshold keep reporting per-group; this only affects groups created from then on.
+    /// `None` disables aggregation, so every group always reports per-group metrics.
+    pub metrics_cardinality_threshold: Option<usize>,
+    /// Max number of [`crate::worker::gear::Gear::build_snapshot`] calls allowed to run at once
+    /// across every group this manager owns. Shared node-wide (not per group), since it's node
+    /// CPU/store contention this protects against, not any single group's throughput.
+    pub max_concurrent_snapshot_builds: usize,
 }
 
 struct RaftManagerInner {
     proposal_txs: BTreeMap<u64, mpsc::UnboundedSender<Proposal>>,
+    control_txs: BTreeMap<u64, mpsc::UnboundedSender<RaftWorkerControl>>,
     raft_worker_handles: BTreeMap<u64, JoinHandle<()>>,
+    gear_handles: BTreeMap<u64, JoinHandle<()>>,
     sstable_uploader_handles: BTreeMap<u64, JoinHandle<()>>,
     sequences: BTreeMap<u64, Arc<AtomicU64>>,
+    lsm_trees: BTreeMap<u64, ObjectStoreLsmTree>,
 }
 
 #[derive(Clone)]
@@ -71,6 +109,17 @@ pub struct RaftManager {
     channel_pool: ChannelPool,
 
     lsm_tree_options: LsmTreeOptions,
+    snapshot_policy: SnapshotPolicy,
+    max_size_per_msg: u64,
+    max_inflight_msgs: usize,
+    min_loop_duration: Duration,
+    check_quorum: bool,
+    pre_vote: bool,
+    tick_jitter: Duration,
+    apply_channel_bound: usize,
+    metrics_enabled: bool,
+    metrics_cardinality_threshold: Option<usize>,
+    snapshot_build_limiter: Arc<Semaphore>,
 
     inner: Arc<RwLock<RaftManagerInner>>,
 }
@@ -89,12 +138,26 @@ impl RaftManager {
             version_manager: options.version_manager,
             sstable_store: options.sstable_store,
             lsm_tree_options: options.lsm_tree_options,
+            snapshot_policy: options.snapshot_policy,
+            max_size_per_msg: options.max_size_per_msg,
+            max_inflight_msgs: options.max_inflight_msgs,
+            min_loop_duration: options.min_loop_duration,
+            check_quorum: options.check_quorum,
+            pre_vote: options.pre_vote,
+            tick_jitter: options.tick_jitter,
+            apply_channel_bound: options.apply_channel_bound,
+            metrics_enabled: options.metrics_enabled,
+            metrics_cardinality_threshold: options.metrics_cardinality_threshold,
+            snapshot_build_limiter: Arc::new(Semaphore::new(options.max_concurrent_snapshot_builds)),
             channel_pool: options.channel_pool,
             inner: Arc::new(RwLock::new(RaftManagerInner {
                 proposal_txs: BTreeMap::default(),
+                control_txs: BTreeMap::default(),
                 raft_worker_handles: BTreeMap::default(),
+                gear_handles: BTreeMap::default(),
                 sstable_uploader_handles: BTreeMap::default(),
                 sequences: BTreeMap::default(),
+                lsm_trees: BTreeMap::default(),
             })),
         }
     }
@@ -109,6 +172,8 @@ impl RaftManager {
             raft_node,
             sstable_store: self.sstable_store.clone(),
             write_buffer_capacity: self.lsm_tree_options.write_buffer_capacity,
+            max_memtable_age: self.lsm_tree_options.max_memtable_age,
+            max_immutable_memtables: self.lsm_tree_options.max_immutable_memtables,
             version_manager: self.version_manager.clone(),
             metrics: self.lsm_tree_options.metrics.clone(),
         };
@@ -129,6 +194,26 @@ impl RaftManager {
         };
         let fsm = ObjectLsmTreeFsm::new(fsm_options);
 
+        // Build and bootstrap gear.
+        let (gear_command_tx, gear_command_rx) = mpsc::channel(self.apply_channel_bound);
+        let gear_options = GearOptions {
+            node: self.node,
+            group,
+            raft_node,
+            raft_log_store: raft_log_store.clone(),
+            fsm,
+            hook: Arc::new(NoopGearHook),
+            command_rx: gear_command_rx,
+            snapshot_build_limiter: self.snapshot_build_limiter.clone(),
+        };
+        let mut gear = Gear::new(gear_options);
+        let gear_handle = tokio::spawn(async move {
+            let result = gear.run().await;
+            if let Err(e) = result {
+                error!(gear = ?gear, "error raised when running gear: {}", e);
+            }
+        });
+
         // Build raft logger.
         let raft_logger = self
             .raft_logger_root
@@ -137,6 +222,13 @@ impl RaftManager {
         // Build raft worker.
         let peers = self.raft_network.raft_nodes(group).await?;
         let (proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (control_tx, control_rx) = mpsc::unbounded_channel();
+        // Includes the group being created here, so a threshold of e.g. 100 means the 101st
+        // group (not the 102nd) is the first one to aggregate.
+        let group_count = self.inner.read().await.raft_worker_handles.len() + 1;
+        let metrics_cardinality_aggregated = self
+            .metrics_cardinality_threshold
+            .map_or(false, |threshold| group_count > threshold);
         let raft_worker_options = RaftWorkerOptions {
             group,
             node: self.node,
@@ -146,10 +238,22 @@ impl RaftManager {
             raft_log_store,
             raft_logger,
             raft_network: self.raft_network.clone(),
+            clock: Arc::new(RealClock),
 
             proposal_rx,
+            control_rx,
 
-            fsm,
+            gear_command_tx,
+
+            snapshot_policy: self.snapshot_policy,
+            max_size_per_msg: self.max_size_per_msg,
+            max_inflight_msgs: self.max_inflight_msgs,
+            min_loop_duration: self.min_loop_duration,
+            check_quorum: self.check_quorum,
+            pre_vote: self.pre_vote,
+            tick_jitter: self.tick_jitter,
+            metrics_enabled: self.metrics_enabled,
+            metrics_cardinality_aggregated,
         };
         let mut raft_worker = RaftWorker::build(raft_worker_options).await?;
 
@@ -194,10 +298,12 @@ impl RaftManager {
 
         let mut guard = self.inner.write().await;
         if guard.proposal_txs.insert(raft_node, proposal_tx).is_some()
+            || guard.control_txs.insert(raft_node, control_tx).is_some()
             || guard
                 .raft_worker_handles
                 .insert(raft_node, raft_worker_handle)
                 .is_some()
+            || guard.gear_handles.insert(raft_node, gear_handle).is_some()
             || guard
                 .sstable_uploader_handles
                 .insert(raft_node, sstable_uploader_handle)
@@ -207,9 +313,10 @@ impl RaftManager {
                 .sequences
                 .insert(raft_node, Arc::new(AtomicU64::new(rtimestamp())))
                 .is_some()
+            || guard.lsm_trees.insert(raft_node, lsm_tree).is_some()
         {
             return Err(Error::Other(format!(
-                "`proposal tx` or `raft worker handle` or `sstable uploader handle` or `sequence` of {} already exists",
+                "`proposal tx` or `control tx` or `raft worker handle` or `gear handle` or `sstable uploader handle` or `sequence` or `lsm tree` of {} already exists",
                 raft_node
             )));
         }
@@ -232,6 +339,65 @@ impl RaftManager {
         })
     }
 
+    /// Gracefully transfers leadership of `raft_node`'s group to `target_raft_node`, e.g. to drain
+    /// a node for planned maintenance without forcing an election. Resolves once the target has
+    /// become leader, or with an error if it isn't a current voter.
+    pub async fn transfer_leader(&self, raft_node: u64, target_raft_node: u64) -> Result<()> {
+        let control_tx = self.control_tx(raft_node).await?;
+        let (notify_tx, notify_rx) = oneshot::channel();
+        control_tx
+            .send(RaftWorkerControl::TransferLeader {
+                target_raft_node,
+                notify: notify_tx,
+            })
+            .map_err(Error::err)?;
+        notify_rx.await.map_err(Error::err)?
+    }
+
+    /// Forces `raft_node` to (pre-)campaign for leadership, e.g. after an operator has isolated a
+    /// bad leader and wants a specific follower to take over.
+    pub async fn campaign(&self, raft_node: u64) -> Result<()> {
+        let control_tx = self.control_tx(raft_node).await?;
+        let (notify_tx, notify_rx) = oneshot::channel();
+        control_tx
+            .send(RaftWorkerControl::Campaign { notify: notify_tx })
+            .map_err(Error::err)?;
+        notify_rx.await.map_err(Error::err)?
+    }
+
+    /// Forces `raft_node` to step down to follower, e.g. to stop it from winning future elections
+    /// while it's being drained.
+    pub async fn step_down(&self, raft_node: u64) -> Result<()> {
+        let control_tx = self.control_tx(raft_node).await?;
+        let (notify_tx, notify_rx) = oneshot::channel();
+        control_tx
+            .send(RaftWorkerControl::StepDown { notify: notify_tx })
+            .map_err(Error::err)?;
+        notify_rx.await.map_err(Error::err)?
+    }
+
+    /// Reports `raft_node`'s current [`RaftStatus`], e.g. for a `runkvctl raft status` command to
+    /// display replication lag.
+    pub async fn status(&self, raft_node: u64) -> Result<RaftStatus> {
+        let control_tx = self.control_tx(raft_node).await?;
+        let (notify_tx, notify_rx) = oneshot::channel();
+        control_tx
+            .send(RaftWorkerControl::Status { notify: notify_tx })
+            .map_err(Error::err)?;
+        notify_rx.await.map_err(Error::err)?
+    }
+
+    async fn control_tx(&self, raft_node: u64) -> Result<mpsc::UnboundedSender<RaftWorkerControl>> {
+        let inner = self.inner.read().await;
+        inner.control_txs.get(&raft_node).cloned().ok_or_else(|| {
+            RaftManageError::RaftNodeNotExists {
+                raft_node,
+                node: self.node,
+            }
+            .into()
+        })
+    }
+
     // TODO: REMOVE ME.
     pub async fn get_sequence(&self, raft_node: u64) -> Result<Arc<AtomicU64>> {
         let inner = self.inner.read().await;
@@ -243,4 +409,77 @@ impl RaftManager {
             .into()
         })
     }
+
+    /// Returns `raft_node`'s locally applied LSM tree, e.g. to serve a read-only node's gets
+    /// without proposing through raft (see [`crate::config::WheelConfig::read_only`]).
+    pub async fn get_lsm_tree(&self, raft_node: u64) -> Result<ObjectStoreLsmTree> {
+        let inner = self.inner.read().await;
+        inner.lsm_trees.get(&raft_node).cloned().ok_or_else(|| {
+            RaftManageError::RaftNodeNotExists {
+                raft_node,
+                node: self.node,
+            }
+            .into()
+        })
+    }
+
+    /// Drains every raft group this node currently hosts, for planned node maintenance: for each
+    /// group where this node is leader, transfers leadership away to another voter first (see
+    /// [`Self::transfer_leader`]), so the node can be taken out of service without forcing an
+    /// election on whoever's left behind. A group with no other voter to hand off to (e.g. this
+    /// node is its only voter) is skipped rather than treated as an error; an error from querying
+    /// or transferring any group's leadership stops the drain and is returned. Otherwise returns
+    /// one [`DrainedGroup`] per group in `raft_node` order.
+    ///
+    /// Does not remove this node from any group's voter set: that requires proposing a
+    /// conf-change entry through raft consensus, which this codebase doesn't implement yet
+    /// (`EntryConfChangeV2`/`EntryConfChange` entries are decoded off the wire but never proposed
+    /// -- see `RaftWorker::append_log_entries`). [`DrainedGroup::still_voter`] is therefore always
+    /// `true` today; callers still need to reassign or shrink group membership through whatever
+    /// out-of-band process this cluster currently uses for that before decommissioning the node.
+    pub async fn drain(&self) -> Result<Vec<DrainedGroup>> {
+        let raft_nodes: Vec<u64> = self.inner.read().await.control_txs.keys().copied().collect();
+        let mut drained = Vec::with_capacity(raft_nodes.len());
+        for raft_node in raft_nodes {
+            let leadership_transferred = self.drain_leadership(raft_node).await?;
+            drained.push(DrainedGroup {
+                raft_node,
+                leadership_transferred,
+                still_voter: true,
+            });
+        }
+        Ok(drained)
+    }
+
+    /// Transfers `raft_node`'s leadership away if this node currently holds it, handing off to
+    /// some other voter in its conf state. Returns whether a transfer was attempted; doing
+    /// nothing because this node isn't leader, or because it's the group's only voter, is not an
+    /// error.
+    async fn drain_leadership(&self, raft_node: u64) -> Result<bool> {
+        if self.status(raft_node).await?.leader_id != raft_node {
+            return Ok(false);
+        }
+        let group_log_store = RaftGroupLogStore::new(raft_node, self.raft_log_store.clone());
+        let voters = group_log_store
+            .get_conf_state()
+            .await?
+            .map_or_else(Vec::new, |cs| cs.voters);
+        let Some(&target_raft_node) = voters.iter().find(|&&voter| voter != raft_node) else {
+            return Ok(false);
+        };
+        self.transfer_leader(raft_node, target_raft_node).await?;
+        Ok(true)
+    }
+}
+
+/// Outcome of attempting to drain one group hosted on this node, as part of [`RaftManager::drain`].
+#[derive(Clone, Copy, Debug)]
+pub struct DrainedGroup {
+    pub raft_node: u64,
+    /// `true` if this node was leader and leadership was handed off to another voter. `false` if
+    /// it wasn't leader, or no other voter was available to hand off to.
+    pub leadership_transferred: bool,
+    /// Whether this node is still a voter in the group's conf state after the drain attempt.
+    /// Always `true` today -- see [`RaftManager::drain`]'s doc comment for why.
+    pub still_voter: bool,
 }
@@ -0,0 +1,326 @@
+use std::collections::btree_map::{BTreeMap, Entry};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use itertools::Itertools;
+use quinn::{ClientConfig, Endpoint, NewConnection, ServerConfig};
+use runkv_common::config::Node;
+use tokio::sync::{mpsc, RwLock};
+
+use super::raft_network::{decode_raft_request_data, encode_raft_request_data, RaftClient, RaftNetwork};
+use crate::error::{Error, RaftManageError, Result};
+
+/// Messages larger than this are rejected rather than buffered unbounded while a stream is read.
+const MAX_MESSAGE_SIZE: usize = 64 << 20;
+
+type MessageChannelPair = (
+    mpsc::UnboundedSender<raft::prelude::Message>,
+    Option<mpsc::UnboundedReceiver<raft::prelude::Message>>,
+);
+
+/// QUIC-backed [`RaftClient`]. Every `send` opens a fresh unidirectional stream on the underlying
+/// connection, so a large `MsgAppend` on one stream can't head-of-line-block the many small
+/// messages a multi-raft wheel sends to the same peer concurrently, unlike the single HTTP/2
+/// connection [`super::raft_network::GrpcRaftNetwork`] multiplexes everything over.
+#[derive(Clone)]
+pub struct QuicRaftClient {
+    connection: quinn::Connection,
+}
+
+impl QuicRaftClient {
+    fn new(connection: quinn::Connection) -> Self {
+        Self { connection }
+    }
+}
+
+#[async_trait]
+impl RaftClient for QuicRaftClient {
+    async fn send(&mut self, msgs: Vec<raft::prelude::Message>) -> Result<()> {
+        let data = encode_raft_request_data(&msgs)?;
+        let mut send_stream = self.connection.open_uni().await.map_err(Error::err)?;
+        send_stream.write_all(&data).await.map_err(Error::err)?;
+        send_stream.finish().await.map_err(Error::err)?;
+        Ok(())
+    }
+}
+
+struct QuicRaftNetworkCore {
+    /// `{ raft node -> node }`
+    raft_nodes: BTreeMap<u64, u64>,
+    /// `{ raft node -> channels }`
+    message_channels: BTreeMap<u64, MessageChannelPair>,
+    /// `{ group -> [ raft node, .. ] }`
+    groups: BTreeMap<u64, Vec<u64>>,
+    /// `{ node -> addr }`
+    addrs: BTreeMap<u64, SocketAddr>,
+    /// `{ node -> connection }`, cached so repeated `client` calls for the same peer reuse the
+    /// handshake instead of paying for a new one every time.
+    connections: BTreeMap<u64, quinn::Connection>,
+}
+
+/// QUIC-based [`RaftNetwork`]. Binds its own QUIC endpoint and drives an accept loop in the
+/// background, independent of the tonic/gRPC server the wheel otherwise runs, so it can be
+/// dropped in as an alternative transport without touching the rest of the RPC surface.
+#[derive(Clone)]
+pub struct QuicRaftNetwork {
+    node: u64,
+    core: Arc<RwLock<QuicRaftNetworkCore>>,
+    endpoint: Endpoint,
+}
+
+impl QuicRaftNetwork {
+    /// Binds `addr`, registers `server_config`/`client_config` for inbound and outbound
+    /// connections respectively, and spawns the background task that accepts connections and
+    /// feeds decoded messages into `recv`. `nodes` seeds the `node -> addr` routing table used by
+    /// `client`; more nodes can be learned later via [`QuicRaftNetwork::put_node`].
+    pub async fn new(
+        node: u64,
+        addr: SocketAddr,
+        server_config: ServerConfig,
+        client_config: ClientConfig,
+        nodes: Vec<Node>,
+    ) -> Result<Self> {
+        let mut builder = Endpoint::builder();
+        builder.listen(server_config);
+        builder.default_client_config(client_config);
+        let (endpoint, mut incoming) = builder.bind(&addr).map_err(Error::err)?;
+
+        let addrs = nodes
+            .into_iter()
+            .map(|n| resolve(&n).map(|addr| (n.id, addr)))
+            .try_collect()?;
+
+        let network = Self {
+            node,
+            core: Arc::new(RwLock::new(QuicRaftNetworkCore {
+                raft_nodes: BTreeMap::default(),
+                message_channels: BTreeMap::default(),
+                groups: BTreeMap::default(),
+                addrs,
+                connections: BTreeMap::default(),
+            })),
+            endpoint,
+        };
+
+        let serving = network.clone();
+        tokio::spawn(async move {
+            while let Some(connecting) = incoming.next().await {
+                let serving = serving.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serving.serve_connection(connecting).await {
+                        tracing::warn!("quic raft network connection ended: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(network)
+    }
+
+    /// Learns or updates the address of `node`, e.g. after membership change.
+    pub async fn put_node(&self, node: Node) -> Result<()> {
+        let addr = resolve(&node)?;
+        self.core.write().await.addrs.insert(node.id, addr);
+        Ok(())
+    }
+
+    async fn serve_connection(&self, connecting: quinn::Connecting) -> Result<()> {
+        let NewConnection { mut uni_streams, .. } = connecting.await.map_err(Error::err)?;
+        while let Some(stream) = uni_streams.next().await {
+            let recv_stream = match stream {
+                Ok(recv_stream) => recv_stream,
+                Err(quinn::ConnectionError::ApplicationClosed(_)) => return Ok(()),
+                Err(e) => return Err(Error::err(e)),
+            };
+            let data = recv_stream
+                .read_to_end(MAX_MESSAGE_SIZE)
+                .await
+                .map_err(Error::err)?;
+            let msgs = decode_raft_request_data(&data)?;
+            self.recv(msgs).await?;
+        }
+        Ok(())
+    }
+
+    async fn connect(&self, node: u64) -> Result<quinn::Connection> {
+        let mut guard = self.core.write().await;
+        if let Some(connection) = guard.connections.get(&node) {
+            if connection.close_reason().is_none() {
+                return Ok(connection.clone());
+            }
+        }
+        let addr = *guard
+            .addrs
+            .get(&node)
+            .ok_or_else(|| Error::err(anyhow::anyhow!("address of node {} not found", node)))?;
+        let NewConnection { connection, .. } = self
+            .endpoint
+            .connect(addr, "runkv")
+            .map_err(Error::err)?
+            .await
+            .map_err(Error::err)?;
+        guard.connections.insert(node, connection.clone());
+        Ok(connection)
+    }
+}
+
+fn resolve(node: &Node) -> Result<SocketAddr> {
+    (node.host.as_str(), node.port)
+        .to_socket_addrs()
+        .map_err(Error::err)?
+        .next()
+        .ok_or_else(|| Error::err(anyhow::anyhow!("unable to resolve node {} address", node.id)))
+}
+
+#[async_trait]
+impl RaftNetwork for QuicRaftNetwork {
+    type RaftClient = QuicRaftClient;
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn register(&self, group: u64, raft_nodes: BTreeMap<u64, u64>) -> Result<()> {
+        let mut guard = self.core.write().await;
+        match guard.groups.entry(group) {
+            Entry::Occupied(_) => return Err(RaftManageError::RaftGroupAlreadyExists(group).into()),
+            Entry::Vacant(v) => {
+                v.insert(raft_nodes.keys().copied().collect_vec());
+            }
+        }
+        for (raft_node, node) in raft_nodes {
+            if guard.raft_nodes.get(&raft_node).is_some() {
+                guard.groups.remove(&group);
+                return Err(RaftManageError::RaftNodeAlreadyExists {
+                    group,
+                    raft_node,
+                    node,
+                }
+                .into());
+            }
+            guard.raft_nodes.insert(raft_node, node);
+            let (tx, rx) = mpsc::unbounded_channel();
+            guard.message_channels.insert(raft_node, (tx, Some(rx)));
+        }
+        Ok(())
+    }
+
+    async fn client(&self, raft_node: u64) -> Result<QuicRaftClient> {
+        let node = {
+            let guard = self.core.read().await;
+            *guard
+                .raft_nodes
+                .get(&raft_node)
+                .ok_or(RaftManageError::RaftNodeNotExists {
+                    raft_node,
+                    node: self.node,
+                })?
+        };
+        let connection = self.connect(node).await?;
+        Ok(QuicRaftClient::new(connection))
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn recv(&self, msgs: Vec<raft::prelude::Message>) -> Result<()> {
+        let guard = self.core.read().await;
+        for msg in msgs {
+            let tx = &guard
+                .message_channels
+                .get(&msg.to)
+                .ok_or(RaftManageError::RaftNodeNotExists {
+                    raft_node: msg.to,
+                    node: self.node,
+                })?
+                .0;
+            tx.send(msg).map_err(Error::err)?;
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn take_message_rx(
+        &self,
+        raft_node: u64,
+    ) -> Result<mpsc::UnboundedReceiver<raft::prelude::Message>> {
+        let mut guard = self.core.write().await;
+        let channel = guard.message_channels.get_mut(&raft_node).ok_or(
+            RaftManageError::RaftNodeNotExists {
+                raft_node,
+                node: self.node,
+            },
+        )?;
+        let rx = channel.1.take().ok_or_else(|| {
+            RaftManageError::Other(format!(
+                "message rx of raft node {} has already been taken",
+                raft_node
+            ))
+        })?;
+        Ok(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use runkv_common::config::Node;
+    use test_log::test;
+
+    use super::*;
+
+    /// Self-signed cert + matching client/server configs for loopback-only test traffic.
+    fn test_quic_configs() -> (ServerConfig, ClientConfig) {
+        let cert = rcgen::generate_simple_self_signed(vec!["runkv".into()]).unwrap();
+        let cert_der = cert.serialize_der().unwrap();
+        let priv_key = cert.serialize_private_key_der();
+        let priv_key = rustls::PrivateKey(priv_key);
+        let cert_chain = vec![rustls::Certificate(cert_der.clone())];
+
+        let server_config = ServerConfig::with_single_cert(cert_chain, priv_key).unwrap();
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add(&rustls::Certificate(cert_der)).unwrap();
+        let client_config = ClientConfig::with_root_certificates(roots);
+
+        (server_config, client_config)
+    }
+
+    async fn build_network(node: u64, nodes: Vec<Node>) -> QuicRaftNetwork {
+        let (server_config, client_config) = test_quic_configs();
+        let addr: SocketAddr = (Ipv4Addr::LOCALHOST, 0).into();
+        QuicRaftNetwork::new(node, addr, server_config, client_config, nodes)
+            .await
+            .unwrap()
+    }
+
+    #[test(tokio::test)]
+    async fn test_send_recv_round_trip_over_quic() {
+        let network_1 = build_network(1, vec![]).await;
+        let addr_1 = network_1.endpoint.local_addr().unwrap();
+        let network_2 = build_network(
+            2,
+            vec![Node {
+                id: 1,
+                host: addr_1.ip().to_string(),
+                port: addr_1.port(),
+            }],
+        )
+        .await;
+
+        network_1.register(100, BTreeMap::from_iter([(10, 1)])).await.unwrap();
+        network_2.register(100, BTreeMap::from_iter([(10, 1)])).await.unwrap();
+
+        let mut rx = network_1.take_message_rx(10).await.unwrap();
+
+        let mut client = network_2.client(10).await.unwrap();
+        let msg = raft::prelude::Message {
+            to: 10,
+            ..Default::default()
+        };
+        client.send(vec![msg]).await.unwrap();
+
+        let received = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(received.to, 10);
+    }
+}
@@ -1,5 +1,6 @@
+use bytesize::ByteSize;
 use runkv_common::config::{
-    CacheConfig, LsmTreeConfig, MinioConfig, Node, PrometheusConfig, S3Config,
+    CacheConfig, FsConfig, LsmTreeConfig, MinioConfig, Node, PrometheusConfig, S3Config,
 };
 use serde::Deserialize;
 
@@ -16,16 +17,31 @@ pub struct WheelConfig {
     pub rudder: Node,
     pub s3: Option<S3Config>,
     pub minio: Option<MinioConfig>,
+    pub fs: Option<FsConfig>,
     pub buffer: BufferConfig,
     pub cache: CacheConfig,
     pub lsm_tree: LsmTreeConfig,
     pub raft_log_store: RaftLogStoreConfig,
+    #[serde(default)]
+    pub raft: RaftConfig,
     pub prometheus: PrometheusConfig,
+    /// When set, this node never proposes: writes are rejected with [`crate::error::Error::ReadOnly`]
+    /// and reads are served straight from its locally applied LSM tree state instead of round-tripping
+    /// through raft. Pair with learner membership so the node still receives replicated log entries to
+    /// apply. Defaults to `false`.
+    #[serde(default)]
+    pub read_only: bool,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct BufferConfig {
     pub write_buffer_capacity: String,
+    /// Max time a memtable may stay active before it's sealed regardless of size. Keeps a
+    /// low-traffic group's writes from sitting unflushed in memory indefinitely.
+    pub max_memtable_age: String,
+    /// Max number of sealed memtables allowed to queue up waiting for upload before writes stall
+    /// to apply backpressure.
+    pub max_immutable_memtables: usize,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -34,4 +50,147 @@ pub struct RaftLogStoreConfig {
     pub log_file_capacity: String,
     pub block_cache_capacity: String,
     pub persist: String,
+    /// Forwarded to `RaftLogStoreOptions::compression_threshold`: raft log batches smaller than
+    /// this are persisted uncompressed. Defaults to
+    /// `runkv_storage::raft_log_store::DEFAULT_COMPRESSION_THRESHOLD`.
+    #[serde(default = "default_compression_threshold")]
+    pub compression_threshold: String,
+    /// Forwarded to `RaftLogStoreOptions::strict_repair`: whether opening the log returns an error
+    /// on a torn trailing record instead of truncating it back to the last good one. A record that
+    /// fails to decode for any other reason is an error either way. Off by default, matching
+    /// crash-recovery's usual case; turn this on where a torn record should be a loud failure
+    /// instead of a silent truncation.
+    #[serde(default)]
+    pub strict_repair: bool,
+    /// Interval between [`runkv_storage::raft_log_store::RaftLogStoreGcWorker`] runs, which
+    /// reclaim frozen log segments that compaction has made obsolete. Defaults to
+    /// `runkv_storage::raft_log_store::DEFAULT_GC_INTERVAL`.
+    #[serde(default = "default_gc_interval")]
+    pub gc_interval: String,
+}
+
+fn default_compression_threshold() -> String {
+    ByteSize(runkv_storage::raft_log_store::DEFAULT_COMPRESSION_THRESHOLD as u64).to_string()
+}
+
+fn default_gc_interval() -> String {
+    humantime::format_duration(runkv_storage::raft_log_store::DEFAULT_GC_INTERVAL).to_string()
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RaftConfig {
+    /// Number of raft log entries a group may accumulate past its last triggered compaction
+    /// before [`crate::worker::raft::SnapshotPolicy`] trims the log again. `0` (the default)
+    /// disables automatic triggering.
+    #[serde(default)]
+    pub snapshot_log_gap_threshold: u64,
+    /// Forwarded to `RaftWorkerOptions::max_size_per_msg`. Lower this on memory-constrained
+    /// nodes, raise it on high-latency links.
+    #[serde(default = "default_max_size_per_msg")]
+    pub max_size_per_msg: u64,
+    /// Forwarded to `RaftWorkerOptions::max_inflight_msgs`.
+    #[serde(default = "default_max_inflight_msgs")]
+    pub max_inflight_msgs: usize,
+    /// Forwarded to `RaftWorkerOptions::min_loop_duration`, in milliseconds: a floor on how often
+    /// the ready loop spins when it has actual work to do. Has no effect while a group is idle,
+    /// since idle iterations block on a channel recv or a heartbeat timer instead of looping.
+    #[serde(default = "default_min_loop_duration_millis")]
+    pub min_loop_duration_millis: u64,
+    /// Forwarded to `RaftWorkerOptions::check_quorum`: whether a group steps down as leader when
+    /// it can't reach a quorum of followers within an election timeout. Defaults to on; test and
+    /// single-node deployments that want a leader to keep serving through a quorum loss can turn
+    /// it off.
+    #[serde(default = "default_check_quorum")]
+    pub check_quorum: bool,
+    /// Forwarded to `RaftWorkerOptions::pre_vote`: whether a node pre-campaigns before bumping its
+    /// term for real, so a partitioned-then-rejoined node doesn't force an unnecessary
+    /// re-election. Defaults to on. Enabling this without [`Self::check_quorum`] has known
+    /// leader-stickiness caveats upstream; `RaftWorker::build` warns rather than rejects that
+    /// combination.
+    #[serde(default = "default_pre_vote")]
+    pub pre_vote: bool,
+    /// Forwarded to `RaftWorkerOptions::tick_jitter`, in milliseconds: the upper bound on random
+    /// jitter added to each group's heartbeat tick, so groups on the same node don't stay
+    /// lockstepped and spike CPU/network on every tick in unison. `0` disables jitter.
+    #[serde(default = "default_tick_jitter_millis")]
+    pub tick_jitter_millis: u64,
+    /// Forwarded to `RaftManagerOptions::apply_channel_bound`: the bound of the channel between a
+    /// raft node's worker and its [`crate::worker::gear::Gear`]. Lower this to fail fast (via
+    /// backpressure) when apply falls behind; raise it to absorb larger apply latency spikes
+    /// before the raft ready loop is throttled.
+    #[serde(default = "default_apply_channel_bound")]
+    pub apply_channel_bound: usize,
+    /// Forwarded to `RaftWorkerOptions::metrics_enabled`: whether [`crate::worker::raft::RaftWorker`]
+    /// observes its per-op histograms/gauges on the ready, send, append and commit paths; its
+    /// poll-channel and apply-enqueue latencies are observed either way. Defaults to on; turn off
+    /// on a node running many tiny groups whose scrape cost isn't worth paying for metrics nobody
+    /// looks at.
+    #[serde(default = "default_metrics_enabled")]
+    pub metrics_enabled: bool,
+    /// Forwarded to `RaftManagerOptions::max_concurrent_snapshot_builds`: the max number of
+    /// [`crate::worker::gear::Gear::build_snapshot`] calls allowed to run at once across every
+    /// group on this node. Bounds how much a mass lag event (many followers snapshotting at
+    /// once) can steal from foreground reads/writes; extra builds queue rather than run.
+    #[serde(default = "default_max_concurrent_snapshot_builds")]
+    pub max_concurrent_snapshot_builds: usize,
+    /// Forwarded to `RaftManagerOptions::metrics_cardinality_threshold`: above this many groups
+    /// on the node, newly created groups aggregate their metrics at the node level instead of
+    /// reporting per-group, bounding prometheus cardinality on a node hosting many groups.
+    /// Defaults to unset, so every group always reports per-group metrics regardless of count.
+    #[serde(default)]
+    pub metrics_cardinality_threshold: Option<usize>,
+}
+
+impl Default for RaftConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_log_gap_threshold: 0,
+            max_size_per_msg: default_max_size_per_msg(),
+            max_inflight_msgs: default_max_inflight_msgs(),
+            min_loop_duration_millis: default_min_loop_duration_millis(),
+            check_quorum: default_check_quorum(),
+            pre_vote: default_pre_vote(),
+            tick_jitter_millis: default_tick_jitter_millis(),
+            apply_channel_bound: default_apply_channel_bound(),
+            metrics_enabled: default_metrics_enabled(),
+            max_concurrent_snapshot_builds: default_max_concurrent_snapshot_builds(),
+            metrics_cardinality_threshold: None,
+        }
+    }
+}
+
+fn default_max_size_per_msg() -> u64 {
+    crate::worker::raft::DEFAULT_MAX_SIZE_PER_MSG
+}
+
+fn default_max_inflight_msgs() -> usize {
+    crate::worker::raft::DEFAULT_MAX_INFLIGHT_MSGS
+}
+
+fn default_min_loop_duration_millis() -> u64 {
+    crate::worker::raft::DEFAULT_MIN_LOOP_DURATION.as_millis() as u64
+}
+
+fn default_check_quorum() -> bool {
+    crate::worker::raft::DEFAULT_CHECK_QUORUM
+}
+
+fn default_pre_vote() -> bool {
+    crate::worker::raft::DEFAULT_PRE_VOTE
+}
+
+fn default_tick_jitter_millis() -> u64 {
+    crate::worker::raft::DEFAULT_TICK_JITTER.as_millis() as u64
+}
+
+fn default_apply_channel_bound() -> usize {
+    crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND
+}
+
+fn default_metrics_enabled() -> bool {
+    true
+}
+
+fn default_max_concurrent_snapshot_builds() -> usize {
+    crate::components::raft_manager::DEFAULT_MAX_CONCURRENT_SNAPSHOT_BUILDS
 }
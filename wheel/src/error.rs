@@ -15,12 +15,24 @@ pub enum Error {
     SerdeError(String),
     #[error("raft error: {0}")]
     RaftError(#[from] raft::Error),
+    /// A proposal was dropped before committing, e.g. the leader stepped down or a new one was
+    /// elected while it was pending. Unlike other [`raft::Error`]s, this one is safe to retry: the
+    /// proposal never committed under the old leadership, so resubmitting it can't double-apply.
+    /// [`crate::worker::raft::RaftWorker`] already acts on that by not tearing down its run loop
+    /// over a dropped proposal the way it does for other errors; no caller above the worker
+    /// currently resubmits on it, though.
+    #[error("raft proposal dropped before committing, safe to retry")]
+    ProposalDropped,
     #[error("raft manage error: {0}")]
     RaftManagerError(#[from] RaftManageError),
     #[error("meta error: {0}")]
     MetaError(#[from] MetaError),
     #[error("kv error: {0}")]
     KvError(#[from] KvError),
+    #[error("gear error: {0}")]
+    GearError(#[from] GearError),
+    #[error("wheel node is read-only")]
+    ReadOnly,
     #[error("other: {0}")]
     Other(String),
 }
@@ -78,3 +90,18 @@ pub enum KvError {
     #[error("no valid leader in raft group {0}")]
     NoValidLeader(u64),
 }
+
+/// Errors from talking to the [`crate::worker::gear::Gear`] on the other end of a
+/// `gear_command_tx`, distinguished from [`Error::Other`] so a caller can tell "the consumer is
+/// gone, stop retrying" apart from a transient failure worth retrying.
+#[derive(thiserror::Error, Debug)]
+pub enum GearError {
+    /// `gear_command_tx.send(GearCommand::Apply { .. })` (or `Shutdown`) failed because the
+    /// receiving `Gear` has already exited and dropped `command_rx`.
+    #[error("apply consumer gone")]
+    ApplyConsumerGone,
+    /// `gear_command_tx.send(GearCommand::BuildSnapshot { .. })` (or `InstallSnapshot`) failed, or
+    /// its reply oneshot was dropped without a reply, because the receiving `Gear` exited first.
+    #[error("snapshot worker gone")]
+    SnapshotWorkerGone,
+}
@@ -8,6 +8,7 @@ pub mod service;
 pub mod worker;
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytesize::ByteSize;
 use components::raft_manager::{RaftManager, RaftManagerOptions};
@@ -30,8 +31,10 @@ use runkv_storage::components::{
 };
 use runkv_storage::manifest::{VersionManager, VersionManagerOptions};
 use runkv_storage::raft_log_store::store::RaftLogStoreOptions;
-use runkv_storage::raft_log_store::RaftLogStore;
-use runkv_storage::{MemObjectStore, ObjectStoreRef, S3ObjectStore};
+use runkv_storage::raft_log_store::{
+    RaftLogStore, RaftLogStoreGcWorker, RaftLogStoreGcWorkerOptions,
+};
+use runkv_storage::{FsObjectStore, MemObjectStore, ObjectStoreRef, S3ObjectStore};
 use service::{Wheel, WheelOptions};
 use tonic::transport::Server;
 use tracing::info;
@@ -100,6 +103,8 @@ pub async fn build_wheel_with_object_store(
     let txn_notify_pool = build_txn_notify_pool();
 
     let raft_log_store = build_raft_log_store(config).await?;
+    let raft_log_store_gc_worker =
+        build_raft_log_store_gc_worker(config, raft_log_store.clone())?;
     let raft_network = build_raft_network(config, channel_pool.clone());
     let raft_manager = build_raft_manager(
         config,
@@ -119,11 +124,15 @@ pub async fn build_wheel_with_object_store(
         raft_network,
         raft_manager,
         txn_notify_pool,
+        read_only: config.read_only,
     };
 
     let wheel = Wheel::new(options);
 
-    Ok((wheel, vec![Box::new(heartbeater)]))
+    Ok((
+        wheel,
+        vec![Box::new(heartbeater), Box::new(raft_log_store_gc_worker)],
+    ))
 }
 
 async fn build_object_store(config: &WheelConfig) -> ObjectStoreRef {
@@ -133,6 +142,9 @@ async fn build_object_store(config: &WheelConfig) -> ObjectStoreRef {
     } else if let Some(c) = &config.minio {
         info!("minio config found, create minio object store");
         Arc::new(S3ObjectStore::new_with_minio(&c.url).await)
+    } else if let Some(c) = &config.fs {
+        info!("fs config found, create fs object store");
+        Arc::new(FsObjectStore::new(c.root.clone()))
     } else {
         info!("no object store config found, create default memory object store");
         Arc::new(MemObjectStore::default())
@@ -163,6 +175,7 @@ fn build_sstable_store(
             .parse::<ByteSize>()
             .map_err(Error::config_err)?
             .0 as usize,
+        enable_content_dedup: false,
     };
     let sstable_store = SstableStore::new(sstable_store_options);
     Ok(Arc::new(sstable_store))
@@ -236,12 +249,35 @@ async fn build_raft_log_store(config: &WheelConfig) -> Result<RaftLogStore> {
             .persist
             .parse()
             .map_err(Error::config_err)?,
+        strict_repair: config.raft_log_store.strict_repair,
+        compression_threshold: config
+            .raft_log_store
+            .compression_threshold
+            .parse::<ByteSize>()
+            .map_err(Error::config_err)?
+            .0 as usize,
     };
     RaftLogStore::open(raft_log_store_options)
         .await
         .map_err(Error::StorageError)
 }
 
+fn build_raft_log_store_gc_worker(
+    config: &WheelConfig,
+    raft_log_store: RaftLogStore,
+) -> Result<RaftLogStoreGcWorker> {
+    let options = RaftLogStoreGcWorkerOptions {
+        store: raft_log_store,
+        gc_interval: config
+            .raft_log_store
+            .gc_interval
+            .parse::<humantime::Duration>()
+            .map_err(Error::config_err)?
+            .into(),
+    };
+    Ok(RaftLogStoreGcWorker::new(options))
+}
+
 fn build_raft_network(config: &WheelConfig, channel_pool: ChannelPool) -> GrpcRaftNetwork {
     GrpcRaftNetwork::new(config.id, channel_pool)
 }
@@ -273,6 +309,13 @@ fn build_raft_manager(
                 .parse::<ByteSize>()
                 .map_err(Error::config_err)?
                 .0 as usize,
+            max_memtable_age: config
+                .buffer
+                .max_memtable_age
+                .parse::<humantime::Duration>()
+                .map_err(Error::config_err)?
+                .into(),
+            max_immutable_memtables: config.buffer.max_immutable_memtables,
             sstable_capacity: config
                 .lsm_tree
                 .sstable_capacity
@@ -300,6 +343,19 @@ fn build_raft_manager(
                 .into(),
             metrics: lsm_tree_metrics,
         },
+        snapshot_policy: crate::worker::raft::SnapshotPolicy {
+            log_gap_threshold: config.raft.snapshot_log_gap_threshold,
+        },
+        max_size_per_msg: config.raft.max_size_per_msg,
+        max_inflight_msgs: config.raft.max_inflight_msgs,
+        min_loop_duration: Duration::from_millis(config.raft.min_loop_duration_millis),
+        check_quorum: config.raft.check_quorum,
+        pre_vote: config.raft.pre_vote,
+        tick_jitter: Duration::from_millis(config.raft.tick_jitter_millis),
+        apply_channel_bound: config.raft.apply_channel_bound,
+        metrics_enabled: config.raft.metrics_enabled,
+        max_concurrent_snapshot_builds: config.raft.max_concurrent_snapshot_builds,
+        metrics_cardinality_threshold: config.raft.metrics_cardinality_threshold,
     };
     Ok(RaftManager::new(raft_manager_options))
 }
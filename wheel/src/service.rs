@@ -4,20 +4,21 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use async_trait::async_trait;
+use bytes::Bytes;
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use runkv_common::channel_pool::ChannelPool;
 use runkv_common::coding::BytesSerde;
 use runkv_common::config::Node;
-use runkv_common::context::Context;
+use runkv_common::context::{now_millis, Context};
 use runkv_common::notify_pool::NotifyPool;
 use runkv_common::sync::TicketLock;
 use runkv_proto::common::Endpoint;
 use runkv_proto::kv::kv_service_server::KvService;
 use runkv_proto::kv::{
     kv_op_request, kv_op_response, DeleteRequest, DeleteResponse, GetRequest, GetResponse,
-    KvOpRequest, PutRequest, PutResponse, SnapshotRequest, SnapshotResponse, TxnRequest,
-    TxnResponse,
+    KvOpRequest, KvOpResponse, PutRequest, PutResponse, SnapshotRequest, SnapshotResponse,
+    TxnRequest, TxnResponse,
 };
 use runkv_proto::wheel::raft_service_server::RaftService;
 use runkv_proto::wheel::wheel_service_server::WheelService;
@@ -30,7 +31,7 @@ use tracing::{trace_span, Instrument};
 
 use crate::components::command::Command;
 use crate::components::raft_manager::RaftManager;
-use crate::components::raft_network::{GrpcRaftNetwork, RaftNetwork};
+use crate::components::raft_network::{decode_raft_request_data, GrpcRaftNetwork, RaftNetwork};
 use crate::error::{Error, KvError, Result};
 use crate::meta::MetaStoreRef;
 use crate::worker::raft::Proposal;
@@ -96,6 +97,7 @@ pub struct WheelOptions {
     pub raft_network: GrpcRaftNetwork,
     pub raft_manager: RaftManager,
     pub txn_notify_pool: NotifyPool<u64, Result<TxnResponse>>,
+    pub read_only: bool,
 }
 
 struct WheelInner {
@@ -108,6 +110,11 @@ struct WheelInner {
 
     sequence_lock: TicketLock,
 
+    /// Never propose: writes are rejected with [`Error::ReadOnly`] and reads are served from
+    /// locally applied state instead of round-tripping through raft. See
+    /// [`crate::config::WheelConfig::read_only`].
+    read_only: bool,
+
     metrics: WheelServiceMetrics,
 }
 
@@ -131,6 +138,8 @@ impl Wheel {
 
                 sequence_lock: TicketLock::default(),
 
+                read_only: options.read_only,
+
                 metrics: WheelServiceMetrics::new(options.node),
             }),
         }
@@ -211,23 +220,32 @@ impl Wheel {
         assert!(!raft_nodes.is_empty());
         let raft_node = *raft_nodes.first().unwrap();
 
-        let read_only = request.ops.iter().all(|op| {
+        let read_only_ops = request.ops.iter().all(|op| {
             matches!(
                 op.request,
                 Some(kv_op_request::Request::Snapshot(_)) | Some(kv_op_request::Request::Get(_))
             )
         });
 
+        if self.inner.read_only && !read_only_ops {
+            return Err(Error::ReadOnly);
+        }
+
         let sequence = self.inner.raft_manager.get_sequence(raft_node).await?;
 
         self.inner.sequence_lock.async_acquire().await;
 
-        let sequence = if read_only {
+        let sequence = if read_only_ops {
             sequence.load(Ordering::Acquire)
         } else {
             sequence.fetch_add(1, Ordering::SeqCst) + 1
         };
 
+        if self.inner.read_only {
+            self.inner.sequence_lock.release();
+            return self.txn_local(request, raft_node, sequence).await;
+        }
+
         // Register request.
         let request_id = self.inner.request_id.fetch_add(1, Ordering::SeqCst) + 1;
         span.record("request_id", &request_id);
@@ -246,6 +264,7 @@ impl Wheel {
         let ctx = Context {
             span_id: span_id.map_or(0, |id| id.into_u64()),
             request_id,
+            propose_at: now_millis(),
         };
         let data = cmd.encode_to_vec().map_err(Error::serde_err)?;
         let context = ctx.encode_to_vec().map_err(Error::serde_err)?;
@@ -256,6 +275,9 @@ impl Wheel {
             .get_proposal_channel(raft_node)
             .await?;
 
+        // TODO: this send only fails if the worker's channel is closed; a proposal that gets
+        // dropped after this (e.g. `Error::ProposalDropped` from a leadership change) isn't
+        // observed here, so `rx` below just keeps waiting until the caller's own deadline expires.
         proposal_tx
             .send(Proposal { data, context })
             .map_err(Error::err)?;
@@ -311,6 +333,38 @@ impl Wheel {
         // TODO: Find the potential leader.
         Ok(raft_nodes)
     }
+
+    /// Serves an already-validated read-only [`TxnRequest`] straight from `raft_node`'s locally
+    /// applied LSM tree, skipping the raft proposal round trip entirely. Only reached when
+    /// [`WheelInner::read_only`] is set, so a node that is never a voter (just a raft learner)
+    /// can still serve reads of the state replicated to it.
+    async fn txn_local(
+        &self,
+        request: TxnRequest,
+        raft_node: u64,
+        sequence: u64,
+    ) -> Result<TxnResponse> {
+        let lsm_tree = self.inner.raft_manager.get_lsm_tree(raft_node).await?;
+        let mut ops = Vec::with_capacity(request.ops.len());
+        for op in request.ops {
+            let response = match op.request.unwrap() {
+                kv_op_request::Request::Get(GetRequest { key, sequence: seq }) => {
+                    let value = lsm_tree
+                        .get(&Bytes::from(key), if seq > 0 { seq } else { sequence })
+                        .await?
+                        .map(|v| v.to_vec())
+                        .unwrap_or_default();
+                    kv_op_response::Response::Get(GetResponse { value })
+                }
+                kv_op_request::Request::Snapshot(SnapshotRequest { .. }) => {
+                    kv_op_response::Response::Snapshot(SnapshotResponse { sequence })
+                }
+                req => unreachable!("read-only ops already enforced in txn_inner: {:?}", req),
+            };
+            ops.push(KvOpResponse { response: Some(response) });
+        }
+        Ok(TxnResponse { ops })
+    }
 }
 
 #[async_trait]
@@ -377,9 +431,7 @@ impl RaftService for Wheel {
         request: Request<RaftRequest>,
     ) -> core::result::Result<Response<RaftResponse>, Status> {
         let req = request.into_inner();
-        let msgs = bincode::deserialize(&req.data)
-            .map_err(Error::serde_err)
-            .map_err(internal)?;
+        let msgs = decode_raft_request_data(&req.data).map_err(internal)?;
         self.inner.raft_network.recv(msgs).await.map_err(internal)?;
         let rsp = RaftResponse::default();
         Ok(Response::new(rsp))
@@ -0,0 +1,571 @@
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use runkv_common::coding::BytesSerde;
+use runkv_common::Worker;
+use tokio::sync::{mpsc, oneshot, Semaphore};
+use tracing::warn;
+
+use crate::components::command::{Command, GearCommand};
+use crate::components::fsm::Fsm;
+use crate::components::raft_log_store::RaftGroupLogStore;
+use crate::error::{Error, GearError, Result};
+
+/// Default for [`GearOptions::command_rx`]'s channel bound. Bounded so a slow `Fsm` throttles the
+/// owning [`crate::worker::raft::RaftWorker`] (via `gear_command_tx.send().await`) instead of
+/// letting committed-but-unapplied entries pile up in memory.
+pub const DEFAULT_APPLY_CHANNEL_BOUND: usize = 256;
+
+lazy_static! {
+    // Separate from `RAFT_LATENCY_HISTOGRAM_VEC` because this tracks payload sizes, not
+    // durations; sharing labels with it keeps the two easy to cross-reference per group.
+    static ref GEAR_SIZE_HISTOGRAM_VEC: prometheus::HistogramVec = prometheus::register_histogram_vec!(
+        "gear_size_histogram_vec",
+        "gear payload size histogram vec",
+        &["op", "node", "group", "raft_node"]
+    )
+    .unwrap();
+}
+
+struct GearMetrics {
+    apply_log_entries_latency_histogram: prometheus::Histogram,
+    apply_notify_latency_histogram: prometheus::Histogram,
+
+    build_snapshot_latency_histogram: prometheus::Histogram,
+    install_snapshot_latency_histogram: prometheus::Histogram,
+    snapshot_size_bytes_histogram: prometheus::Histogram,
+}
+
+impl GearMetrics {
+    fn new(node: u64, group: u64, raft_node: u64) -> Self {
+        Self {
+            apply_log_entries_latency_histogram: super::raft::RAFT_LATENCY_HISTOGRAM_VEC
+                .get_metric_with_label_values(&[
+                    "apply_log_entries",
+                    &node.to_string(),
+                    &group.to_string(),
+                    &raft_node.to_string(),
+                ])
+                .unwrap(),
+            apply_notify_latency_histogram: super::raft::RAFT_LATENCY_HISTOGRAM_VEC
+                .get_metric_with_label_values(&[
+                    "apply_notify",
+                    &node.to_string(),
+                    &group.to_string(),
+                    &raft_node.to_string(),
+                ])
+                .unwrap(),
+
+            build_snapshot_latency_histogram: super::raft::RAFT_LATENCY_HISTOGRAM_VEC
+                .get_metric_with_label_values(&[
+                    "build_snapshot",
+                    &node.to_string(),
+                    &group.to_string(),
+                    &raft_node.to_string(),
+                ])
+                .unwrap(),
+            install_snapshot_latency_histogram: super::raft::RAFT_LATENCY_HISTOGRAM_VEC
+                .get_metric_with_label_values(&[
+                    "install_snapshot",
+                    &node.to_string(),
+                    &group.to_string(),
+                    &raft_node.to_string(),
+                ])
+                .unwrap(),
+            snapshot_size_bytes_histogram: GEAR_SIZE_HISTOGRAM_VEC
+                .get_metric_with_label_values(&[
+                    "snapshot",
+                    &node.to_string(),
+                    &group.to_string(),
+                    &raft_node.to_string(),
+                ])
+                .unwrap(),
+        }
+    }
+}
+
+/// Synchronous, per-[`Command`] extension point for [`Gear::apply`]. Unlike [`Fsm::apply`], which
+/// processes entries in async batches after the fact, a `GearHook` runs inline with log
+/// application, once per entry, in raft log order — suited to use cases that need to react to a
+/// command immediately (e.g. conditional writes) rather than wait on the `Fsm`'s batched
+/// notification.
+pub trait GearHook: Send + Sync {
+    fn on_command(&self, group: u64, command: &Command) -> Result<()>;
+}
+
+pub type GearHookRef = Arc<dyn GearHook>;
+
+/// [`GearHook`] that does nothing, used when nothing needs synchronous per-entry dispatch.
+#[derive(Debug, Default)]
+pub struct NoopGearHook;
+
+impl GearHook for NoopGearHook {
+    fn on_command(&self, _group: u64, _command: &Command) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub struct GearOptions<F: Fsm> {
+    pub node: u64,
+    pub group: u64,
+    pub raft_node: u64,
+
+    pub raft_log_store: RaftGroupLogStore,
+    pub fsm: F,
+    pub hook: GearHookRef,
+
+    pub command_rx: mpsc::Receiver<GearCommand>,
+
+    /// Shared node-wide across every group's [`Gear`], so at most this many
+    /// [`Gear::build_snapshot`] calls run at once regardless of how many groups ask for one
+    /// together (e.g. after a mass lag event). See [`Gear::build_snapshot`].
+    pub snapshot_build_limiter: Arc<Semaphore>,
+}
+
+/// Owns an [`Fsm`] and drives it from [`GearCommand`]s received over a bounded channel, decoupling
+/// FSM application from the raft ready loop. The bound on `command_rx` is what gives
+/// [`crate::worker::raft::RaftWorker`] backpressure: once it fills up, `gear_command_tx.send()`
+/// blocks the ready loop instead of letting committed-but-unapplied entries accumulate unbounded.
+pub struct Gear<F: Fsm> {
+    node: u64,
+    group: u64,
+    raft_node: u64,
+
+    raft_log_store: RaftGroupLogStore,
+    fsm: F,
+    hook: GearHookRef,
+
+    command_rx: mpsc::Receiver<GearCommand>,
+
+    snapshot_build_limiter: Arc<Semaphore>,
+
+    metrics: GearMetrics,
+}
+
+impl<F: Fsm> std::fmt::Debug for Gear<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Gear")
+            .field("node", &self.node)
+            .field("group", &self.group)
+            .field("raft_node", &self.raft_node)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl<F: Fsm> Worker for Gear<F> {
+    async fn run(&mut self) -> anyhow::Result<()> {
+        loop {
+            match self.run_inner().await {
+                Ok(_) => return Ok(()),
+                Err(e) => warn!("error occur when gear running: {}", e),
+            }
+        }
+    }
+}
+
+impl<F: Fsm> Gear<F> {
+    pub fn new(options: GearOptions<F>) -> Self {
+        Self {
+            node: options.node,
+            group: options.group,
+            raft_node: options.raft_node,
+
+            raft_log_store: options.raft_log_store,
+            fsm: options.fsm,
+            hook: options.hook,
+
+            command_rx: options.command_rx,
+
+            snapshot_build_limiter: options.snapshot_build_limiter,
+
+            metrics: GearMetrics::new(options.node, options.group, options.raft_node),
+        }
+    }
+
+    async fn run_inner(&mut self) -> Result<()> {
+        while let Some(command) = self.command_rx.recv().await {
+            match command {
+                GearCommand::Apply {
+                    group,
+                    range,
+                    is_leader,
+                } => self.post_apply(group, range, is_leader).await?,
+                GearCommand::BuildSnapshot {
+                    group: _,
+                    index,
+                    notifier,
+                } => {
+                    let snapshot = self.build_snapshot(index).await?;
+                    let _ = notifier.send(snapshot);
+                }
+                GearCommand::InstallSnapshot {
+                    group: _,
+                    index: _,
+                    snapshot,
+                    notifier,
+                } => {
+                    self.install_snapshot(snapshot).await?;
+                    let _ = notifier.send(());
+                }
+                GearCommand::Shutdown { notifier } => {
+                    // `command_rx` is a single FIFO queue shared with `Apply`, so every apply
+                    // range queued ahead of this `Shutdown` has already been drained by the time
+                    // we get here; nothing further to do before acknowledging.
+                    let _ = notifier.send(());
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Per-entry hook invoked for every entry in an applied range, before the batch is handed to
+    /// the `Fsm`. Decodes normal, non-empty entries into a [`Command`] and dispatches them to
+    /// `self.hook` synchronously and in raft log order; the `Fsm`'s own `apply` still runs
+    /// afterwards, in bulk, for the async/notify-based consumers.
+    fn apply(&self, group: u64, entry: &raft::prelude::Entry) -> Result<()> {
+        if entry.entry_type() != raft::prelude::EntryType::EntryNormal || entry.data.is_empty() {
+            return Ok(());
+        }
+        let command = Command::decode(&entry.data).map_err(|e| Error::serde_err(e.to_string()))?;
+        self.hook.on_command(group, &command)
+    }
+
+    #[tracing::instrument(level = "trace")]
+    async fn post_apply(&mut self, group: u64, range: Range<u64>, is_leader: bool) -> Result<()> {
+        let start = Instant::now();
+
+        let entries = if range.end > range.start {
+            self.raft_log_store
+                .entries(range.start, (range.end - range.start) as usize)
+                .await?
+        } else {
+            vec![]
+        };
+
+        for entry in entries.iter() {
+            self.apply(group, entry)?;
+        }
+
+        let notify_elapsed = self.fsm.apply(group, is_leader, entries).await?;
+
+        // `apply_log_entries_latency_histogram` only covers FSM processing proper. The time spent
+        // handing results off downstream (e.g. notifying a waiting proposer) is reported
+        // separately so a backed-up downstream consumer doesn't masquerade as a slow FSM.
+        let fsm_elapsed = start.elapsed().saturating_sub(notify_elapsed);
+
+        self.metrics
+            .apply_log_entries_latency_histogram
+            .observe(fsm_elapsed.as_secs_f64());
+        self.metrics
+            .apply_notify_latency_histogram
+            .observe(notify_elapsed.as_secs_f64());
+
+        Ok(())
+    }
+
+    /// Builds a snapshot of the FSM's state as of `index`, to hand off to a lagging follower.
+    ///
+    /// Note: real FSM state (the LSM tree contents) isn't serialized into snapshots yet — see the
+    /// `todo!()` in `RaftWorker::apply_snapshot` — so this currently only captures `index` itself,
+    /// matching the only thing [`Gear::install_snapshot`] can make use of today.
+    ///
+    /// Bounded by `snapshot_build_limiter`, shared node-wide: a large build competes with
+    /// foreground reads/writes for the store and CPU, so at most `max_concurrent_snapshot_builds`
+    /// (see [`GearOptions::snapshot_build_limiter`]) run at once across every group on the node,
+    /// and the rest queue here rather than piling on together after a mass lag event.
+    #[tracing::instrument(level = "trace")]
+    async fn build_snapshot(&self, index: u64) -> Result<Vec<u8>> {
+        let _permit = self
+            .snapshot_build_limiter
+            .acquire()
+            .await
+            .expect("snapshot_build_limiter is never closed");
+
+        let start = Instant::now();
+        let snapshot = bincode::serialize(&index).map_err(Error::serde_err)?;
+        self.metrics
+            .build_snapshot_latency_histogram
+            .observe(start.elapsed().as_secs_f64());
+        self.metrics
+            .snapshot_size_bytes_histogram
+            .observe(snapshot.len() as f64);
+        Ok(snapshot)
+    }
+
+    /// Installs a snapshot built by [`Gear::build_snapshot`].
+    #[tracing::instrument(level = "trace")]
+    async fn install_snapshot(&self, snapshot: Vec<u8>) -> Result<()> {
+        let start = Instant::now();
+        let _index: u64 = bincode::deserialize(&snapshot).map_err(Error::serde_err)?;
+        self.metrics
+            .install_snapshot_latency_histogram
+            .observe(start.elapsed().as_secs_f64());
+        Ok(())
+    }
+}
+
+/// Asks the [`Gear`] on the other end of `gear_command_tx` to stop, and waits for it to
+/// acknowledge once every [`GearCommand::Apply`] queued ahead of this call has been drained.
+/// Sending to an already-exited `Gear` (or one that exits before acknowledging) surfaces as
+/// [`GearError::ApplyConsumerGone`] rather than panicking, so a caller can tell "already gone"
+/// apart from other failures and skip retrying.
+pub async fn shutdown_gear(gear_command_tx: &mpsc::Sender<GearCommand>) -> Result<()> {
+    let (notifier, notify_rx) = oneshot::channel();
+    gear_command_tx
+        .send(GearCommand::Shutdown { notifier })
+        .await
+        .map_err(|_| GearError::ApplyConsumerGone)?;
+    notify_rx.await.map_err(|_| GearError::ApplyConsumerGone.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use runkv_storage::raft_log_store::entry::RaftLogBatchBuilder;
+    use runkv_storage::raft_log_store::log::Persist;
+    use runkv_storage::raft_log_store::store::RaftLogStoreOptions;
+    use runkv_storage::raft_log_store::RaftLogStore;
+    use test_log::test;
+
+    use super::*;
+    use crate::components::fsm::tests::MockFsm;
+
+    async fn build_raft_log_store(path: &str) -> RaftLogStore {
+        let options = RaftLogStoreOptions {
+            node: 0,
+            log_dir_path: path.to_string(),
+            log_file_capacity: 64 << 20,
+            block_cache_capacity: 64 << 20,
+            persist: Persist::Sync,
+            strict_repair: false,
+            compression_threshold: runkv_storage::raft_log_store::DEFAULT_COMPRESSION_THRESHOLD,
+        };
+        RaftLogStore::open(options).await.unwrap()
+    }
+
+    #[test(tokio::test)]
+    async fn test_build_and_install_snapshot_populates_metrics() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store);
+
+        let (fsm, _apply_rx) = MockFsm::new(true);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        // Unique node id so this test's time series don't mix with other tests' observations of
+        // the same globally registered histograms.
+        const NODE: u64 = 97;
+        let gear = Gear::new(GearOptions {
+            node: NODE,
+            group: 100,
+            raft_node: 1,
+            raft_log_store: group_raft_log_store,
+            fsm,
+            hook: Arc::new(NoopGearHook),
+            command_rx,
+            snapshot_build_limiter: Arc::new(Semaphore::new(2)),
+        });
+
+        let snapshot = gear.build_snapshot(42).await.unwrap();
+        gear.install_snapshot(snapshot).await.unwrap();
+
+        let build_histogram = super::super::raft::RAFT_LATENCY_HISTOGRAM_VEC
+            .get_metric_with_label_values(&["build_snapshot", &NODE.to_string(), "100", "1"])
+            .unwrap();
+        let install_histogram = super::super::raft::RAFT_LATENCY_HISTOGRAM_VEC
+            .get_metric_with_label_values(&["install_snapshot", &NODE.to_string(), "100", "1"])
+            .unwrap();
+        let size_histogram = GEAR_SIZE_HISTOGRAM_VEC
+            .get_metric_with_label_values(&["snapshot", &NODE.to_string(), "100", "1"])
+            .unwrap();
+
+        assert_eq!(build_histogram.get_sample_count(), 1);
+        assert_eq!(install_histogram.get_sample_count(), 1);
+        assert_eq!(size_histogram.get_sample_count(), 1);
+        assert!(size_histogram.get_sample_sum() > 0.0);
+    }
+
+    #[derive(Default)]
+    struct CountingGearHook {
+        counter: std::sync::atomic::AtomicU64,
+        seen: std::sync::Mutex<Vec<u64>>,
+    }
+
+    impl GearHook for CountingGearHook {
+        fn on_command(&self, _group: u64, command: &Command) -> Result<()> {
+            if let Command::CompactRaftLog { index, .. } = command {
+                self.counter
+                    .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                self.seen.lock().unwrap().push(*index);
+            }
+            Ok(())
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn test_apply_dispatches_commands_to_hook_in_order() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(2).await.unwrap();
+        let group_raft_log_store = RaftGroupLogStore::new(2, raft_log_store);
+
+        let (fsm, _apply_rx) = MockFsm::new(true);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let hook = Arc::new(CountingGearHook::default());
+        let gear = Gear::new(GearOptions {
+            node: 98,
+            group: 100,
+            raft_node: 1,
+            raft_log_store: group_raft_log_store,
+            fsm,
+            hook: hook.clone(),
+            command_rx,
+            snapshot_build_limiter: Arc::new(Semaphore::new(2)),
+        });
+
+        for i in 0..5 {
+            let entry = raft::prelude::Entry {
+                entry_type: raft::prelude::EntryType::EntryNormal as i32,
+                index: i,
+                data: bincode::serialize(&Command::CompactRaftLog {
+                    index: i,
+                    sequence: i,
+                })
+                .unwrap(),
+                ..Default::default()
+            };
+            gear.apply(100, &entry).unwrap();
+        }
+
+        assert_eq!(hook.counter.load(std::sync::atomic::Ordering::SeqCst), 5);
+        assert_eq!(*hook.seen.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test(tokio::test)]
+    async fn test_shutdown_drains_pending_apply_before_acking() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(3).await.unwrap();
+        let group_raft_log_store = RaftGroupLogStore::new(3, raft_log_store.clone());
+
+        let entry = raft::prelude::Entry {
+            entry_type: raft::prelude::EntryType::EntryNormal as i32,
+            // A group's raft log starts at index 1.
+            index: 1,
+            data: bincode::serialize(&Command::CompactRaftLog {
+                index: 0,
+                sequence: 0,
+            })
+            .unwrap(),
+            ..Default::default()
+        };
+        let mut builder = RaftLogBatchBuilder::default();
+        builder.add(
+            3,
+            1,
+            1,
+            &[],
+            &crate::components::raft_log_store::encode_entry_data(&entry),
+        );
+        raft_log_store.append(builder.build()).await.unwrap();
+
+        let (fsm, mut apply_rx) = MockFsm::new(true);
+        let (command_tx, command_rx) = mpsc::channel(DEFAULT_APPLY_CHANNEL_BOUND);
+        let mut gear = Gear::new(GearOptions {
+            node: 99,
+            group: 3,
+            raft_node: 1,
+            raft_log_store: group_raft_log_store,
+            fsm,
+            hook: Arc::new(NoopGearHook),
+            command_rx,
+            snapshot_build_limiter: Arc::new(Semaphore::new(2)),
+        });
+        let handle = tokio::spawn(async move { gear.run().await });
+
+        command_tx
+            .send(GearCommand::Apply {
+                group: 3,
+                range: 1..2,
+                is_leader: true,
+            })
+            .await
+            .unwrap();
+
+        // Queued on the same FIFO channel behind the `Apply` above: by the time `shutdown_gear`'s
+        // ack fires, the applied entry must already have reached the `Fsm`.
+        shutdown_gear(&command_tx).await.unwrap();
+
+        assert_eq!(apply_rx.recv().await.unwrap().index, 1);
+        handle.await.unwrap().unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn test_shutdown_gear_surfaces_typed_error_when_consumer_gone() {
+        let (command_tx, command_rx) = mpsc::channel::<GearCommand>(1);
+        drop(command_rx);
+
+        let err = shutdown_gear(&command_tx).await.unwrap_err();
+        assert_matches!(err, Error::GearError(GearError::ApplyConsumerGone));
+    }
+
+    #[test(tokio::test)]
+    async fn test_build_snapshot_queues_past_the_concurrency_limit() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(4).await.unwrap();
+        let group_raft_log_store = RaftGroupLogStore::new(4, raft_log_store);
+
+        const LIMIT: usize = 1;
+        let (fsm, _apply_rx) = MockFsm::new(true);
+        let (_command_tx, command_rx) = mpsc::channel(1);
+        let gear = Arc::new(Gear::new(GearOptions {
+            node: 100,
+            group: 4,
+            raft_node: 1,
+            raft_log_store: group_raft_log_store,
+            fsm,
+            hook: Arc::new(NoopGearHook),
+            command_rx,
+            snapshot_build_limiter: Arc::new(Semaphore::new(LIMIT)),
+        }));
+
+        // Simulate `LIMIT` builds already in flight by holding their permits directly, without
+        // going through `build_snapshot` (which would return immediately and release them again).
+        let held_permits = gear
+            .snapshot_build_limiter
+            .clone()
+            .acquire_many_owned(LIMIT as u32)
+            .await
+            .unwrap();
+
+        let mut queued = tokio::spawn({
+            let gear = gear.clone();
+            async move { gear.build_snapshot(1).await }
+        });
+
+        // With every permit held, the extra build must queue rather than run.
+        tokio::select! {
+            _ = &mut queued => panic!("build_snapshot should block while the concurrency limit is fully held"),
+            _ = tokio::time::sleep(Duration::from_millis(200)) => {}
+        }
+
+        // Freeing a permit (as a real in-flight build finishing would) lets the queued one run.
+        drop(held_permits);
+        let snapshot = tokio::time::timeout(Duration::from_secs(5), queued)
+            .await
+            .expect("build_snapshot should complete once a permit frees up")
+            .unwrap()
+            .unwrap();
+        assert_eq!(bincode::deserialize::<u64>(&snapshot).unwrap(), 1);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod gear;
 pub mod heartbeater;
 pub mod raft;
+pub mod raft_executor;
 pub mod sstable_uploader;
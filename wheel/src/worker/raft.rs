@@ -1,4 +1,6 @@
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
@@ -6,22 +8,35 @@ use futures::future;
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use prost::Message;
-use runkv_common::context::Context;
+use raft::Storage;
+use rand::Rng;
+use runkv_common::coding::BytesSerde;
+use runkv_common::context::{now_millis, Context};
 use runkv_common::Worker;
-use runkv_storage::raft_log_store::entry::RaftLogBatchBuilder;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tracing::{trace, trace_span, warn};
 
-use crate::components::fsm::Fsm;
+use crate::components::clock::ClockRef;
+use crate::components::command::GearCommand;
 use crate::components::raft_log_store::{encode_entry_data, RaftGroupLogStore};
 use crate::components::raft_network::{RaftClient, RaftNetwork};
-use crate::error::{Error, Result};
+use crate::error::{Error, GearError, Result};
 
 const RAFT_HEARTBEAT_TICK_DURATION: Duration = Duration::from_millis(100);
 
+/// Tick interval for a group with a single voter, used in place of
+/// [`RAFT_HEARTBEAT_TICK_DURATION`]. A lone voter never has a peer to heartbeat or to time out
+/// waiting on, so the only thing a tick still does for it is advance an election timer this node
+/// itself already resolved (see [`RaftWorker::build`]'s single-voter fast path below); ticking
+/// it at the normal cadence just wakes the group for nothing. Many small single-replica groups on
+/// one node add up, so this is stretched out instead of left at [`RAFT_HEARTBEAT_TICK_DURATION`].
+const RAFT_SINGLE_VOTER_TICK_DURATION: Duration = Duration::from_secs(1);
+
 lazy_static! {
-    static ref RAFT_LATENCY_HISTOGRAM_VEC: prometheus::HistogramVec =
+    // `pub(crate)` so `crate::worker::gear::Gear` can report its own ops (`apply_log_entries`,
+    // `apply_notify`) against the same vec/label scheme instead of registering a duplicate.
+    pub(crate) static ref RAFT_LATENCY_HISTOGRAM_VEC: prometheus::HistogramVec =
         prometheus::register_histogram_vec!(
             "raft_latency_histogram_vec",
             "raft latency histogram vec",
@@ -34,13 +49,57 @@ lazy_static! {
         &["op", "node", "group", "raft_node"]
     )
     .unwrap();
+    /// Node-level counterpart to [`RAFT_LATENCY_HISTOGRAM_VEC`], labeled only by `op`/`node`. A
+    /// [`RaftWorker`] built with [`RaftWorkerOptions::metrics_cardinality_aggregated`] reports here
+    /// instead, trading per-group detail for bounded cardinality on a node hosting many groups.
+    pub(crate) static ref RAFT_LATENCY_HISTOGRAM_VEC_AGGREGATED: prometheus::HistogramVec =
+        prometheus::register_histogram_vec!(
+            "raft_latency_histogram_vec_aggregated",
+            "raft latency histogram vec, aggregated at the node level",
+            &["op", "node"]
+        )
+        .unwrap();
+    /// Node-level counterpart to [`RAFT_THROUGHPUT_GAUGE_VEC`]. See
+    /// [`RAFT_LATENCY_HISTOGRAM_VEC_AGGREGATED`].
+    static ref RAFT_THROUGHPUT_GAUGE_VEC_AGGREGATED: prometheus::GaugeVec =
+        prometheus::register_gauge_vec!(
+            "raft_throughput_gauge_vec_aggregated",
+            "raft throughput gauge vec, aggregated at the node level",
+            &["op", "node"]
+        )
+        .unwrap();
+    /// Messages dropped after exhausting [`SEND_MAX_RETRIES`] retries to an unreachable peer.
+    /// Raft's own protocol retries will re-send the underlying state eventually, but a steady
+    /// stream of drops here means a peer has been unreachable for a while and is worth paging on.
+    static ref RAFT_DROPPED_MESSAGES_COUNTER_VEC: prometheus::IntCounterVec =
+        prometheus::register_int_counter_vec!(
+            "raft_dropped_messages_counter_vec",
+            "raft messages dropped after exhausting send retries to a peer",
+            &["node", "group", "raft_node", "peer"]
+        )
+        .unwrap();
 }
 
+/// How many times [`RaftWorker::send_messages`] retries a failed `send` to a single peer before
+/// giving up on that batch and counting it as dropped.
+const SEND_MAX_RETRIES: usize = 3;
+/// Base delay before the first retry; doubled on each subsequent attempt.
+const SEND_RETRY_BASE_BACKOFF: Duration = Duration::from_millis(10);
+
 struct RaftMetrics {
     append_log_entries_latency_histogram: prometheus::Histogram,
     append_log_entries_throughput_gauge: prometheus::Gauge,
 
-    apply_log_entries_latency_histogram: prometheus::Histogram,
+    /// Time spent handing a committed range off to [`crate::worker::gear::Gear`], i.e. blocked on
+    /// `gear_command_tx.send().await`. This is where a slow `Fsm` shows up from `RaftWorker`'s
+    /// side: as enqueue latency, not as `handle_ready` latency directly.
+    apply_enqueue_latency_histogram: prometheus::Histogram,
+
+    /// End-to-end latency from `Context::propose_at` (stamped when the proposer first calls
+    /// `propose`) to this entry being observed as committed in `apply_log_entries`, on whichever
+    /// node happens to observe it. The single number users actually care about, as opposed to the
+    /// individual append/apply/send legs.
+    commit_latency_histogram: prometheus::Histogram,
 
     send_messages_latency_histogram: prometheus::Histogram,
     send_messages_throughput_gauge: prometheus::Gauge,
@@ -50,67 +109,49 @@ struct RaftMetrics {
 }
 
 impl RaftMetrics {
-    fn new(node: u64, group: u64, raft_node: u64) -> Self {
+    /// `aggregated` drops the `group`/`raft_node` labels, reporting into
+    /// [`RAFT_LATENCY_HISTOGRAM_VEC_AGGREGATED`]/[`RAFT_THROUGHPUT_GAUGE_VEC_AGGREGATED`] instead
+    /// of the per-group vecs. See [`RaftWorkerOptions::metrics_cardinality_aggregated`].
+    fn new(node: u64, group: u64, raft_node: u64, aggregated: bool) -> Self {
+        let node = node.to_string();
+        let group = group.to_string();
+        let raft_node = raft_node.to_string();
+        let histogram = |op: &str| {
+            if aggregated {
+                RAFT_LATENCY_HISTOGRAM_VEC_AGGREGATED
+                    .get_metric_with_label_values(&[op, &node])
+                    .unwrap()
+            } else {
+                RAFT_LATENCY_HISTOGRAM_VEC
+                    .get_metric_with_label_values(&[op, &node, &group, &raft_node])
+                    .unwrap()
+            }
+        };
+        let gauge = |op: &str| {
+            if aggregated {
+                RAFT_THROUGHPUT_GAUGE_VEC_AGGREGATED
+                    .get_metric_with_label_values(&[op, &node])
+                    .unwrap()
+            } else {
+                RAFT_THROUGHPUT_GAUGE_VEC
+                    .get_metric_with_label_values(&[op, &node, &group, &raft_node])
+                    .unwrap()
+            }
+        };
+
         Self {
-            append_log_entries_latency_histogram: RAFT_LATENCY_HISTOGRAM_VEC
-                .get_metric_with_label_values(&[
-                    "append_log_entries",
-                    &node.to_string(),
-                    &group.to_string(),
-                    &raft_node.to_string(),
-                ])
-                .unwrap(),
-            append_log_entries_throughput_gauge: RAFT_THROUGHPUT_GAUGE_VEC
-                .get_metric_with_label_values(&[
-                    "append_log_entries",
-                    &node.to_string(),
-                    &group.to_string(),
-                    &raft_node.to_string(),
-                ])
-                .unwrap(),
+            append_log_entries_latency_histogram: histogram("append_log_entries"),
+            append_log_entries_throughput_gauge: gauge("append_log_entries"),
 
-            apply_log_entries_latency_histogram: RAFT_LATENCY_HISTOGRAM_VEC
-                .get_metric_with_label_values(&[
-                    "apply_log_entries",
-                    &node.to_string(),
-                    &group.to_string(),
-                    &raft_node.to_string(),
-                ])
-                .unwrap(),
+            apply_enqueue_latency_histogram: histogram("apply_enqueue"),
 
-            send_messages_latency_histogram: RAFT_LATENCY_HISTOGRAM_VEC
-                .get_metric_with_label_values(&[
-                    "send_messages",
-                    &node.to_string(),
-                    &group.to_string(),
-                    &raft_node.to_string(),
-                ])
-                .unwrap(),
-            send_messages_throughput_gauge: RAFT_THROUGHPUT_GAUGE_VEC
-                .get_metric_with_label_values(&[
-                    "send_messages",
-                    &node.to_string(),
-                    &group.to_string(),
-                    &raft_node.to_string(),
-                ])
-                .unwrap(),
+            commit_latency_histogram: histogram("commit"),
 
-            handle_ready_latency_histogram: RAFT_LATENCY_HISTOGRAM_VEC
-                .get_metric_with_label_values(&[
-                    "handle_ready",
-                    &node.to_string(),
-                    &group.to_string(),
-                    &raft_node.to_string(),
-                ])
-                .unwrap(),
-            poll_channel_latency_histogram: RAFT_LATENCY_HISTOGRAM_VEC
-                .get_metric_with_label_values(&[
-                    "poll_channel",
-                    &node.to_string(),
-                    &group.to_string(),
-                    &raft_node.to_string(),
-                ])
-                .unwrap(),
+            send_messages_latency_histogram: histogram("send_messages"),
+            send_messages_throughput_gauge: gauge("send_messages"),
+
+            handle_ready_latency_histogram: histogram("handle_ready"),
+            poll_channel_latency_histogram: histogram("poll_channel"),
         }
     }
 }
@@ -123,10 +164,116 @@ pub struct Proposal {
 
 pub enum RaftStartMode {
     Initialize { peers: Vec<u64> },
-    Restart { peers: Vec<u64> },
+    Restart { peers: Vec<u64>, applied: u64 },
+    /// Starts a never-before-run node from an FSM snapshot pulled out-of-band (e.g. from an
+    /// existing peer's [`crate::worker::gear::Gear`]) instead of replaying this group's full
+    /// history, so joining a large, long-running group doesn't have to wait on raft to ship that
+    /// history one [`GearCommand::Apply`] range at a time. `snapshot`/`snapshot_term` must match
+    /// the state as of `applied`: [`RaftWorker::build`] seeds the log to start right after
+    /// `applied` (see [`RaftGroupLogStore::seed_snapshot_boundary`]) and hands `snapshot` to this
+    /// worker's own `Gear` via [`GearCommand::InstallSnapshot`] before raft starts ticking.
+    ///
+    /// [`GearCommand::Apply`]: crate::components::command::GearCommand::Apply
+    /// [`GearCommand::InstallSnapshot`]: crate::components::command::GearCommand::InstallSnapshot
+    Bootstrap {
+        peers: Vec<u64>,
+        applied: u64,
+        snapshot_term: u64,
+        snapshot: Vec<u8>,
+    },
+}
+
+/// Out-of-band control messages for a running [`RaftWorker`], distinct from raft protocol
+/// [`raft::prelude::Message`]s and client [`Proposal`]s.
+pub enum RaftWorkerControl {
+    /// Gracefully transfers leadership to `target_raft_node`, e.g. to drain a node for planned
+    /// maintenance without forcing an election. `notify` resolves once `raft_soft_state.leader_id`
+    /// becomes `target_raft_node`, or with an error if the target isn't a current voter.
+    TransferLeader {
+        target_raft_node: u64,
+        notify: oneshot::Sender<Result<()>>,
+    },
+    /// Forces this node to start a (pre-)election, e.g. after an operator has isolated a bad
+    /// leader and wants a specific follower to take over. `notify` resolves once the election has
+    /// been started, not once it's won.
+    Campaign { notify: oneshot::Sender<Result<()>> },
+    /// Forces this node to step down to follower, e.g. to stop it from winning future elections
+    /// while it's being drained. `notify` resolves once the step-down has been applied.
+    StepDown { notify: oneshot::Sender<Result<()>> },
+    /// Reports this group's current [`RaftStatus`], e.g. for a `runkvctl raft status` command to
+    /// display replication lag. `notify` resolves with the status as of whenever this message is
+    /// processed, not a live snapshot.
+    Status {
+        notify: oneshot::Sender<Result<RaftStatus>>,
+    },
+}
+
+/// A group's replication state as of some point in time, for observability: debugging a group
+/// that looks stuck, or a follower that's falling behind on applying committed entries.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RaftStatus {
+    /// `0` if this node doesn't currently know of a leader.
+    pub leader_id: u64,
+    pub term: u64,
+    /// Highest log index known to be committed, per this node's persisted `HardState`. May lag
+    /// slightly behind what [`raft::RawNode`] has committed in memory but not yet persisted.
+    pub committed_index: u64,
+    /// Highest log index handed off to [`crate::worker::gear::Gear`] for applying to the FSM.
+    /// [`Gear`] applies asynchronously, so the FSM's own applied index may still lag this.
+    ///
+    /// [`Gear`]: crate::worker::gear::Gear
+    pub applied_index: u64,
+    /// Highest log index persisted in this node's raft log.
+    pub last_index: u64,
+}
+
+/// Configures when a raft group should trigger compacting its log, i.e. when it should treat the
+/// FSM state as having been snapshotted up to the applied index.
+#[derive(Clone, Copy, Debug)]
+pub struct SnapshotPolicy {
+    /// Trigger once the raft log has grown this many entries past the last triggered index. `0`
+    /// (the default) disables automatic triggering.
+    pub log_gap_threshold: u64,
+}
+
+impl Default for SnapshotPolicy {
+    fn default() -> Self {
+        Self {
+            log_gap_threshold: 0,
+        }
+    }
 }
 
-pub struct RaftWorkerOptions<RN: RaftNetwork, F: Fsm> {
+impl SnapshotPolicy {
+    /// Whether a snapshot should be triggered given the raft log has advanced from
+    /// `last_snapshot_index` to `applied_index`.
+    pub fn should_trigger(&self, last_snapshot_index: u64, applied_index: u64) -> bool {
+        self.log_gap_threshold > 0
+            && applied_index.saturating_sub(last_snapshot_index) >= self.log_gap_threshold
+    }
+}
+
+/// Default for [`RaftWorkerOptions::max_size_per_msg`], matching the value hardcoded before this
+/// became configurable.
+pub const DEFAULT_MAX_SIZE_PER_MSG: u64 = 1 << 20;
+/// Default for [`RaftWorkerOptions::max_inflight_msgs`], matching the value hardcoded before this
+/// became configurable.
+pub const DEFAULT_MAX_INFLIGHT_MSGS: usize = 256;
+/// Default for [`RaftWorkerOptions::min_loop_duration`], matching the value hardcoded before this
+/// became configurable.
+pub const DEFAULT_MIN_LOOP_DURATION: Duration = Duration::from_millis(10);
+/// Default for [`RaftWorkerOptions::check_quorum`], matching the value hardcoded before this
+/// became configurable.
+pub const DEFAULT_CHECK_QUORUM: bool = true;
+/// Default for [`RaftWorkerOptions::pre_vote`], matching the value hardcoded before this became
+/// configurable.
+pub const DEFAULT_PRE_VOTE: bool = true;
+/// Default for [`RaftWorkerOptions::tick_jitter`]: a fifth of [`RAFT_HEARTBEAT_TICK_DURATION`],
+/// enough to spread heartbeats out across a node hosting many groups without meaningfully
+/// delaying any individual tick.
+pub const DEFAULT_TICK_JITTER: Duration = Duration::from_millis(20);
+
+pub struct RaftWorkerOptions<RN: RaftNetwork> {
     pub group: u64,
     pub node: u64,
     pub raft_node: u64,
@@ -136,15 +283,69 @@ pub struct RaftWorkerOptions<RN: RaftNetwork, F: Fsm> {
     pub raft_logger: slog::Logger,
     pub raft_network: RN,
 
-    pub proposal_rx: mpsc::UnboundedReceiver<Proposal>,
+    /// Drives the tick/backoff loop's notion of time. [`RealClock`] in production; tests inject a
+    /// [`MockClock`] to advance virtual time and exercise election/heartbeat timing without real
+    /// sleeps.
+    pub clock: ClockRef,
 
-    pub fsm: F,
+    pub proposal_rx: mpsc::UnboundedReceiver<Proposal>,
+    pub control_rx: mpsc::UnboundedReceiver<RaftWorkerControl>,
+
+    /// Hands committed entry ranges off to the [`crate::worker::gear::Gear`] that owns the FSM.
+    /// Bounded so a slow apply side throttles the raft ready loop (via `send().await`) instead of
+    /// letting committed-but-unapplied entries pile up in memory.
+    pub gear_command_tx: mpsc::Sender<GearCommand>,
+
+    pub snapshot_policy: SnapshotPolicy,
+
+    /// Caps the total size (in bytes) of log entries raft batches into a single outbound
+    /// message. Lower this on memory-constrained nodes; raise it on high-latency links where the
+    /// default throttles throughput. Must be non-zero.
+    pub max_size_per_msg: u64,
+    /// Caps the number of in-flight (unacknowledged) append messages raft will keep outstanding
+    /// per follower before waiting for acks. Must be non-zero.
+    pub max_inflight_msgs: usize,
+    /// Floor on how often the ready loop spins when it has actual work to do, to keep a busy
+    /// group from pegging a CPU core re-checking empty channels. Has no effect when the loop is
+    /// idle, since idle iterations block on [`RaftWorkerOptions::clock`]'s sleep or a channel
+    /// recv instead of looping at all.
+    pub min_loop_duration: Duration,
+    /// Whether this raft group steps down as leader when it can't reach a quorum of followers
+    /// within an election timeout. Defaults to `true`; test and single-node scenarios that want
+    /// a leader to keep serving reads/writes through a quorum loss (or just to elect faster, with
+    /// fewer safety checks in the way) can turn it off. See [`RaftWorkerOptions::pre_vote`] for a
+    /// caveat about disabling one but not the other.
+    pub check_quorum: bool,
+    /// Whether a node campaigns with a non-disruptive pre-vote round before bumping its term and
+    /// campaigning for real, so a partitioned-then-rejoined node doesn't force an unnecessary
+    /// re-election just by rejoining. Defaults to `true`. Per upstream raft-rs, enabling this
+    /// without [`RaftWorkerOptions::check_quorum`] has known correctness caveats around leader
+    /// stickiness; [`RaftWorker::build`] warns if it sees that combination rather than rejecting
+    /// it outright, since some callers may still want it.
+    pub pre_vote: bool,
+    /// Upper bound on random jitter added to each heartbeat tick interval, so groups that all
+    /// started at the same time (e.g. every group on a freshly started node) don't stay in
+    /// lockstep and spike the node's CPU/network on every tick in unison. `0` disables jitter
+    /// entirely, ticking at exactly the base interval like before this was configurable.
+    pub tick_jitter: Duration,
+    /// Whether [`RaftWorker`] observes its per-op histograms/gauges in [`RaftWorker::handle_ready`],
+    /// [`RaftWorker::send_messages`] and [`RaftWorker::append_log_entries`]. Defaults to `true`;
+    /// turn off on a node hosting many tiny groups whose scrape cost isn't worth paying for
+    /// metrics nobody looks at.
+    pub metrics_enabled: bool,
+    /// If set, this worker's metrics drop their `group`/`raft_node` labels and aggregate into a
+    /// single node-level series per op (see `RAFT_LATENCY_HISTOGRAM_VEC_AGGREGATED`), instead of
+    /// the usual per-group series. Intended for callers that decide this per worker based on how
+    /// many groups the node is currently hosting, e.g.
+    /// [`crate::components::raft_manager::RaftManagerOptions::metrics_cardinality_threshold`],
+    /// trading per-group detail for bounded prometheus cardinality once a node hosts too many
+    /// groups for per-group labels to stay cheap.
+    pub metrics_cardinality_aggregated: bool,
 }
 
-pub struct RaftWorker<RN, F>
+pub struct RaftWorker<RN>
 where
     RN: RaftNetwork,
-    F: Fsm,
 {
     group: u64,
     node: u64,
@@ -152,22 +353,67 @@ where
 
     raft: raft::RawNode<RaftGroupLogStore>,
     raft_log_store: RaftGroupLogStore,
-    _raft_network: RN,
+    raft_network: RN,
     raft_soft_state: Option<raft::SoftState>,
     raft_clients: HashMap<u64, RN::RaftClient>,
 
+    clock: ClockRef,
+    min_loop_duration: Duration,
+    /// [`RAFT_SINGLE_VOTER_TICK_DURATION`] for a single-voter group,
+    /// [`RAFT_HEARTBEAT_TICK_DURATION`] otherwise. Fixed at construction time; a later membership
+    /// change isn't reflected here today (see [`RaftWorker::build`]'s single-voter fast path,
+    /// which has the same limitation).
+    tick_interval: Duration,
+    /// [`RaftWorkerOptions::tick_jitter`]. Added to [`Self::tick_interval`] afresh, via
+    /// [`Self::next_tick_interval`], each time a tick deadline is scheduled, so groups started at
+    /// the same instant drift apart instead of re-converging.
+    tick_jitter: Duration,
+    /// Next heartbeat-tick deadline for [`Self::poll_once`]'s standalone, non-blocking ticking.
+    /// Unused by [`Self::run_inner`], which tracks its own `remaining_timeout` across the
+    /// blocking sleep/select it's free to use outside of a multiplexed executor.
+    tick_deadline: Option<Duration>,
+
     message_rx: mpsc::UnboundedReceiver<raft::prelude::Message>,
     proposal_rx: mpsc::UnboundedReceiver<Proposal>,
+    control_rx: mpsc::UnboundedReceiver<RaftWorkerControl>,
+
+    gear_command_tx: mpsc::Sender<GearCommand>,
+
+    /// Per-peer message batches, scratch space reused across [`Self::send_messages`] calls.
+    /// Entries (and the `Vec`s in them) outlive a single call and are `clear()`ed rather than
+    /// dropped, so steady-state traffic doesn't reallocate the map or its buckets every ready
+    /// cycle.
+    raft_node_msgs_scratch: HashMap<u64, Vec<raft::prelude::Message>>,
 
-    fsm: F,
+    /// Caches each raft log entry's decoded `Context::span_id` for the lifetime of a single
+    /// [`Self::handle_ready`] call, keyed by [`raft::prelude::Entry::index`]. The same entry
+    /// commonly shows up in both an outgoing `MsgAppend` (once per follower, via
+    /// [`Self::send_messages`]) and this node's own [`Self::append_log_entries`] batch within one
+    /// ready cycle; without this, each of those sites re-runs `bincode::deserialize` on identical
+    /// bytes. Cleared at the top of every `handle_ready` so it never grows unbounded. Only
+    /// populated when built with the `tracing` feature, since `span_id` is only used there.
+    decoded_context_span_ids: HashMap<u64, u64>,
+
+    /// Range (and leadership) staged by [`Self::apply_log_entries`] but not yet handed to `Gear`.
+    /// See [`Self::flush_pending_apply`].
+    pending_apply: Option<(Range<u64>, bool)>,
 
     metrics: RaftMetrics,
+    metrics_enabled: bool,
+
+    snapshot_policy: SnapshotPolicy,
+    last_snapshot_index: u64,
+    /// Highest log index handed off to [`crate::worker::gear::Gear`] so far. Reported as
+    /// [`RaftStatus::applied_index`]; see there for why it's an approximation of the FSM's own
+    /// applied index rather than the real thing.
+    applied_index: u64,
+
+    pending_leader_transfer: Option<(u64, oneshot::Sender<Result<()>>)>,
 }
 
-impl<RN, F> std::fmt::Debug for RaftWorker<RN, F>
+impl<RN> std::fmt::Debug for RaftWorker<RN>
 where
     RN: RaftNetwork,
-    F: Fsm,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RaftWorker")
@@ -179,10 +425,9 @@ where
 }
 
 #[async_trait]
-impl<RN, F> Worker for RaftWorker<RN, F>
+impl<RN> Worker for RaftWorker<RN>
 where
     RN: RaftNetwork,
-    F: Fsm,
 {
     async fn run(&mut self) -> anyhow::Result<()> {
         // TODO: Gracefully kill.
@@ -195,15 +440,33 @@ where
     }
 }
 
-impl<RN, F> RaftWorker<RN, F>
+impl<RN> RaftWorker<RN>
 where
     RN: RaftNetwork,
-    F: Fsm,
 {
-    pub async fn build(options: RaftWorkerOptions<RN, F>) -> Result<Self> {
+    pub async fn build(options: RaftWorkerOptions<RN>) -> Result<Self> {
+        if options.max_size_per_msg == 0 {
+            return Err(Error::ConfigError(
+                "`max_size_per_msg` must be non-zero".to_string(),
+            ));
+        }
+        if options.max_inflight_msgs == 0 {
+            return Err(Error::ConfigError(
+                "`max_inflight_msgs` must be non-zero".to_string(),
+            ));
+        }
+        if options.pre_vote && !options.check_quorum {
+            warn!(
+                "raft group {} raft node {} has `pre_vote` enabled without `check_quorum`; \
+                 upstream raft-rs notes this combination has known leader-stickiness caveats",
+                options.group, options.raft_node
+            );
+        }
+
         let applied = match options.raft_start_mode {
             RaftStartMode::Initialize { .. } => 0,
-            RaftStartMode::Restart { .. } => options.fsm.raft_applied_index().await?,
+            RaftStartMode::Restart { applied, .. } => applied,
+            RaftStartMode::Bootstrap { applied, .. } => applied,
         };
 
         let raft_config = raft::Config {
@@ -211,10 +474,10 @@ where
             // election_tick: todo!(),
             // heartbeat_tick: todo!(),
             applied,
-            max_size_per_msg: 1 << 20,
-            max_inflight_msgs: 256,
-            check_quorum: true,
-            pre_vote: true,
+            max_size_per_msg: options.max_size_per_msg,
+            max_inflight_msgs: options.max_inflight_msgs,
+            check_quorum: options.check_quorum,
+            pre_vote: options.pre_vote,
             // min_election_tick: todo!(),
             // max_election_tick: todo!(),
             read_only_option: raft::ReadOnlyOption::Safe,
@@ -229,12 +492,19 @@ where
 
         let peers = match options.raft_start_mode {
             RaftStartMode::Initialize { ref peers } => peers.clone(),
-            RaftStartMode::Restart { ref peers } => peers.clone(),
+            RaftStartMode::Restart { ref peers, .. } => peers.clone(),
+            RaftStartMode::Bootstrap { ref peers, .. } => peers.clone(),
         };
 
         let raft_log_store = options.raft_log_store.clone();
 
-        if let RaftStartMode::Initialize { .. } = options.raft_start_mode {
+        // `Initialize` and `Bootstrap` both start this node with no prior raft metadata of its
+        // own, so both need a freshly-written `ConfState`; `Restart` reuses whatever this node
+        // already persisted before it last stopped.
+        if matches!(
+            options.raft_start_mode,
+            RaftStartMode::Initialize { .. } | RaftStartMode::Bootstrap { .. }
+        ) {
             let cs = raft::prelude::ConfState {
                 voters: peers.clone(),
                 ..Default::default()
@@ -242,9 +512,44 @@ where
             raft_log_store.put_conf_state(&cs).await.unwrap();
         };
 
-        let raft =
+        if let RaftStartMode::Bootstrap {
+            applied,
+            snapshot_term,
+            ref snapshot,
+            ..
+        } = options.raft_start_mode
+        {
+            raft_log_store
+                .seed_snapshot_boundary(applied, snapshot_term)
+                .await?;
+
+            let (notifier, notify_rx) = oneshot::channel();
+            options
+                .gear_command_tx
+                .send(GearCommand::InstallSnapshot {
+                    group: options.group,
+                    index: applied,
+                    snapshot: snapshot.clone(),
+                    notifier,
+                })
+                .await
+                .map_err(|_| GearError::SnapshotWorkerGone)?;
+            notify_rx.await.map_err(|_| GearError::SnapshotWorkerGone)?;
+        }
+
+        let mut raft =
             raft::RawNode::new(&raft_config, raft_log_store.clone(), &options.raft_logger).await?;
 
+        // A group with a single voter never has a rival to lose an election to, so there's no
+        // reason to make it wait out the usual randomized election timeout (driven by
+        // `Self::tick`) before it can accept proposals: campaign for it immediately. Harmless if
+        // this node is already leader (e.g. `Restart`): `raft`'s own `MsgHup` handling ignores a
+        // campaign while already leading.
+        let single_voter = peers.len() == 1;
+        if single_voter {
+            raft.campaign().await.map_err(Error::RaftError)?;
+        }
+
         let message_rx = options
             .raft_network
             .take_message_rx(options.raft_node)
@@ -263,19 +568,134 @@ where
 
             raft,
             raft_log_store,
-            _raft_network: options.raft_network,
+            raft_network: options.raft_network,
             raft_soft_state: None,
             raft_clients,
 
-            fsm: options.fsm,
+            clock: options.clock,
+            min_loop_duration: options.min_loop_duration,
+            tick_interval: if single_voter {
+                RAFT_SINGLE_VOTER_TICK_DURATION
+            } else {
+                RAFT_HEARTBEAT_TICK_DURATION
+            },
+            tick_jitter: options.tick_jitter,
+            tick_deadline: None,
+
+            gear_command_tx: options.gear_command_tx,
+
+            raft_node_msgs_scratch: HashMap::default(),
+            decoded_context_span_ids: HashMap::default(),
+            pending_apply: None,
 
             proposal_rx: options.proposal_rx,
+            control_rx: options.control_rx,
             message_rx,
 
-            metrics: RaftMetrics::new(options.node, options.group, options.raft_node),
+            metrics: RaftMetrics::new(
+                options.node,
+                options.group,
+                options.raft_node,
+                options.metrics_cardinality_aggregated,
+            ),
+            metrics_enabled: options.metrics_enabled,
+
+            snapshot_policy: options.snapshot_policy,
+            last_snapshot_index: applied,
+            applied_index: applied,
+
+            pending_leader_transfer: None,
         })
     }
 
+    /// Drains up to a batch of pending messages/proposals/controls, applies them, and services
+    /// any raft-internal ready work. Never awaits on an empty channel or a sleep. Shared by
+    /// [`Self::run_inner`]'s own loop and [`Self::poll_once`], the variant
+    /// [`crate::worker::raft_executor::MultiplexedRaftExecutor`] drives. Returns whether there
+    /// was anything to do.
+    async fn drain_and_process(&mut self) -> Result<bool> {
+        const BATCH_SIZE: usize = 128;
+        let mut msgs = Vec::with_capacity(BATCH_SIZE);
+        let mut proposals = Vec::with_capacity(BATCH_SIZE);
+        let mut controls = Vec::with_capacity(BATCH_SIZE);
+
+        let pool_channel_span = trace_span!("pool_channel_span");
+        let pool_channel_span_guard = pool_channel_span.enter();
+        let start_poll_channel = Instant::now();
+
+        for _ in 0..BATCH_SIZE {
+            match self.message_rx.try_recv() {
+                Ok(msg) => msgs.push(msg),
+                Err(mpsc::error::TryRecvError::Empty) => {}
+                Err(e) => return Err(Error::err(e)),
+            }
+
+            match self.proposal_rx.try_recv() {
+                Ok(proposal) => proposals.push(proposal),
+                Err(mpsc::error::TryRecvError::Empty) => {}
+                Err(e) => return Err(Error::err(e)),
+            }
+
+            match self.control_rx.try_recv() {
+                Ok(control) => controls.push(control),
+                Err(mpsc::error::TryRecvError::Empty) => {}
+                Err(e) => return Err(Error::err(e)),
+            }
+        }
+
+        self.metrics
+            .poll_channel_latency_histogram
+            .observe(start_poll_channel.elapsed().as_secs_f64());
+        drop(pool_channel_span_guard);
+
+        let was_idle = msgs.is_empty() && proposals.is_empty() && controls.is_empty();
+
+        for proposal in proposals {
+            self.propose_or_warn(proposal).await?;
+        }
+
+        for control in controls {
+            self.handle_control(control).await?;
+        }
+
+        for msg in msgs {
+            self.step(msg).await?;
+        }
+
+        let has_ready = self.raft.has_ready().await;
+        if has_ready {
+            self.handle_ready().await?;
+        }
+
+        Ok(!was_idle || has_ready)
+    }
+
+    /// Non-blocking unit of work for [`crate::worker::raft_executor::MultiplexedRaftExecutor`]:
+    /// calls [`Self::drain_and_process`] and fires the heartbeat tick if it's due, but never
+    /// blocks the way [`Self::run_inner`]'s own idle wait does, so a caller round-robining many
+    /// groups on one task can give this group a turn without getting stuck on it. Returns
+    /// whether there was anything to do, which the executor uses to decide whether this group
+    /// has earned another turn within its budget or should yield to the next group.
+    pub(crate) async fn poll_once(&mut self) -> Result<bool> {
+        let did_work = self.drain_and_process().await?;
+
+        let now = self.clock.now();
+        let tick_deadline = match self.tick_deadline {
+            Some(deadline) => deadline,
+            None => {
+                let deadline = now + self.next_tick_interval();
+                self.tick_deadline = Some(deadline);
+                deadline
+            }
+        };
+        if now >= tick_deadline {
+            self.tick().await;
+            self.tick_deadline = Some(now + self.next_tick_interval());
+        }
+
+        Ok(did_work)
+    }
+
     async fn run_inner(&mut self) -> Result<()> {
         // // [`Interval`] with default [`MissedTickBehavior::Brust`].
         // let mut ticker = tokio::time::interval(RAFT_HEARTBEAT_TICK_DURATION);
@@ -304,57 +724,51 @@ where
         //     }
         // }
 
-        const MIN_LOOP_DURATION: Duration = Duration::from_millis(10);
-        let mut remaining_timeout = RAFT_HEARTBEAT_TICK_DURATION;
+        let mut remaining_timeout = self.next_tick_interval();
         loop {
-            let now = Instant::now();
-
-            const BATCH_SIZE: usize = 128;
-            let mut msgs = Vec::with_capacity(BATCH_SIZE);
-            let mut proposals = Vec::with_capacity(BATCH_SIZE);
-
-            let pool_channel_span = trace_span!("pool_channel_span");
-            let pool_channel_span_guard = pool_channel_span.enter();
-            let start_poll_channel = Instant::now();
-
-            for _ in 0..BATCH_SIZE {
-                match self.message_rx.try_recv() {
-                    Ok(msg) => msgs.push(msg),
-                    Err(mpsc::error::TryRecvError::Empty) => {}
-                    Err(e) => return Err(Error::err(e)),
+            let now = self.clock.now();
+
+            let did_work = self.drain_and_process().await?;
+
+            // Rather than busy-polling again after `min_loop_duration`, block until a
+            // message/proposal/control actually shows up or the next heartbeat tick is due,
+            // whichever comes first. This is what keeps a wheel with hundreds of mostly-idle
+            // groups from waking every group every `min_loop_duration` for nothing.
+            let elapsed = if !did_work {
+                tokio::select! {
+                    biased;
+                    msg = self.message_rx.recv() => {
+                        match msg {
+                            Some(msg) => self.step(msg).await?,
+                            None => return Err(Error::err(anyhow::anyhow!("raft message channel closed"))),
+                        }
+                    }
+                    proposal = self.proposal_rx.recv() => {
+                        match proposal {
+                            Some(proposal) => self.propose_or_warn(proposal).await?,
+                            None => return Err(Error::err(anyhow::anyhow!("raft proposal channel closed"))),
+                        }
+                    }
+                    control = self.control_rx.recv() => {
+                        match control {
+                            Some(control) => self.handle_control(control).await?,
+                            None => return Err(Error::err(anyhow::anyhow!("raft control channel closed"))),
+                        }
+                    }
+                    _ = self.clock.sleep(remaining_timeout) => {}
                 }
-
-                match self.proposal_rx.try_recv() {
-                    Ok(proposal) => proposals.push(proposal),
-                    Err(mpsc::error::TryRecvError::Empty) => {}
-                    Err(e) => return Err(Error::err(e)),
+                self.clock.now() - now
+            } else {
+                let mut elapsed = self.clock.now() - now;
+                if elapsed < self.min_loop_duration {
+                    self.clock.sleep(self.min_loop_duration - elapsed).await;
+                    elapsed = self.clock.now() - now;
                 }
-            }
-
-            self.metrics
-                .poll_channel_latency_histogram
-                .observe(start_poll_channel.elapsed().as_secs_f64());
-            drop(pool_channel_span_guard);
-
-            for proposal in proposals {
-                self.propose(proposal).await?;
-            }
-
-            for msg in msgs {
-                self.step(msg).await?;
-            }
-
-            if self.raft.has_ready().await {
-                self.handle_ready().await?;
-            }
+                elapsed
+            };
 
-            let mut elapsed = now.elapsed();
-            if elapsed < MIN_LOOP_DURATION {
-                tokio::time::sleep(MIN_LOOP_DURATION - elapsed).await;
-                elapsed = now.elapsed();
-            }
             if elapsed >= remaining_timeout {
-                remaining_timeout = RAFT_HEARTBEAT_TICK_DURATION;
+                remaining_timeout = self.next_tick_interval();
                 self.tick().await;
             } else {
                 remaining_timeout -= elapsed;
@@ -362,6 +776,19 @@ where
         }
     }
 
+    /// [`Self::tick_interval`] plus a fresh random jitter up to [`Self::tick_jitter`], recomputed
+    /// every time a tick deadline is scheduled so this group's tick phase keeps drifting relative
+    /// to every other group's instead of settling into a fixed (and possibly still synchronized)
+    /// offset.
+    fn next_tick_interval(&self) -> Duration {
+        let jitter_millis = self.tick_jitter.as_millis() as u64;
+        if jitter_millis == 0 {
+            return self.tick_interval;
+        }
+        let jitter = Duration::from_millis(rand::thread_rng().gen_range(0..jitter_millis));
+        self.tick_interval + jitter
+    }
+
     // #[tracing::instrument(level = "trace")]
     async fn tick(&mut self) {
         self.raft.tick().await;
@@ -378,7 +805,25 @@ where
         self.raft
             .propose(proposal.context, proposal.data)
             .await
-            .map_err(Error::RaftError)
+            .map_err(|e| match e {
+                raft::Error::ProposalDropped => Error::ProposalDropped,
+                e => Error::RaftError(e),
+            })
+    }
+
+    /// Like [`Self::propose`], but treats [`Error::ProposalDropped`] as benign: the proposal never
+    /// committed under the old leadership, so there's nothing to recover from, unlike the errors
+    /// this worker's run loop does tear itself down and restart over. Warns and moves on instead
+    /// of propagating.
+    async fn propose_or_warn(&mut self, proposal: Proposal) -> Result<()> {
+        match self.propose(proposal).await {
+            Ok(()) => Ok(()),
+            Err(Error::ProposalDropped) => {
+                warn!("proposal dropped before committing, discarding");
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
     }
 
     #[tracing::instrument(level = "trace")]
@@ -386,9 +831,115 @@ where
         self.raft.step(msg).await.map_err(Error::RaftError)
     }
 
+    #[tracing::instrument(level = "trace")]
+    async fn handle_control(&mut self, control: RaftWorkerControl) -> Result<()> {
+        match control {
+            RaftWorkerControl::TransferLeader {
+                target_raft_node,
+                notify,
+            } => self.transfer_leader(target_raft_node, notify).await,
+            RaftWorkerControl::Campaign { notify } => self.campaign(notify).await,
+            RaftWorkerControl::StepDown { notify } => self.step_down(notify).await,
+            RaftWorkerControl::Status { notify } => self.status(notify).await,
+        }
+    }
+
+    /// Forces this node to (pre-)campaign for leadership, respecting the `pre_vote` setting
+    /// already configured on [`raft::Config`] when the node was built.
+    #[tracing::instrument(level = "trace")]
+    async fn campaign(&mut self, notify: oneshot::Sender<Result<()>>) -> Result<()> {
+        let result = self.raft.campaign().await.map_err(Error::RaftError);
+        let _ = notify.send(result);
+        Ok(())
+    }
+
+    /// Reports this group's current [`RaftStatus`]. `committed_index`/`last_index` are read from
+    /// `raft_log_store` (i.e. persisted state) rather than from `self.raft`'s in-memory state, to
+    /// avoid depending on the raft crate exposing that directly.
+    #[tracing::instrument(level = "trace")]
+    async fn status(&mut self, notify: oneshot::Sender<Result<RaftStatus>>) -> Result<()> {
+        let result = self.read_status().await;
+        let _ = notify.send(result);
+        Ok(())
+    }
+
+    async fn read_status(&self) -> Result<RaftStatus> {
+        let hard_state = self.raft_log_store.get_hard_state().await?.unwrap_or_default();
+        let last_index = self
+            .raft_log_store
+            .last_index()
+            .await
+            .map_err(Error::RaftError)?;
+        Ok(RaftStatus {
+            leader_id: self.raft_soft_state.as_ref().map_or(0, |ss| ss.leader_id),
+            term: hard_state.term,
+            committed_index: hard_state.commit,
+            applied_index: self.applied_index,
+            last_index,
+        })
+    }
+
+    /// Forces this node to step down to follower.
+    #[tracing::instrument(level = "trace")]
+    async fn step_down(&mut self, notify: oneshot::Sender<Result<()>>) -> Result<()> {
+        let result = self.raft.step_down().await.map_err(Error::RaftError);
+        let _ = notify.send(result);
+        Ok(())
+    }
+
+    /// Initiates a graceful leadership transfer to `target_raft_node`, rejecting targets that
+    /// aren't in the current voter set. `notify` is resolved by [`RaftWorker::handle_ready`] once
+    /// the transfer completes, i.e. once `raft_soft_state.leader_id` becomes `target_raft_node`.
+    #[tracing::instrument(level = "trace")]
+    async fn transfer_leader(
+        &mut self,
+        target_raft_node: u64,
+        notify: oneshot::Sender<Result<()>>,
+    ) -> Result<()> {
+        let cs = self
+            .raft_log_store
+            .get_conf_state()
+            .await?
+            .unwrap_or_default();
+        if !cs.voters.contains(&target_raft_node) {
+            let _ = notify.send(Err(Error::Other(format!(
+                "raft node {} is not a voter of group {}",
+                target_raft_node, self.group
+            ))));
+            return Ok(());
+        }
+
+        if let Some((_, prev_notify)) = self.pending_leader_transfer.take() {
+            let _ = prev_notify.send(Err(Error::Other(
+                "superseded by a newer leader transfer request".to_string(),
+            )));
+        }
+
+        self.raft.transfer_leader(target_raft_node).await;
+        self.pending_leader_transfer = Some((target_raft_node, notify));
+        Ok(())
+    }
+
+    /// Resolves a pending [`RaftWorkerControl::TransferLeader`] request once the soft state shows
+    /// the transfer target has become leader.
+    fn maybe_complete_leader_transfer(&mut self) {
+        let leader_id = match &self.raft_soft_state {
+            Some(ss) => ss.leader_id,
+            None => return,
+        };
+        if matches!(&self.pending_leader_transfer, Some((target, _)) if *target == leader_id) {
+            let (_, notify) = self.pending_leader_transfer.take().unwrap();
+            let _ = notify.send(Ok(()));
+        }
+    }
+
     #[tracing::instrument(level = "trace")]
     async fn handle_ready(&mut self) -> Result<()> {
-        let start = Instant::now();
+        let start = self.metrics_enabled.then(Instant::now);
+
+        // Entries decoded by `send_messages`/`append_log_entries` below are only ever reused
+        // within the same ready cycle, so the cache is reset here rather than carried forward.
+        self.decoded_context_span_ids.clear();
 
         let mut ready = self.raft.ready().await;
 
@@ -398,6 +949,7 @@ where
                 leader_id: ss.leader_id,
                 raft_state: ss.raft_state,
             });
+            self.maybe_complete_leader_transfer();
         }
 
         // 1. Send messages.
@@ -433,13 +985,52 @@ where
         self.apply_log_entries(ready.take_committed_entries())
             .await?;
 
-        self.metrics
-            .handle_ready_latency_histogram
-            .observe(start.elapsed().as_secs_f64());
+        // Send whatever step 3/9 coalesced into `pending_apply` rather than leaving it for the
+        // next `handle_ready` call, which may be a full `min_loop_duration` away.
+        self.flush_pending_apply().await?;
+
+        if let Some(start) = start {
+            self.metrics
+                .handle_ready_latency_histogram
+                .observe(start.elapsed().as_secs_f64());
+        }
 
         Ok(())
     }
 
+    /// Returns `entry.context`'s decoded `Context::span_id`, consulting and populating
+    /// [`Self::decoded_context_span_ids`] so the same entry never pays for `bincode::deserialize`
+    /// twice within one `handle_ready` call.
+    fn entry_span_id(&mut self, entry: &raft::prelude::Entry) -> Result<u64> {
+        if let Some(span_id) = self.decoded_context_span_ids.get(&entry.index) {
+            return Ok(*span_id);
+        }
+        let ctx: Context = bincode::deserialize(&entry.context).map_err(Error::serde_err)?;
+        self.decoded_context_span_ids.insert(entry.index, ctx.span_id);
+        Ok(ctx.span_id)
+    }
+
+    /// Sends `msgs` via `client`, retrying up to [`SEND_MAX_RETRIES`] times with exponential
+    /// backoff before giving up. A dedicated `async fn` (rather than inlined in `send_messages`)
+    /// so it can be driven per peer without holding `&mut self` across the retry loop.
+    async fn send_with_retry(
+        mut client: RN::RaftClient,
+        msgs: &[raft::prelude::Message],
+    ) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            match client.send(msgs.to_vec()).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < SEND_MAX_RETRIES => {
+                    warn!("send to peer failed, retrying: {}", e);
+                    tokio::time::sleep(SEND_RETRY_BASE_BACKOFF * 2u32.pow(attempt as u32)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
     #[tracing::instrument(level = "trace")]
     async fn send_messages(&mut self, messages: Vec<raft::prelude::Message>) -> Result<()> {
         if messages.is_empty() {
@@ -454,44 +1045,77 @@ where
                         if entry.entry_type() == raft::prelude::EntryType::EntryNormal
                             && !entry.data.is_empty()
                         {
-                            let ctx: Context =
-                                bincode::deserialize(&entry.context).map_err(Error::serde_err)?;
-                            span.follows_from(tracing::Id::from_u64(ctx.span_id));
+                            let span_id = self.entry_span_id(entry)?;
+                            span.follows_from(tracing::Id::from_u64(span_id));
                         }
                     }
                 }
             }
         }
 
-        let start = Instant::now();
+        let start = self.metrics_enabled.then(Instant::now);
 
         let mut bytes = 0;
 
-        let mut raft_node_msgs = HashMap::new();
+        // Reuse the scratch map/vecs from the previous call instead of allocating fresh ones:
+        // `clear()` drops each bucket's contents but keeps its capacity, so steady-state traffic
+        // to the same peers settles into zero allocations here.
+        for msgs in self.raft_node_msgs_scratch.values_mut() {
+            msgs.clear();
+        }
         for msg in messages {
-            bytes += msg.encoded_len();
+            if self.metrics_enabled {
+                bytes += msg.encoded_len();
+            }
             let to = msg.to;
-            raft_node_msgs
+            self.raft_node_msgs_scratch
                 .entry(to)
                 .or_insert_with(|| Vec::with_capacity(16))
                 .push(msg);
         }
-        let futures = raft_node_msgs
-            .into_iter()
-            .map(|(raft_node, msgs)| {
-                let mut client = self.raft_clients.get(&raft_node).unwrap().clone();
-                async move { client.send(msgs).await }
+        let node = self.node;
+        let group = self.group;
+        let raft_node = self.raft_node;
+        let raft_clients = &self.raft_clients;
+        let futures = self
+            .raft_node_msgs_scratch
+            .iter()
+            .filter(|(_, msgs)| !msgs.is_empty())
+            .map(|(&peer, msgs)| {
+                let client = raft_clients.get(&peer).unwrap().clone();
+                async move {
+                    let count = msgs.len();
+                    // A single unreachable peer must not abort sends to the other, healthy
+                    // peers, so failures here are isolated and logged/counted rather than
+                    // propagated: raft's own protocol will drive a retry on the next ready cycle
+                    // regardless, this just avoids tearing down the whole worker loop over it.
+                    if let Err(e) = Self::send_with_retry(client, msgs).await {
+                        warn!(
+                            "dropping {} raft message(s) to peer {} after exhausting retries: {}",
+                            count, peer, e
+                        );
+                        RAFT_DROPPED_MESSAGES_COUNTER_VEC
+                            .with_label_values(&[
+                                &node.to_string(),
+                                &group.to_string(),
+                                &raft_node.to_string(),
+                                &peer.to_string(),
+                            ])
+                            .inc_by(count as u64);
+                    }
+                }
             })
             .collect_vec();
-        future::try_join_all(futures).await?;
+        future::join_all(futures).await;
 
-        let elapsed = start.elapsed();
-        self.metrics
-            .send_messages_latency_histogram
-            .observe(elapsed.as_secs_f64());
-        self.metrics
-            .send_messages_throughput_gauge
-            .add(bytes as f64);
+        if let Some(start) = start {
+            self.metrics
+                .send_messages_latency_histogram
+                .observe(start.elapsed().as_secs_f64());
+            self.metrics
+                .send_messages_throughput_gauge
+                .add(bytes as f64);
+        }
         Ok(())
     }
 
@@ -499,26 +1123,158 @@ where
     async fn apply_snapshot(&mut self, snapshot: &raft::prelude::Snapshot) -> Result<()> {
         // Impl me!!!
         // Impl me!!!
-        // Impl me!!!
+        // Impl me!!! (restoring FSM/log state from `snapshot.data`)
+        //
+        // `raft_clients` must stay in sync with the snapshot's `ConfState` regardless of the
+        // above: membership may have changed while this node was far enough behind to need a
+        // snapshot, and a stale client map would silently black-hole messages to/from peers added
+        // or removed since.
+        let cs = snapshot
+            .metadata
+            .clone()
+            .unwrap_or_default()
+            .conf_state
+            .unwrap_or_default();
+        self.raft_log_store.put_conf_state(&cs).await?;
+        self.reconcile_raft_clients(&cs).await?;
         todo!()
     }
 
+    /// Brings `raft_clients` in line with `cs`: creates clients for any voter/learner not already
+    /// present, and drops clients for peers no longer in either role. Shared by
+    /// [`Self::apply_snapshot`] and, eventually, conf-change handling once that exists.
+    async fn reconcile_raft_clients(&mut self, cs: &raft::prelude::ConfState) -> Result<()> {
+        let wanted: HashSet<u64> = cs
+            .voters
+            .iter()
+            .chain(cs.learners.iter())
+            .copied()
+            .collect();
+
+        for peer in wanted.iter().copied() {
+            if let Entry::Vacant(entry) = self.raft_clients.entry(peer) {
+                entry.insert(self.raft_network.client(peer).await?);
+            }
+        }
+        self.raft_clients.retain(|peer, _| wanted.contains(peer));
+
+        Ok(())
+    }
+
     #[tracing::instrument(level = "trace")]
     async fn apply_log_entries(&mut self, entries: Vec<raft::prelude::Entry>) -> Result<()> {
+        let (first, last) = match (entries.first(), entries.last()) {
+            (Some(first), Some(last)) => (first.index, last.index),
+            _ => return Ok(()),
+        };
+
         let is_leader = match &self.raft_soft_state {
             None => false,
             Some(ss) => ss.raft_state == raft::StateRole::Leader,
         };
 
-        let start = Instant::now();
+        if self.metrics_enabled {
+            self.observe_commit_latency(&entries);
+        }
+
+        // Stage the range rather than sending it immediately: `handle_ready` calls this twice per
+        // cycle (once for the pre-advance ready, once for the light ready after `advance`), and
+        // those two ranges are almost always adjacent. Coalescing them into one `Apply` halves the
+        // wakeups `Gear` pays for under bursty commit, without changing what gets applied or in
+        // what order. `Self::flush_pending_apply` is guaranteed to run before `handle_ready`
+        // returns, so nothing is left unsent.
+        match &mut self.pending_apply {
+            Some((range, pending_is_leader)) if *pending_is_leader == is_leader && range.end == first => {
+                range.end = last + 1;
+            }
+            Some(_) => {
+                self.flush_pending_apply().await?;
+                self.pending_apply = Some((first..(last + 1), is_leader));
+            }
+            None => {
+                self.pending_apply = Some((first..(last + 1), is_leader));
+            }
+        }
 
-        self.fsm.apply(self.group, is_leader, entries).await?;
+        self.applied_index = last;
+        self.maybe_trigger_snapshot(last).await?;
+        Ok(())
+    }
 
-        let elapsed = start.elapsed();
+    /// Sends whatever range [`Self::apply_log_entries`] has coalesced so far to `Gear`, if any.
+    /// Called once per call site that staged a range so no coalesced range is left unsent: at the
+    /// end of [`Self::handle_ready`], and whenever a newly staged range turns out not to be
+    /// adjacent to the one already pending.
+    async fn flush_pending_apply(&mut self) -> Result<()> {
+        let (range, is_leader) = match self.pending_apply.take() {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
 
+        // Hand the range off to `Gear` instead of applying it inline. `gear_command_tx` is
+        // bounded, so once `Gear` falls behind, this `send` blocks and naturally throttles the
+        // ready loop instead of letting committed-but-unapplied entries pile up in memory.
+        let start = Instant::now();
+        self.gear_command_tx
+            .send(GearCommand::Apply {
+                group: self.group,
+                range,
+                is_leader,
+            })
+            .await
+            .map_err(|_| GearError::ApplyConsumerGone)?;
         self.metrics
-            .apply_log_entries_latency_histogram
-            .observe(elapsed.as_secs_f64());
+            .apply_enqueue_latency_histogram
+            .observe(start.elapsed().as_secs_f64());
+
+        Ok(())
+    }
+
+    /// Observes end-to-end propose-to-commit latency for entries that carry a decodable
+    /// [`Context`] with a non-zero `propose_at`. Entries without one (e.g. raft-internal no-ops,
+    /// or test fixtures that don't encode a real `Context`) are silently skipped rather than
+    /// treated as an error, since not every entry on this path is a user proposal.
+    fn observe_commit_latency(&self, entries: &[raft::prelude::Entry]) {
+        let now = now_millis();
+        for entry in entries {
+            if entry.entry_type() != raft::prelude::EntryType::EntryNormal || entry.context.is_empty() {
+                continue;
+            }
+            let ctx = match Context::decode(&entry.context) {
+                Ok(ctx) => ctx,
+                Err(_) => continue,
+            };
+            if ctx.propose_at == 0 || ctx.propose_at > now {
+                continue;
+            }
+            self.metrics
+                .commit_latency_histogram
+                .observe((now - ctx.propose_at) as f64 / 1000.0);
+        }
+    }
+
+    /// Checks the configured [`SnapshotPolicy`] and, if it's time, compacts the raft log up to
+    /// `applied_index`.
+    ///
+    /// Note: this only trims the log; it doesn't build or install an FSM snapshot (see the
+    /// `apply_snapshot` TODO above), so `log_gap_threshold` must stay `0` until that exists. It
+    /// also runs right after the range is *enqueued* to `Gear`, not once `Gear` confirms it's
+    /// actually been applied, which is one more reason triggering must stay disabled for now.
+    #[tracing::instrument(level = "trace")]
+    async fn maybe_trigger_snapshot(&mut self, applied_index: u64) -> Result<()> {
+        if !self
+            .snapshot_policy
+            .should_trigger(self.last_snapshot_index, applied_index)
+        {
+            return Ok(());
+        }
+        trace!(
+            "snapshot policy triggered at applied index {} (last snapshot index {})",
+            applied_index,
+            self.last_snapshot_index,
+        );
+        self.raft_log_store.compact(applied_index).await?;
+        self.last_snapshot_index = applied_index;
         Ok(())
     }
 
@@ -528,17 +1284,26 @@ where
             return Ok(());
         }
 
-        let start = Instant::now();
+        let start = self.metrics_enabled.then(Instant::now);
         let mut bytes = 0;
-        let mut builder = RaftLogBatchBuilder::default();
+        let mut builder = self.raft_log_store.batch_builder();
         for entry in entries {
-            if cfg!(feature = "tracing") && let raft::prelude::EntryType::EntryNormal = entry.entry_type() && !entry.data.is_empty() {
-                let span = tracing::Span::current();
-                let ctx: Context = bincode::deserialize(&entry.context).map_err(Error::serde_err)?;
-                span.follows_from(tracing::Id::from_u64(ctx.span_id));
+            if cfg!(feature = "tracing")
+                && matches!(
+                    entry.entry_type(),
+                    raft::prelude::EntryType::EntryNormal
+                        | raft::prelude::EntryType::EntryConfChange
+                        | raft::prelude::EntryType::EntryConfChangeV2
+                )
+                && !entry.data.is_empty()
+            {
+                let span_id = self.entry_span_id(&entry)?;
+                tracing::Span::current().follows_from(tracing::Id::from_u64(span_id));
             }
 
-            bytes += entry.encoded_len();
+            if self.metrics_enabled {
+                bytes += entry.encoded_len();
+            }
             let data = encode_entry_data(&entry);
             builder.add(
                 self.raft_node,
@@ -555,13 +1320,14 @@ where
             batches
         );
         self.raft_log_store.append(batches).await?;
-        let elapsed = start.elapsed();
-        self.metrics
-            .append_log_entries_latency_histogram
-            .observe(elapsed.as_secs_f64());
-        self.metrics
-            .append_log_entries_throughput_gauge
-            .add(bytes as f64);
+        if let Some(start) = start {
+            self.metrics
+                .append_log_entries_latency_histogram
+                .observe(start.elapsed().as_secs_f64());
+            self.metrics
+                .append_log_entries_throughput_gauge
+                .add(bytes as f64);
+        }
         Ok(())
     }
 
@@ -573,9 +1339,10 @@ where
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
 
     use std::collections::BTreeMap;
+    use std::sync::Arc;
 
     use assert_matches::assert_matches;
     use runkv_common::tracing_slog_drain::TracingSlogDrain;
@@ -584,96 +1351,2533 @@ mod tests {
     use runkv_storage::raft_log_store::store::RaftLogStoreOptions;
     use runkv_storage::raft_log_store::RaftLogStore;
     use test_log::test;
+    use tokio::sync::Mutex;
+    use tokio::task::JoinHandle;
 
     use super::*;
+    use crate::components::clock::{MockClock, RealClock};
+    use crate::components::command::{BatchProposal, Command};
     use crate::components::fsm::tests::MockFsm;
-    use crate::components::raft_network::tests::MockRaftNetwork;
+    use crate::components::fsm::Fsm;
+    use crate::components::raft_network::tests::{MockRaftClient, MockRaftNetwork};
+    use crate::worker::gear::{Gear, GearOptions, NoopGearHook};
 
-    #[test(tokio::test)]
-    async fn test_raft_basic() {
-        let tempdir = tempfile::tempdir().unwrap();
-        let path = tempdir.path().to_str().unwrap();
-        let raft_logger = build_raft_logger();
-        let raft_log_store = build_raft_log_store(path).await;
-        raft_log_store.add_group(1).await.unwrap();
-        raft_log_store.add_group(2).await.unwrap();
-        raft_log_store.add_group(3).await.unwrap();
-        let raft_network = MockRaftNetwork::default();
-        raft_network
+    /// Builds a [`Gear`] wrapping `fsm`, spawns it, and returns the [`GearCommand`] sender to wire
+    /// into [`RaftWorkerOptions::gear_command_tx`].
+    pub(crate) fn spawn_gear<F: Fsm>(
+        node: u64,
+        group: u64,
+        raft_node: u64,
+        raft_log_store: RaftGroupLogStore,
+        fsm: F,
+        bound: usize,
+    ) -> mpsc::Sender<GearCommand> {
+        let (command_tx, command_rx) = mpsc::channel(bound);
+        let mut gear = Gear::new(GearOptions {
+            node,
+            group,
+            raft_node,
+            raft_log_store,
+            fsm,
+            hook: std::sync::Arc::new(NoopGearHook),
+            command_rx,
+            snapshot_build_limiter: std::sync::Arc::new(tokio::sync::Semaphore::new(
+                crate::components::raft_manager::DEFAULT_MAX_CONCURRENT_SNAPSHOT_BUILDS,
+            )),
+        });
+        tokio::spawn(async move {
+            let _ = gear.run().await;
+        });
+        command_tx
+    }
+
+    /// Spawns a task that keeps advancing `clock` one [`RAFT_HEARTBEAT_TICK_DURATION`] at a time,
+    /// sleeping 1ms of real time between each so the worker tasks blocked in [`Clock::sleep`] get
+    /// to run. A single big leap wouldn't do: `RaftWorker::run_inner` fires at most one `tick()`
+    /// per iteration no matter how far the clock jumped, so election/heartbeat timing (which
+    /// counts ticks, not elapsed time) needs many small advances, not one large one. Callers
+    /// should `abort()` the returned handle once the test no longer needs virtual time to move.
+    fn spawn_clock_driver(clock: &MockClock) -> JoinHandle<()> {
+        let clock = clock.clone();
+        tokio::spawn(async move {
+            loop {
+                clock.advance(RAFT_HEARTBEAT_TICK_DURATION);
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+    }
+
+    #[test(tokio::test)]
+    async fn test_raft_basic() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        raft_log_store.add_group(2).await.unwrap();
+        raft_log_store.add_group(3).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10), (3, 10)]))
+            .await
+            .unwrap();
+
+        // Shared by all three workers so the one clock driver below paces their ready loops in
+        // lockstep, the way they'd naturally stay in lockstep against the real wall clock.
+        let clock = MockClock::new();
+
+        macro_rules! worker {
+            ($id:expr) => {
+                build_raft_worker(
+                    100,
+                    10,
+                    $id,
+                    vec![1, 2, 3],
+                    RaftGroupLogStore::new($id, raft_log_store.clone()),
+                    raft_logger.clone(),
+                    raft_network.clone(),
+                    Arc::new(clock.clone()) as ClockRef,
+                )
+                .await
+            };
+        }
+
+        let (proposal_tx_1, _control_tx_1, _handle_1, mut apply_rx_1) = worker!(1);
+        let (_proposal_tx_2, _control_tx_2, _handle_2, mut apply_rx_2) = worker!(2);
+        let (_proposal_tx_3, _control_tx_3, _handle_3, mut apply_rx_3) = worker!(3);
+
+        // Keeps virtual time moving for the rest of the test, driving pre-vote, election, and the
+        // proposal below to completion without a real 10s sleep.
+        let clock_driver = spawn_clock_driver(&clock);
+
+        // Node 1 is elected leader first in this mock network; give the driver above enough real
+        // time to advance virtual time far past the election timeout before proposing to it.
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let data = vec![b'd'; 16];
+        let context = vec![b'c'; 16];
+
+        proposal_tx_1
+            .send(Proposal {
+                data: data.clone(),
+                context: context.clone(),
+            })
+            .unwrap();
+
+        loop {
+            let entry = tokio::select! {
+                entry = apply_rx_1.recv() => entry,
+                entry = apply_rx_2.recv() => entry,
+                entry = apply_rx_3.recv() => entry,
+            };
+            let entry = entry.unwrap();
+            if entry.entry_type() != raft::prelude::EntryType::EntryNormal || entry.data.is_empty()
+            {
+                continue;
+            }
+            assert_matches!(entry, raft::prelude::Entry {
+                data: edata,
+                context: econtext,
+                ..
+            } => {
+                assert_eq!(edata, data);
+                assert_eq!(econtext, context);
+            });
+            break;
+        }
+
+        clock_driver.abort();
+    }
+
+    #[test(tokio::test)]
+    async fn test_propose_without_a_leader_yields_retryable_proposal_dropped() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10)]))
+            .await
+            .unwrap();
+
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+        let (fsm, _apply_rx) = MockFsm::new(true);
+        let gear_command_tx = spawn_gear(
+            10,
+            100,
+            1,
+            group_raft_log_store.clone(),
+            fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let (_proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (_control_tx, control_rx) = mpsc::unbounded_channel();
+        let options = RaftWorkerOptions {
+            group: 100,
+            node: 10,
+            raft_node: 1,
+            // Two voters, and this worker is built standalone (no `run()` loop driving ticks or
+            // ready cycles), so no election ever happens: this node stays a leaderless follower,
+            // exactly the state a proposal must be dropped in.
+            raft_start_mode: RaftStartMode::Initialize { peers: vec![1, 2] },
+            raft_log_store: group_raft_log_store,
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock),
+            proposal_rx,
+            control_rx,
+            gear_command_tx,
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: false,
+        };
+        let mut worker = RaftWorker::build(options).await.unwrap();
+
+        let result = worker
+            .propose(Proposal {
+                data: vec![b'd'; 16],
+                context: vec![],
+            })
+            .await;
+
+        assert_matches!(result, Err(Error::ProposalDropped));
+    }
+
+    #[test(tokio::test)]
+    async fn test_propose_or_warn_swallows_proposal_dropped() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10)]))
+            .await
+            .unwrap();
+
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+        let (fsm, _apply_rx) = MockFsm::new(true);
+        let gear_command_tx = spawn_gear(
+            10,
+            100,
+            1,
+            group_raft_log_store.clone(),
+            fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let (_proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (_control_tx, control_rx) = mpsc::unbounded_channel();
+        let options = RaftWorkerOptions {
+            group: 100,
+            node: 10,
+            raft_node: 1,
+            // Two voters, and this worker is built standalone (no `run()` loop driving ticks or
+            // ready cycles), so no election ever happens: this node stays a leaderless follower,
+            // exactly the state a proposal must be dropped in.
+            raft_start_mode: RaftStartMode::Initialize { peers: vec![1, 2] },
+            raft_log_store: group_raft_log_store,
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock),
+            proposal_rx,
+            control_rx,
+            gear_command_tx,
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: false,
+        };
+        let mut worker = RaftWorker::build(options).await.unwrap();
+
+        // Unlike `propose`, which surfaces `Error::ProposalDropped` to its caller,
+        // `propose_or_warn` (what the run loop actually calls) treats it as benign so a single
+        // dropped proposal never tears down and restarts the whole worker loop.
+        let result = worker
+            .propose_or_warn(Proposal {
+                data: vec![b'd'; 16],
+                context: vec![],
+            })
+            .await;
+
+        assert_matches!(result, Ok(()));
+    }
+
+    #[test(tokio::test)]
+    async fn test_bootstrap_from_snapshot_commits_subsequent_proposals() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+
+        let raft_network = MockRaftNetwork::default();
+
+        // A donor group (raft node 9) standing in for the "large, long-running group" a new node
+        // would otherwise have to replay in full. Give it some committed history before anyone
+        // bootstraps off of it.
+        raft_log_store.add_group(9).await.unwrap();
+        raft_network
+            .register(200, BTreeMap::from_iter([(9, 10)]))
+            .await
+            .unwrap();
+        let (donor_proposal_tx, donor_control_tx, _donor_handle, mut donor_apply_rx) =
+            build_raft_worker(
+                200,
+                10,
+                9,
+                vec![9],
+                RaftGroupLogStore::new(9, raft_log_store.clone()),
+                raft_logger.clone(),
+                raft_network.clone(),
+                Arc::new(RealClock) as ClockRef,
+            )
+            .await;
+
+        // The single-member donor group already won its election in `RaftWorker::build`, before
+        // it ever ticks; this gives its ready loop time to settle.
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        const DONOR_ENTRIES: u64 = 3;
+        for i in 0..DONOR_ENTRIES {
+            donor_proposal_tx
+                .send(Proposal {
+                    data: vec![i as u8; 16],
+                    context: vec![],
+                })
+                .unwrap();
+        }
+        let mut applied = 0;
+        while applied < DONOR_ENTRIES {
+            let entry = donor_apply_rx.recv().await.unwrap();
+            if entry.entry_type() != raft::prelude::EntryType::EntryNormal || entry.data.is_empty()
+            {
+                continue;
+            }
+            applied += 1;
+        }
+
+        let (notify_tx, notify_rx) = tokio::sync::oneshot::channel();
+        donor_control_tx
+            .send(RaftWorkerControl::Status { notify: notify_tx })
+            .unwrap();
+        let donor_status = notify_rx.await.unwrap().unwrap();
+        let snapshot_index = donor_status.applied_index;
+
+        // The term at `snapshot_index`, read straight off the donor's own log, is what a real
+        // snapshot's metadata would carry; the bootstrapping node needs it to seed a log boundary
+        // raft itself would recognize as consistent.
+        let snapshot_term = RaftGroupLogStore::new(9, raft_log_store.clone())
+            .term(snapshot_index)
+            .await
+            .unwrap();
+
+        // Pulls the snapshot out-of-band from the donor's own `Gear`, the way a real bootstrap
+        // would fetch one from an existing peer before ever starting raft.
+        let (donor_fsm, _donor_snapshot_apply_rx) = MockFsm::new(true);
+        let donor_gear_command_tx = spawn_gear(
+            10,
+            200,
+            9,
+            RaftGroupLogStore::new(9, raft_log_store.clone()),
+            donor_fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let (snapshot_notify_tx, snapshot_notify_rx) = tokio::sync::oneshot::channel();
+        donor_gear_command_tx
+            .send(GearCommand::BuildSnapshot {
+                group: 200,
+                index: snapshot_index,
+                notifier: snapshot_notify_tx,
+            })
+            .await
+            .unwrap();
+        let snapshot = snapshot_notify_rx.await.unwrap();
+
+        // Bootstraps a fresh node (raft node 1, never used before) straight from that snapshot.
+        raft_log_store.add_group(1).await.unwrap();
+        raft_network
+            .register(300, BTreeMap::from_iter([(1, 10)]))
+            .await
+            .unwrap();
+        let bootstrap_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+        let (bootstrap_fsm, mut bootstrap_apply_rx) = MockFsm::new(true);
+        let bootstrap_gear_command_tx = spawn_gear(
+            10,
+            300,
+            1,
+            bootstrap_raft_log_store.clone(),
+            bootstrap_fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let (bootstrap_proposal_tx, bootstrap_proposal_rx) = mpsc::unbounded_channel();
+        let (_bootstrap_control_tx, bootstrap_control_rx) = mpsc::unbounded_channel();
+        let options = RaftWorkerOptions {
+            group: 300,
+            node: 10,
+            raft_node: 1,
+            raft_start_mode: RaftStartMode::Bootstrap {
+                peers: vec![1],
+                applied: snapshot_index,
+                snapshot_term,
+                snapshot,
+            },
+            raft_log_store: bootstrap_raft_log_store,
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock),
+            proposal_rx: bootstrap_proposal_rx,
+            control_rx: bootstrap_control_rx,
+            gear_command_tx: bootstrap_gear_command_tx,
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: false,
+        };
+        let mut bootstrap_worker = RaftWorker::build(options).await.unwrap();
+        tokio::spawn(async move {
+            let _ = bootstrap_worker.run().await;
+        });
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        let data = vec![b'x'; 16];
+        bootstrap_proposal_tx
+            .send(Proposal {
+                data: data.clone(),
+                context: vec![],
+            })
+            .unwrap();
+
+        let entry = loop {
+            let entry = bootstrap_apply_rx.recv().await.unwrap();
+            if entry.entry_type() != raft::prelude::EntryType::EntryNormal || entry.data.is_empty()
+            {
+                continue;
+            }
+            break entry;
+        };
+        assert_eq!(entry.data, data);
+        // Started from the snapshot boundary, not from scratch: the first entry this bootstrapped
+        // node ever commits is the one right after the snapshot it was seeded with.
+        assert_eq!(entry.index, snapshot_index + 1);
+    }
+
+    #[test(tokio::test)]
+    async fn test_batch_proposal_commits_as_one_entry_and_applies_atomically() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10)]))
+            .await
+            .unwrap();
+
+        let (proposal_tx, _control_tx, _handle, mut apply_rx) = build_raft_worker(
+            100,
+            10,
+            1,
+            vec![1],
+            RaftGroupLogStore::new(1, raft_log_store.clone()),
+            raft_logger,
+            raft_network,
+            Arc::new(RealClock) as ClockRef,
+        )
+        .await;
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        const KEYS: u64 = 100;
+        let commands = (0..KEYS)
+            .map(|i| Command::CompactRaftLog {
+                index: i,
+                sequence: i,
+            })
+            .collect_vec();
+        let proposal = BatchProposal {
+            commands,
+            context: Context {
+                span_id: 0,
+                request_id: 1,
+                propose_at: 0,
+            },
+        }
+        .encode()
+        .unwrap();
+        proposal_tx.send(proposal).unwrap();
+
+        let entry = loop {
+            let entry = apply_rx.recv().await.unwrap();
+            if entry.entry_type() == raft::prelude::EntryType::EntryNormal && !entry.data.is_empty()
+            {
+                break entry;
+            }
+        };
+
+        // All 100 commands must have committed as part of this single raft log entry.
+        let decoded = Command::decode(&entry.data).unwrap();
+        assert_matches!(decoded, Command::Batch(commands) if commands.len() == KEYS as usize);
+
+        // No other entry should follow for this proposal: the whole batch is one commit.
+        let next = tokio::time::timeout(Duration::from_millis(500), apply_rx.recv()).await;
+        assert!(
+            next.is_err(),
+            "batch must not produce more than one committed entry"
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_transfer_leader() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        raft_log_store.add_group(2).await.unwrap();
+        raft_log_store.add_group(3).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10), (3, 10)]))
+            .await
+            .unwrap();
+
+        macro_rules! worker {
+            ($id:expr) => {
+                build_raft_worker(
+                    100,
+                    10,
+                    $id,
+                    vec![1, 2, 3],
+                    RaftGroupLogStore::new($id, raft_log_store.clone()),
+                    raft_logger.clone(),
+                    raft_network.clone(),
+                    Arc::new(RealClock) as ClockRef,
+                )
+                .await
+            };
+        }
+
+        let (_proposal_tx_1, control_tx_1, _handle_1, mut apply_rx_1) = worker!(1);
+        let (proposal_tx_2, _control_tx_2, _handle_2, mut apply_rx_2) = worker!(2);
+        let (_proposal_tx_3, _control_tx_3, _handle_3, mut apply_rx_3) = worker!(3);
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        // Rejects a transfer target that isn't a voter of the group.
+        let (notify_tx, notify_rx) = tokio::sync::oneshot::channel();
+        control_tx_1
+            .send(RaftWorkerControl::TransferLeader {
+                target_raft_node: 99,
+                notify: notify_tx,
+            })
+            .unwrap();
+        assert!(notify_rx.await.unwrap().is_err());
+
+        // Node 1 is elected leader first in this mock network. Transfer leadership to node 2.
+        let (notify_tx, notify_rx) = tokio::sync::oneshot::channel();
+        control_tx_1
+            .send(RaftWorkerControl::TransferLeader {
+                target_raft_node: 2,
+                notify: notify_tx,
+            })
+            .unwrap();
+        notify_rx.await.unwrap().unwrap();
+
+        // Node 2 is the new leader and can commit proposals.
+        let data = vec![b'd'; 16];
+        let context = vec![b'c'; 16];
+        proposal_tx_2
+            .send(Proposal {
+                data: data.clone(),
+                context: context.clone(),
+            })
+            .unwrap();
+
+        loop {
+            let entry = tokio::select! {
+                entry = apply_rx_1.recv() => entry,
+                entry = apply_rx_2.recv() => entry,
+                entry = apply_rx_3.recv() => entry,
+            };
+            let entry = entry.unwrap();
+            if entry.entry_type() != raft::prelude::EntryType::EntryNormal || entry.data.is_empty()
+            {
+                continue;
+            }
+            assert_matches!(entry, raft::prelude::Entry {
+                data: edata,
+                context: econtext,
+                ..
+            } => {
+                assert_eq!(edata, data);
+                assert_eq!(econtext, context);
+            });
+            break;
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn test_campaign_wins_after_leader_stops() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        raft_log_store.add_group(2).await.unwrap();
+        raft_log_store.add_group(3).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10), (3, 10)]))
+            .await
+            .unwrap();
+
+        macro_rules! worker {
+            ($id:expr) => {
+                build_raft_worker(
+                    100,
+                    10,
+                    $id,
+                    vec![1, 2, 3],
+                    RaftGroupLogStore::new($id, raft_log_store.clone()),
+                    raft_logger.clone(),
+                    raft_network.clone(),
+                    Arc::new(RealClock) as ClockRef,
+                )
+                .await
+            };
+        }
+
+        let (_proposal_tx_1, _control_tx_1, handle_1, _apply_rx_1) = worker!(1);
+        let (proposal_tx_2, control_tx_2, _handle_2, mut apply_rx_2) = worker!(2);
+        let (_proposal_tx_3, _control_tx_3, _handle_3, mut apply_rx_3) = worker!(3);
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        // Node 1 is elected leader first in this mock network. Stop it, as if an operator had
+        // isolated a bad leader, then force node 2 to campaign for the now-vacant leadership.
+        handle_1.abort();
+
+        let (notify_tx, notify_rx) = tokio::sync::oneshot::channel();
+        control_tx_2
+            .send(RaftWorkerControl::Campaign { notify: notify_tx })
+            .unwrap();
+        notify_rx.await.unwrap().unwrap();
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        // Node 2 won the election and can commit proposals.
+        let data = vec![b'd'; 16];
+        let context = vec![b'c'; 16];
+        proposal_tx_2
+            .send(Proposal {
+                data: data.clone(),
+                context: context.clone(),
+            })
+            .unwrap();
+
+        loop {
+            let entry = tokio::select! {
+                entry = apply_rx_2.recv() => entry,
+                entry = apply_rx_3.recv() => entry,
+            };
+            let entry = entry.unwrap();
+            if entry.entry_type() != raft::prelude::EntryType::EntryNormal || entry.data.is_empty()
+            {
+                continue;
+            }
+            assert_matches!(entry, raft::prelude::Entry {
+                data: edata,
+                context: econtext,
+                ..
+            } => {
+                assert_eq!(edata, data);
+                assert_eq!(econtext, context);
+            });
+            break;
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn test_check_quorum_and_pre_vote_disabled_elects_immediately() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10)]))
+            .await
+            .unwrap();
+
+        let (proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (_control_tx, control_rx) = mpsc::unbounded_channel();
+        let (fsm, mut apply_rx) = MockFsm::new(true);
+        let gear_command_tx = spawn_gear(
+            10,
+            100,
+            1,
+            RaftGroupLogStore::new(1, raft_log_store.clone()),
+            fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let options = RaftWorkerOptions {
+            group: 100,
+            node: 10,
+            raft_node: 1,
+
+            raft_start_mode: RaftStartMode::Initialize { peers: vec![1] },
+            raft_log_store: RaftGroupLogStore::new(1, raft_log_store.clone()),
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock) as ClockRef,
+
+            proposal_rx,
+            control_rx,
+
+            gear_command_tx,
+
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: false,
+            pre_vote: false,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: false,
+        };
+        let mut worker = RaftWorker::build(options).await.unwrap();
+        tokio::spawn(async move {
+            let _ = worker.run().await;
+        });
+
+        // A single-voter group wins its own election in `RaftWorker::build`, before it ever ticks,
+        // whether or not `check_quorum`/`pre_vote` are on.
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        let data = vec![b'q'; 16];
+        proposal_tx
+            .send(Proposal {
+                data: data.clone(),
+                context: vec![],
+            })
+            .unwrap();
+
+        let entry = loop {
+            let entry = apply_rx.recv().await.unwrap();
+            if entry.entry_type() != raft::prelude::EntryType::EntryNormal || entry.data.is_empty()
+            {
+                continue;
+            }
+            break entry;
+        };
+        assert_eq!(entry.data, data);
+    }
+
+    #[test(tokio::test)]
+    async fn test_tick_jitter_desynchronizes_groups_started_together() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+
+        const RAFT_NODES: [u64; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(
+                100,
+                BTreeMap::from_iter(RAFT_NODES.into_iter().map(|raft_node| (raft_node, raft_node))),
+            )
+            .await
+            .unwrap();
+
+        let tick_jitter = Duration::from_millis(20);
+        let mut workers = Vec::new();
+        for raft_node in RAFT_NODES {
+            raft_log_store.add_group(raft_node).await.unwrap();
+            let group_raft_log_store = RaftGroupLogStore::new(raft_node, raft_log_store.clone());
+            let (fsm, _apply_rx) = MockFsm::new(true);
+            let gear_command_tx = spawn_gear(
+                10,
+                100,
+                raft_node,
+                group_raft_log_store.clone(),
+                fsm,
+                crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+            );
+            let (_proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+            let (_control_tx, control_rx) = mpsc::unbounded_channel();
+            let options = RaftWorkerOptions {
+                group: 100,
+                node: 10,
+                raft_node,
+                // Every group has every node as a peer, so none take the single-voter fast path
+                // (which uses a different base tick interval) -- what's under test here is
+                // jitter, not that path.
+                raft_start_mode: RaftStartMode::Initialize {
+                    peers: RAFT_NODES.to_vec(),
+                },
+                raft_log_store: group_raft_log_store,
+                raft_logger: raft_logger.clone(),
+                raft_network: raft_network.clone(),
+                clock: Arc::new(RealClock),
+                proposal_rx,
+                control_rx,
+                gear_command_tx,
+                snapshot_policy: SnapshotPolicy::default(),
+                max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+                max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+                min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+                check_quorum: DEFAULT_CHECK_QUORUM,
+                pre_vote: DEFAULT_PRE_VOTE,
+                tick_jitter,
+                metrics_enabled: true,
+                metrics_cardinality_aggregated: false,
+            };
+            workers.push(RaftWorker::build(options).await.unwrap());
+        }
+
+        // Every group's tick interval, as if they'd all started ticking at the same instant.
+        let intervals: Vec<Duration> = workers.iter().map(|w| w.next_tick_interval()).collect();
+
+        for interval in &intervals {
+            assert!(
+                *interval >= RAFT_HEARTBEAT_TICK_DURATION
+                    && *interval < RAFT_HEARTBEAT_TICK_DURATION + tick_jitter,
+                "{:?} outside [{:?}, {:?})",
+                interval,
+                RAFT_HEARTBEAT_TICK_DURATION,
+                RAFT_HEARTBEAT_TICK_DURATION + tick_jitter
+            );
+        }
+        assert!(
+            intervals.iter().unique().count() > 1,
+            "every group's tick interval landed on the exact same value: {:?}",
+            intervals
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_single_voter_commit_latency_tracks_append_latency() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10)]))
+            .await
+            .unwrap();
+
+        // Unique node id so this test's time series don't mix with other tests' observations of
+        // the same globally registered histograms.
+        const NODE: u64 = 101;
+
+        let (proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (_control_tx, control_rx) = mpsc::unbounded_channel();
+        let (fsm, mut apply_rx) = MockFsm::new(true);
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+        let gear_command_tx = spawn_gear(
+            NODE,
+            100,
+            1,
+            group_raft_log_store.clone(),
+            fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let options = RaftWorkerOptions {
+            group: 100,
+            node: NODE,
+            raft_node: 1,
+
+            raft_start_mode: RaftStartMode::Initialize { peers: vec![1] },
+            raft_log_store: group_raft_log_store,
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock),
+
+            proposal_rx,
+            control_rx,
+
+            gear_command_tx,
+
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: false,
+        };
+        let mut worker = RaftWorker::build(options).await.unwrap();
+        tokio::spawn(async move {
+            let _ = worker.run().await;
+        });
+
+        // No sleep to wait out a heartbeat-driven election here: `RaftWorker::build`'s
+        // single-voter fast path already campaigned this node to leader before `run()` started.
+        let ctx = Context {
+            span_id: 0,
+            request_id: 1,
+            propose_at: now_millis(),
+        };
+        proposal_tx
+            .send(Proposal {
+                data: vec![b'd'; 16],
+                context: ctx.encode_to_vec().unwrap(),
+            })
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let entry = apply_rx.recv().await.unwrap();
+                if entry.entry_type() == raft::prelude::EntryType::EntryNormal
+                    && !entry.data.is_empty()
+                {
+                    break;
+                }
+            }
+        })
+        .await
+        .expect("proposal never got applied");
+
+        let commit_histogram = RAFT_LATENCY_HISTOGRAM_VEC
+            .get_metric_with_label_values(&["commit", &NODE.to_string(), "100", "1"])
+            .unwrap();
+        let append_histogram = RAFT_LATENCY_HISTOGRAM_VEC
+            .get_metric_with_label_values(&["append_log_entries", &NODE.to_string(), "100", "1"])
+            .unwrap();
+        assert!(commit_histogram.get_sample_count() > 0);
+        assert!(append_histogram.get_sample_count() > 0);
+
+        let commit_latency =
+            commit_histogram.get_sample_sum() / commit_histogram.get_sample_count() as f64;
+        let append_latency =
+            append_histogram.get_sample_sum() / append_histogram.get_sample_count() as f64;
+        // A single voter commits a proposal in the same ready cycle it appends it in: no
+        // heartbeat wait and no follower round trip sits between the two, unlike a multi-voter
+        // group. So commit latency should track append latency closely, not be off by anything
+        // close to `RAFT_HEARTBEAT_TICK_DURATION`.
+        assert!(
+            (commit_latency - append_latency).abs() < 0.1,
+            "expected commit latency ({commit_latency}s) to track append latency \
+             ({append_latency}s) closely for a single-voter group",
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_status_reports_committed_and_applied_indexes() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10)]))
+            .await
+            .unwrap();
+
+        // A single-member group wins its election in `RaftWorker::build`, before it ever ticks, so
+        // its status settles into a stable baseline before any proposal is sent.
+        let (proposal_tx, control_tx, _handle, mut apply_rx) = build_raft_worker(
+            100,
+            10,
+            1,
+            vec![1],
+            RaftGroupLogStore::new(1, raft_log_store.clone()),
+            raft_logger,
+            raft_network,
+            Arc::new(RealClock) as ClockRef,
+        )
+        .await;
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        let (notify_tx, notify_rx) = tokio::sync::oneshot::channel();
+        control_tx
+            .send(RaftWorkerControl::Status { notify: notify_tx })
+            .unwrap();
+        let baseline = notify_rx.await.unwrap().unwrap();
+        assert_eq!(baseline.leader_id, 1);
+        assert_eq!(baseline.committed_index, baseline.applied_index);
+        assert_eq!(baseline.committed_index, baseline.last_index);
+
+        const PROPOSALS: u64 = 3;
+        for i in 0..PROPOSALS {
+            proposal_tx
+                .send(Proposal {
+                    data: vec![i as u8; 16],
+                    context: vec![],
+                })
+                .unwrap();
+        }
+
+        let mut applied = 0;
+        while applied < PROPOSALS {
+            let entry = apply_rx.recv().await.unwrap();
+            if entry.entry_type() != raft::prelude::EntryType::EntryNormal || entry.data.is_empty()
+            {
+                continue;
+            }
+            applied += 1;
+        }
+
+        let (notify_tx, notify_rx) = tokio::sync::oneshot::channel();
+        control_tx
+            .send(RaftWorkerControl::Status { notify: notify_tx })
+            .unwrap();
+        let status = notify_rx.await.unwrap().unwrap();
+
+        // Every proposal to a single-member group becomes exactly one committed log entry, so the
+        // indexes should have advanced by exactly `PROPOSALS` past the pre-proposal baseline.
+        assert_eq!(status.committed_index, baseline.committed_index + PROPOSALS);
+        assert_eq!(status.applied_index, status.committed_index);
+        assert_eq!(status.last_index, status.committed_index);
+    }
+
+    #[test(tokio::test)]
+    async fn test_partition_elects_majority_leader_and_heals() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        raft_log_store.add_group(2).await.unwrap();
+        raft_log_store.add_group(3).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10), (3, 10)]))
+            .await
+            .unwrap();
+
+        macro_rules! worker {
+            ($id:expr) => {
+                build_raft_worker(
+                    100,
+                    10,
+                    $id,
+                    vec![1, 2, 3],
+                    RaftGroupLogStore::new($id, raft_log_store.clone()),
+                    raft_logger.clone(),
+                    raft_network.clone(),
+                    Arc::new(RealClock) as ClockRef,
+                )
+                .await
+            };
+        }
+
+        let (_proposal_tx_1, _control_tx_1, _handle_1, mut apply_rx_1) = worker!(1);
+        let (proposal_tx_2, control_tx_2, _handle_2, mut apply_rx_2) = worker!(2);
+        let (_proposal_tx_3, _control_tx_3, _handle_3, mut apply_rx_3) = worker!(3);
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        // Node 1 is elected leader first in this mock network. Isolate it from the other two,
+        // which still form a majority on their own.
+        raft_network.partition([1], [2, 3]).await;
+
+        // Node 1 can no longer reach anyone, so force node 2 to campaign rather than wait out a
+        // real election timeout.
+        let (notify_tx, notify_rx) = tokio::sync::oneshot::channel();
+        control_tx_2
+            .send(RaftWorkerControl::Campaign { notify: notify_tx })
+            .unwrap();
+        notify_rx.await.unwrap().unwrap();
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        // Node 2 won the election on the majority side and can commit proposals...
+        let data = vec![b'd'; 16];
+        let context = vec![b'c'; 16];
+        proposal_tx_2
+            .send(Proposal {
+                data: data.clone(),
+                context: context.clone(),
+            })
+            .unwrap();
+
+        loop {
+            let entry = tokio::select! {
+                entry = apply_rx_2.recv() => entry,
+                entry = apply_rx_3.recv() => entry,
+            };
+            let entry = entry.unwrap();
+            if entry.entry_type() != raft::prelude::EntryType::EntryNormal || entry.data.is_empty()
+            {
+                continue;
+            }
+            assert_matches!(entry, raft::prelude::Entry {
+                data: edata,
+                context: econtext,
+                ..
+            } => {
+                assert_eq!(edata, data);
+                assert_eq!(econtext, context);
+            });
+            break;
+        }
+
+        // ...while node 1, still partitioned away, never sees it.
+        let isolated = tokio::time::timeout(Duration::from_secs(2), apply_rx_1.recv()).await;
+        assert!(
+            isolated.is_err(),
+            "partitioned-away node 1 should not observe entries committed on the majority side"
+        );
+
+        // Healing lets node 1 rejoin: it can only catch up on the entry above by accepting the
+        // new leader's higher-term AppendEntries, which means it has stepped down to follower.
+        raft_network.heal().await;
+
+        let caught_up = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let entry = apply_rx_1.recv().await.unwrap();
+                if entry.entry_type() == raft::prelude::EntryType::EntryNormal
+                    && !entry.data.is_empty()
+                {
+                    return entry;
+                }
+            }
+        })
+        .await
+        .expect("node 1 should catch up on the majority side's entry once healed");
+        assert_eq!(caught_up.data, data);
+        assert_eq!(caught_up.context, context);
+    }
+
+    /// Wraps [`MockRaftClient`] to record the encoded size of every non-empty append message it
+    /// sends, so tests can assert `max_size_per_msg` is honored.
+    #[derive(Clone)]
+    struct RecordingRaftClient {
+        inner: MockRaftClient,
+        sizes: Arc<Mutex<Vec<usize>>>,
+    }
+
+    #[async_trait]
+    impl RaftClient for RecordingRaftClient {
+        async fn send(&mut self, msgs: Vec<raft::prelude::Message>) -> Result<()> {
+            {
+                let mut sizes = self.sizes.lock().await;
+                for msg in &msgs {
+                    if msg.msg_type() == raft::prelude::MessageType::MsgAppend
+                        && !msg.entries.is_empty()
+                    {
+                        sizes.push(msg.encoded_len());
+                    }
+                }
+            }
+            self.inner.send(msgs).await
+        }
+    }
+
+    #[derive(Clone)]
+    struct RecordingRaftNetwork {
+        inner: MockRaftNetwork,
+        sizes: Arc<Mutex<Vec<usize>>>,
+    }
+
+    #[async_trait]
+    impl RaftNetwork for RecordingRaftNetwork {
+        type RaftClient = RecordingRaftClient;
+
+        async fn register(&self, group: u64, raft_nodes: BTreeMap<u64, u64>) -> Result<()> {
+            self.inner.register(group, raft_nodes).await
+        }
+
+        async fn client(&self, raft_node: u64) -> Result<Self::RaftClient> {
+            Ok(RecordingRaftClient {
+                inner: self.inner.client(raft_node).await?,
+                sizes: self.sizes.clone(),
+            })
+        }
+
+        async fn recv(&self, msgs: Vec<raft::prelude::Message>) -> Result<()> {
+            self.inner.recv(msgs).await
+        }
+
+        async fn take_message_rx(
+            &self,
+            raft_node: u64,
+        ) -> Result<mpsc::UnboundedReceiver<raft::prelude::Message>> {
+            self.inner.take_message_rx(raft_node).await
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn test_max_size_per_msg_caps_append_messages() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        raft_log_store.add_group(2).await.unwrap();
+        raft_log_store.add_group(3).await.unwrap();
+
+        let sizes = Arc::new(Mutex::new(Vec::new()));
+        let raft_network = RecordingRaftNetwork {
+            inner: MockRaftNetwork::default(),
+            sizes: sizes.clone(),
+        };
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10), (3, 10)]))
+            .await
+            .unwrap();
+
+        const MAX_SIZE_PER_MSG: u64 = 256;
+
+        macro_rules! worker {
+            ($id:expr) => {{
+                let (proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+                let (_control_tx, control_rx) = mpsc::unbounded_channel();
+                let (fsm, apply_rx) = MockFsm::new(true);
+                let group_raft_log_store = RaftGroupLogStore::new($id, raft_log_store.clone());
+                let gear_command_tx = spawn_gear(
+                    10,
+                    100,
+                    $id,
+                    group_raft_log_store.clone(),
+                    fsm,
+                    crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+                );
+                let options = RaftWorkerOptions {
+                    group: 100,
+                    node: 10,
+                    raft_node: $id,
+                    raft_start_mode: RaftStartMode::Initialize {
+                        peers: vec![1, 2, 3],
+                    },
+                    raft_log_store: group_raft_log_store,
+                    raft_logger: raft_logger.clone(),
+                    raft_network: raft_network.clone(),
+                    clock: Arc::new(RealClock),
+                    proposal_rx,
+                    control_rx,
+                    gear_command_tx,
+                    snapshot_policy: SnapshotPolicy::default(),
+                    max_size_per_msg: MAX_SIZE_PER_MSG,
+                    max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+                    min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+                    check_quorum: DEFAULT_CHECK_QUORUM,
+                    pre_vote: DEFAULT_PRE_VOTE,
+                    tick_jitter: DEFAULT_TICK_JITTER,
+                    metrics_enabled: true,
+                    metrics_cardinality_aggregated: false,
+                };
+                let mut worker = RaftWorker::build(options).await.unwrap();
+                tokio::spawn(async move {
+                    let _ = worker.run().await;
+                });
+                (proposal_tx, apply_rx)
+            }};
+        }
+
+        let (proposal_tx_1, _apply_rx_1) = worker!(1);
+        let (_proposal_tx_2, _apply_rx_2) = worker!(2);
+        let (_proposal_tx_3, _apply_rx_3) = worker!(3);
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        // Propose many small entries back-to-back so they queue up and get batched into append
+        // messages that would exceed `MAX_SIZE_PER_MSG` if the cap were ignored.
+        const PROPOSALS: usize = 64;
+        for i in 0..PROPOSALS {
+            proposal_tx_1
+                .send(Proposal {
+                    data: format!("value-{:04}", i).into_bytes(),
+                    context: vec![],
+                })
+                .unwrap();
+        }
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        let recorded = sizes.lock().await;
+        assert!(!recorded.is_empty());
+        // A single unwindowed append carrying every proposed entry would be far larger than the
+        // cap; seeing more than one append message confirms entries were split to respect it.
+        assert!(
+            recorded.len() > 1,
+            "expected entries to be split across multiple append messages to respect the cap"
+        );
+        for size in recorded.iter() {
+            assert!(
+                *size <= MAX_SIZE_PER_MSG as usize * 2,
+                "append message size {} exceeds twice the configured cap {}",
+                size,
+                MAX_SIZE_PER_MSG
+            );
+        }
+    }
+
+    /// `Fsm` whose `apply` only ever forwards entries through a bounded channel, so a deliberately
+    /// slow-to-drain receiver inflates the time `apply` reports as spent handing results off,
+    /// without doing any other FSM work that would inflate it for a different reason.
+    #[derive(Clone)]
+    struct BlockingFsm {
+        tx: mpsc::Sender<raft::prelude::Entry>,
+    }
+
+    #[async_trait]
+    impl Fsm for BlockingFsm {
+        async fn apply(
+            &self,
+            _group: u64,
+            is_leader: bool,
+            entries: Vec<raft::prelude::Entry>,
+        ) -> Result<Duration> {
+            if !is_leader {
+                return Ok(Duration::ZERO);
+            }
+            let mut notify_elapsed = Duration::ZERO;
+            for entry in entries {
+                let start = Instant::now();
+                self.tx.send(entry).await.unwrap();
+                notify_elapsed += start.elapsed();
+            }
+            Ok(notify_elapsed)
+        }
+
+        async fn raft_applied_index(&self) -> Result<u64> {
+            Ok(0)
+        }
+    }
+
+    /// Wraps [`MockRaftClient`] so sends to one specific raft node always fail, to test that
+    /// [`RaftWorker::send_messages`] isolates a single unreachable peer from the rest.
+    #[derive(Clone)]
+    struct FlakyRaftClient {
+        inner: MockRaftClient,
+        always_fail: bool,
+    }
+
+    #[async_trait]
+    impl RaftClient for FlakyRaftClient {
+        async fn send(&mut self, msgs: Vec<raft::prelude::Message>) -> Result<()> {
+            if self.always_fail {
+                return Err(Error::err(anyhow::anyhow!("simulated send failure")));
+            }
+            self.inner.send(msgs).await
+        }
+    }
+
+    #[derive(Clone)]
+    struct FlakyRaftNetwork {
+        inner: MockRaftNetwork,
+        flaky_raft_node: u64,
+    }
+
+    #[async_trait]
+    impl RaftNetwork for FlakyRaftNetwork {
+        type RaftClient = FlakyRaftClient;
+
+        async fn register(&self, group: u64, raft_nodes: BTreeMap<u64, u64>) -> Result<()> {
+            self.inner.register(group, raft_nodes).await
+        }
+
+        async fn client(&self, raft_node: u64) -> Result<FlakyRaftClient> {
+            Ok(FlakyRaftClient {
+                inner: self.inner.client(raft_node).await?,
+                always_fail: raft_node == self.flaky_raft_node,
+            })
+        }
+
+        async fn recv(&self, msgs: Vec<raft::prelude::Message>) -> Result<()> {
+            self.inner.recv(msgs).await
+        }
+
+        async fn take_message_rx(
+            &self,
+            raft_node: u64,
+        ) -> Result<mpsc::UnboundedReceiver<raft::prelude::Message>> {
+            self.inner.take_message_rx(raft_node).await
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn test_send_messages_isolates_unreachable_peer() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+
+        let raft_network = FlakyRaftNetwork {
+            inner: MockRaftNetwork::default(),
+            flaky_raft_node: 2,
+        };
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10), (3, 10)]))
+            .await
+            .unwrap();
+
+        let mut healthy_rx = raft_network.take_message_rx(3).await.unwrap();
+
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+        let (fsm, _apply_rx) = MockFsm::new(true);
+        let gear_command_tx = spawn_gear(
+            10,
+            100,
+            1,
+            group_raft_log_store.clone(),
+            fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let (_proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (_control_tx, control_rx) = mpsc::unbounded_channel();
+        let options = RaftWorkerOptions {
+            group: 100,
+            node: 10,
+            raft_node: 1,
+            raft_start_mode: RaftStartMode::Initialize {
+                peers: vec![1, 2, 3],
+            },
+            raft_log_store: group_raft_log_store,
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock),
+            proposal_rx,
+            control_rx,
+            gear_command_tx,
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: false,
+        };
+        let mut worker = RaftWorker::build(options).await.unwrap();
+
+        let msg_to_flaky = raft::prelude::Message {
+            to: 2,
+            ..Default::default()
+        };
+        let msg_to_healthy = raft::prelude::Message {
+            to: 3,
+            ..Default::default()
+        };
+
+        // A peer that never comes back mustn't fail the whole batch, nor block the healthy peer's
+        // message from going out.
+        worker
+            .send_messages(vec![msg_to_flaky, msg_to_healthy])
+            .await
+            .unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(1), healthy_rx.recv())
+            .await
+            .expect("healthy peer should still receive its message")
+            .unwrap();
+        assert_eq!(received.to, 3);
+
+        let dropped = RAFT_DROPPED_MESSAGES_COUNTER_VEC
+            .with_label_values(&["10", "100", "1", "2"])
+            .get();
+        assert!(
+            dropped > 0,
+            "expected the dropped-messages counter to record the permanently failing peer"
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_send_messages_reuses_scratch_allocations() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10)]))
+            .await
+            .unwrap();
+        let _peer_rx = raft_network.take_message_rx(2).await.unwrap();
+
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+        let (fsm, _apply_rx) = MockFsm::new(true);
+        let gear_command_tx = spawn_gear(
+            10,
+            100,
+            1,
+            group_raft_log_store.clone(),
+            fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let (_proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (_control_tx, control_rx) = mpsc::unbounded_channel();
+        let options = RaftWorkerOptions {
+            group: 100,
+            node: 10,
+            raft_node: 1,
+            raft_start_mode: RaftStartMode::Initialize { peers: vec![1, 2] },
+            raft_log_store: group_raft_log_store,
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock),
+            proposal_rx,
+            control_rx,
+            gear_command_tx,
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: false,
+        };
+        let mut worker = RaftWorker::build(options).await.unwrap();
+
+        let msg = raft::prelude::Message {
+            to: 2,
+            ..Default::default()
+        };
+
+        // Same peer, same-size batch, back to back: the scratch vec backing peer 2's bucket
+        // should be the exact allocation reused, not a fresh one.
+        worker.send_messages(vec![msg.clone()]).await.unwrap();
+        let bucket = worker.raft_node_msgs_scratch.get(&2).unwrap();
+        let (ptr_after_first, cap_after_first) = (bucket.as_ptr(), bucket.capacity());
+
+        worker.send_messages(vec![msg]).await.unwrap();
+        let bucket = worker.raft_node_msgs_scratch.get(&2).unwrap();
+        let (ptr_after_second, cap_after_second) = (bucket.as_ptr(), bucket.capacity());
+
+        assert_eq!(
+            ptr_after_first, ptr_after_second,
+            "same-size batches to the same peer should reuse the scratch vec's allocation"
+        );
+        assert_eq!(cap_after_first, cap_after_second);
+    }
+
+    #[test(tokio::test)]
+    async fn test_entry_span_id_decodes_once_per_index_per_ready_cycle() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10)]))
+            .await
+            .unwrap();
+
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+        let (fsm, _apply_rx) = MockFsm::new(true);
+        let gear_command_tx = spawn_gear(
+            10,
+            100,
+            1,
+            group_raft_log_store.clone(),
+            fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let (_proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (_control_tx, control_rx) = mpsc::unbounded_channel();
+        let options = RaftWorkerOptions {
+            group: 100,
+            node: 10,
+            raft_node: 1,
+            raft_start_mode: RaftStartMode::Initialize { peers: vec![1] },
+            raft_log_store: group_raft_log_store,
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock),
+            proposal_rx,
+            control_rx,
+            gear_command_tx,
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: false,
+        };
+        let mut worker = RaftWorker::build(options).await.unwrap();
+
+        let entry = raft::prelude::Entry {
+            index: 7,
+            context: Context {
+                span_id: 111,
+                request_id: 0,
+                propose_at: 0,
+            }
+            .encode_to_vec()
+            .unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(worker.entry_span_id(&entry).unwrap(), 111);
+
+        // Same index, deliberately mismatched context bytes: if this decoded again instead of
+        // hitting the per-ready cache, it would return 222, not the original 111.
+        let restamped_entry = raft::prelude::Entry {
+            index: 7,
+            context: Context {
+                span_id: 222,
+                request_id: 0,
+                propose_at: 0,
+            }
+            .encode_to_vec()
+            .unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(
+            worker.entry_span_id(&restamped_entry).unwrap(),
+            111,
+            "same entry index within one ready cycle must hit the cache, not re-decode"
+        );
+
+        // A fresh `handle_ready` cycle resets the cache, so a genuinely new ready can decode a
+        // different context at the same index (raft indices are only unique within one log, but
+        // the cache itself is scoped to a single ready regardless).
+        worker.decoded_context_span_ids.clear();
+        assert_eq!(worker.entry_span_id(&restamped_entry).unwrap(), 222);
+    }
+
+    #[test(tokio::test)]
+    async fn test_append_log_entries_links_span_for_conf_change_entries() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10)]))
+            .await
+            .unwrap();
+
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+        let (fsm, _apply_rx) = MockFsm::new(true);
+        let gear_command_tx = spawn_gear(
+            10,
+            100,
+            1,
+            group_raft_log_store.clone(),
+            fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let (_proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (_control_tx, control_rx) = mpsc::unbounded_channel();
+        let options = RaftWorkerOptions {
+            group: 100,
+            node: 10,
+            raft_node: 1,
+            raft_start_mode: RaftStartMode::Initialize { peers: vec![1] },
+            raft_log_store: group_raft_log_store,
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock),
+            proposal_rx,
+            control_rx,
+            gear_command_tx,
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: false,
+        };
+        let mut worker = RaftWorker::build(options).await.unwrap();
+
+        // A membership-change proposal: `entry_type` is `EntryConfChangeV2`, not `EntryNormal`,
+        // but it still carries a tracing `Context` in `entry.context` the same way a normal
+        // proposal does.
+        // Index 1: the first index a fresh group's log accepts.
+        let entry = raft::prelude::Entry {
+            entry_type: raft::prelude::EntryType::EntryConfChangeV2 as i32,
+            index: 1,
+            data: vec![b'd'; 4],
+            context: Context {
+                span_id: 333,
+                request_id: 0,
+                propose_at: 0,
+            }
+            .encode_to_vec()
+            .unwrap(),
+            ..Default::default()
+        };
+        worker.append_log_entries(vec![entry]).await.unwrap();
+
+        // `append_log_entries` must have decoded the conf-change entry's context (via
+        // `entry_span_id`) rather than skipping it for not being `EntryNormal`, proving its span
+        // links through append the same way a normal proposal's does. Without the `tracing`
+        // feature, `append_log_entries` decodes no contexts at all.
+        let expected = if cfg!(feature = "tracing") {
+            Some(&333)
+        } else {
+            None
+        };
+        assert_eq!(worker.decoded_context_span_ids.get(&1), expected);
+    }
+
+    #[test(tokio::test)]
+    async fn test_metrics_cardinality_aggregated_drops_group_and_raft_node_labels() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10)]))
+            .await
+            .unwrap();
+
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+        let (fsm, _apply_rx) = MockFsm::new(true);
+        let gear_command_tx = spawn_gear(
+            10,
+            100,
+            1,
+            group_raft_log_store.clone(),
+            fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let (_proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (_control_tx, control_rx) = mpsc::unbounded_channel();
+        // Unique node/group so this test's sample counts don't mix with other tests' observations
+        // of the same globally registered vecs. The raft node is the one registered above.
+        const NODE: u64 = 102;
+        const GROUP: u64 = 102;
+        const RAFT_NODE: u64 = 1;
+        let options = RaftWorkerOptions {
+            group: GROUP,
+            node: NODE,
+            raft_node: RAFT_NODE,
+            raft_start_mode: RaftStartMode::Initialize { peers: vec![1] },
+            raft_log_store: group_raft_log_store,
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock),
+            proposal_rx,
+            control_rx,
+            gear_command_tx,
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: true,
+        };
+        let mut worker = RaftWorker::build(options).await.unwrap();
+
+        let entry = raft::prelude::Entry {
+            index: 1,
+            data: vec![b'd'; 4],
+            ..Default::default()
+        };
+        worker.append_log_entries(vec![entry]).await.unwrap();
+
+        let aggregated = RAFT_LATENCY_HISTOGRAM_VEC_AGGREGATED
+            .get_metric_with_label_values(&["append_log_entries", &NODE.to_string()])
+            .unwrap();
+        assert!(
+            aggregated.get_sample_count() > 0,
+            "expected the aggregated, node-level histogram to have recorded a sample"
+        );
+
+        // The per-group vec must not have gained a series for this group/raft_node: aggregation
+        // means this worker never touches it at all.
+        let per_group = RAFT_LATENCY_HISTOGRAM_VEC
+            .get_metric_with_label_values(&[
+                "append_log_entries",
+                &NODE.to_string(),
+                &GROUP.to_string(),
+                &RAFT_NODE.to_string(),
+            ])
+            .unwrap();
+        assert_eq!(
+            per_group.get_sample_count(),
+            0,
+            "aggregated worker must not report into the per-group/raft_node histogram"
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_reconcile_raft_clients_adds_and_drops_peers_from_conf_state() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+
+        let raft_network = MockRaftNetwork::default();
+        // Peer 3 is registered on the network (as it would be on a running cluster) but isn't
+        // one of this node's initial peers, simulating a voter added while this node was behind
+        // far enough to need a snapshot.
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10), (3, 10)]))
+            .await
+            .unwrap();
+
+        let mut peer_3_rx = raft_network.take_message_rx(3).await.unwrap();
+
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+        let (fsm, _apply_rx) = MockFsm::new(true);
+        let gear_command_tx = spawn_gear(
+            10,
+            100,
+            1,
+            group_raft_log_store.clone(),
+            fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let (_proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (_control_tx, control_rx) = mpsc::unbounded_channel();
+        let options = RaftWorkerOptions {
+            group: 100,
+            node: 10,
+            raft_node: 1,
+            raft_start_mode: RaftStartMode::Initialize { peers: vec![1, 2] },
+            raft_log_store: group_raft_log_store,
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock),
+            proposal_rx,
+            control_rx,
+            gear_command_tx,
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: false,
+        };
+        let mut worker = RaftWorker::build(options).await.unwrap();
+        assert!(!worker.raft_clients.contains_key(&3));
+
+        // A snapshot reflecting the group having grown to include peer 3, and having dropped
+        // peer 2, as `ConfState` alone would look after a membership change this node missed.
+        let cs = raft::prelude::ConfState {
+            voters: vec![1, 3],
+            ..Default::default()
+        };
+        worker.reconcile_raft_clients(&cs).await.unwrap();
+
+        assert!(worker.raft_clients.contains_key(&3));
+        assert!(!worker.raft_clients.contains_key(&2));
+
+        // The newly created client must actually be usable.
+        worker
+            .send_messages(vec![raft::prelude::Message {
+                to: 3,
+                ..Default::default()
+            }])
+            .await
+            .unwrap();
+        let received = tokio::time::timeout(Duration::from_secs(1), peer_3_rx.recv())
+            .await
+            .expect("should be able to message the newly added peer")
+            .unwrap();
+        assert_eq!(received.to, 3);
+    }
+
+    #[test(tokio::test)]
+    async fn test_apply_metrics_isolate_notify_latency_from_fsm_latency() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        raft_log_store.add_group(2).await.unwrap();
+        raft_log_store.add_group(3).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10), (3, 10)]))
+            .await
+            .unwrap();
+
+        // Unique node id so this test's time series don't mix with other tests' observations of
+        // the same globally registered histograms.
+        const NODE: u64 = 99;
+
+        macro_rules! worker {
+            ($id:expr) => {{
+                let (proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+                let (_control_tx, control_rx) = mpsc::unbounded_channel();
+                // Capacity 1, pre-filled, so the first real `tx.send` blocks until the slot is
+                // drained rather than completing immediately.
+                let (tx, rx) = mpsc::channel(1);
+                tx.try_send(raft::prelude::Entry::default()).unwrap();
+                let fsm = BlockingFsm { tx };
+                let group_raft_log_store = RaftGroupLogStore::new($id, raft_log_store.clone());
+                let gear_command_tx = spawn_gear(
+                    NODE,
+                    100,
+                    $id,
+                    group_raft_log_store.clone(),
+                    fsm,
+                    crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+                );
+                let options = RaftWorkerOptions {
+                    group: 100,
+                    node: NODE,
+                    raft_node: $id,
+                    raft_start_mode: RaftStartMode::Initialize {
+                        peers: vec![1, 2, 3],
+                    },
+                    raft_log_store: group_raft_log_store,
+                    raft_logger: raft_logger.clone(),
+                    raft_network: raft_network.clone(),
+                    clock: Arc::new(RealClock),
+                    proposal_rx,
+                    control_rx,
+                    gear_command_tx,
+                    snapshot_policy: SnapshotPolicy::default(),
+                    max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+                    max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+                    min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+                    check_quorum: DEFAULT_CHECK_QUORUM,
+                    pre_vote: DEFAULT_PRE_VOTE,
+                    tick_jitter: DEFAULT_TICK_JITTER,
+                    metrics_enabled: true,
+                    metrics_cardinality_aggregated: false,
+                };
+                let mut worker = RaftWorker::build(options).await.unwrap();
+                tokio::spawn(async move {
+                    let _ = worker.run().await;
+                });
+                (proposal_tx, rx)
+            }};
+        }
+
+        let (proposal_tx_1, rx_1) = worker!(1);
+        let (_proposal_tx_2, rx_2) = worker!(2);
+        let (_proposal_tx_3, rx_3) = worker!(3);
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        proposal_tx_1
+            .send(Proposal {
+                data: vec![b'd'; 16],
+                context: vec![],
+            })
+            .unwrap();
+
+        // Leave the pre-filled channel undrained for a while to simulate a blocked downstream
+        // apply consumer, then drain the filler entry so the real `tx.send` can complete.
+        const BLOCK: Duration = Duration::from_millis(500);
+        tokio::time::sleep(BLOCK).await;
+        let mut rxs = vec![rx_1, rx_2, rx_3];
+        for rx in rxs.iter_mut() {
+            let _ = rx.try_recv();
+        }
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let mut fsm_elapsed = Duration::ZERO;
+        let mut notify_elapsed = Duration::ZERO;
+        for raft_node in [1u64, 2, 3] {
+            let fsm_histogram = RAFT_LATENCY_HISTOGRAM_VEC
+                .get_metric_with_label_values(&[
+                    "apply_log_entries",
+                    &NODE.to_string(),
+                    "100",
+                    &raft_node.to_string(),
+                ])
+                .unwrap();
+            let notify_histogram = RAFT_LATENCY_HISTOGRAM_VEC
+                .get_metric_with_label_values(&[
+                    "apply_notify",
+                    &NODE.to_string(),
+                    "100",
+                    &raft_node.to_string(),
+                ])
+                .unwrap();
+            fsm_elapsed += Duration::from_secs_f64(fsm_histogram.get_sample_sum());
+            notify_elapsed += Duration::from_secs_f64(notify_histogram.get_sample_sum());
+        }
+
+        // The blocked receiver inflated `apply_notify_latency_histogram`...
+        assert!(
+            notify_elapsed >= BLOCK / 2,
+            "expected notify latency to reflect the blocked consumer, got {:?}",
+            notify_elapsed
+        );
+        // ...but `apply_log_entries_latency_histogram` (pure FSM processing, which `BlockingFsm`
+        // does none of) stayed low, proving the two move independently.
+        assert!(
+            fsm_elapsed < BLOCK / 2,
+            "expected fsm latency to stay low despite the blocked consumer, got {:?}",
+            fsm_elapsed
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_commit_latency_histogram_records_end_to_end_latency() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        raft_log_store.add_group(2).await.unwrap();
+        raft_log_store.add_group(3).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10), (3, 10)]))
+            .await
+            .unwrap();
+
+        // Unique node id so this test's time series don't mix with other tests' observations of
+        // the same globally registered histograms.
+        const NODE: u64 = 100;
+
+        macro_rules! worker {
+            ($id:expr) => {{
+                let (proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+                let (_control_tx, control_rx) = mpsc::unbounded_channel();
+                let (fsm, apply_rx) = MockFsm::new(true);
+                let group_raft_log_store = RaftGroupLogStore::new($id, raft_log_store.clone());
+                let gear_command_tx = spawn_gear(
+                    NODE,
+                    100,
+                    $id,
+                    group_raft_log_store.clone(),
+                    fsm,
+                    crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+                );
+                let options = RaftWorkerOptions {
+                    group: 100,
+                    node: NODE,
+                    raft_node: $id,
+                    raft_start_mode: RaftStartMode::Initialize {
+                        peers: vec![1, 2, 3],
+                    },
+                    raft_log_store: group_raft_log_store,
+                    raft_logger: raft_logger.clone(),
+                    raft_network: raft_network.clone(),
+                    clock: Arc::new(RealClock),
+                    proposal_rx,
+                    control_rx,
+                    gear_command_tx,
+                    snapshot_policy: SnapshotPolicy::default(),
+                    max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+                    max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+                    min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+                    check_quorum: DEFAULT_CHECK_QUORUM,
+                    pre_vote: DEFAULT_PRE_VOTE,
+                    tick_jitter: DEFAULT_TICK_JITTER,
+                    metrics_enabled: true,
+                    metrics_cardinality_aggregated: false,
+                };
+                let mut worker = RaftWorker::build(options).await.unwrap();
+                tokio::spawn(async move {
+                    let _ = worker.run().await;
+                });
+                (proposal_tx, apply_rx)
+            }};
+        }
+
+        let (proposal_tx_1, mut apply_rx_1) = worker!(1);
+        let (_proposal_tx_2, mut apply_rx_2) = worker!(2);
+        let (_proposal_tx_3, mut apply_rx_3) = worker!(3);
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        const SIMULATED_PROPOSE_DELAY: Duration = Duration::from_millis(50);
+        let ctx = Context {
+            span_id: 0,
+            request_id: 1,
+            propose_at: now_millis() - SIMULATED_PROPOSE_DELAY.as_millis() as u64,
+        };
+        proposal_tx_1
+            .send(Proposal {
+                data: vec![b'd'; 16],
+                context: ctx.encode_to_vec().unwrap(),
+            })
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(30), async {
+            loop {
+                let entry = tokio::select! {
+                    entry = apply_rx_1.recv() => entry,
+                    entry = apply_rx_2.recv() => entry,
+                    entry = apply_rx_3.recv() => entry,
+                };
+                let entry = entry.unwrap();
+                if entry.entry_type() == raft::prelude::EntryType::EntryNormal && !entry.data.is_empty() {
+                    break;
+                }
+            }
+        })
+        .await
+        .expect("proposal never got applied");
+
+        let mut sample_count = 0;
+        let mut sample_sum = 0.0;
+        for raft_node in [1u64, 2, 3] {
+            let histogram = RAFT_LATENCY_HISTOGRAM_VEC
+                .get_metric_with_label_values(&["commit", &NODE.to_string(), "100", &raft_node.to_string()])
+                .unwrap();
+            sample_count += histogram.get_sample_count();
+            sample_sum += histogram.get_sample_sum();
+        }
+
+        assert!(sample_count > 0, "expected commit_latency_histogram to have recorded at least one sample");
+        assert!(
+            sample_sum >= SIMULATED_PROPOSE_DELAY.as_secs_f64(),
+            "expected recorded commit latency to reflect the simulated propose delay, got {}s",
+            sample_sum
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_commit_latency_histogram_stays_empty_when_metrics_disabled() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10)]))
+            .await
+            .unwrap();
+
+        // Unique node id so this test's time series don't mix with other tests' observations of
+        // the same globally registered histograms.
+        const NODE: u64 = 103;
+
+        let (proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (_control_tx, control_rx) = mpsc::unbounded_channel();
+        let (fsm, mut apply_rx) = MockFsm::new(true);
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+        let gear_command_tx = spawn_gear(
+            NODE,
+            100,
+            1,
+            group_raft_log_store.clone(),
+            fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
+        let options = RaftWorkerOptions {
+            group: 100,
+            node: NODE,
+            raft_node: 1,
+            // A single-voter group wins its own election in `RaftWorker::build`, so this test
+            // doesn't need to wait out a multi-voter election before proposing.
+            raft_start_mode: RaftStartMode::Initialize { peers: vec![1] },
+            raft_log_store: group_raft_log_store,
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock),
+            proposal_rx,
+            control_rx,
+            gear_command_tx,
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: false,
+            metrics_cardinality_aggregated: false,
+        };
+        let mut worker = RaftWorker::build(options).await.unwrap();
+        tokio::spawn(async move {
+            let _ = worker.run().await;
+        });
+
+        let ctx = Context {
+            span_id: 0,
+            request_id: 1,
+            propose_at: now_millis(),
+        };
+        proposal_tx
+            .send(Proposal {
+                data: vec![b'd'; 16],
+                context: ctx.encode_to_vec().unwrap(),
+            })
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let entry = apply_rx.recv().await.unwrap();
+                if entry.entry_type() == raft::prelude::EntryType::EntryNormal && !entry.data.is_empty() {
+                    break;
+                }
+            }
+        })
+        .await
+        .expect("proposal never got applied");
+
+        let histogram = RAFT_LATENCY_HISTOGRAM_VEC
+            .get_metric_with_label_values(&["commit", &NODE.to_string(), "100", "1"])
+            .unwrap();
+        assert_eq!(
+            histogram.get_sample_count(),
+            0,
+            "commit_latency_histogram should stay empty when metrics_enabled is false"
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_idle_loop_falls_back_to_event_driven_wait() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        raft_log_store.add_group(2).await.unwrap();
+        raft_log_store.add_group(3).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
             .register(100, BTreeMap::from_iter([(1, 10), (2, 10), (3, 10)]))
             .await
             .unwrap();
 
+        // Unique node id so this test's sample counts don't mix with other tests' observations of
+        // the same globally registered histogram.
+        const NODE: u64 = 104;
+
         macro_rules! worker {
             ($id:expr) => {
                 build_raft_worker(
                     100,
-                    10,
+                    NODE,
                     $id,
                     vec![1, 2, 3],
                     RaftGroupLogStore::new($id, raft_log_store.clone()),
                     raft_logger.clone(),
                     raft_network.clone(),
+                    Arc::new(RealClock) as ClockRef,
                 )
                 .await
             };
         }
 
+        let (_proposal_tx_1, _control_tx_1, _handle_1, _apply_rx_1) = worker!(1);
+        let (_proposal_tx_2, _control_tx_2, _handle_2, _apply_rx_2) = worker!(2);
+        let (_proposal_tx_3, _control_tx_3, _handle_3, _apply_rx_3) = worker!(3);
+
+        // Let leader election settle so the cluster is genuinely idle (no proposals, steady-state
+        // heartbeats only) for the measurement window below.
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        let sample_count = |raft_node: u64| {
+            RAFT_LATENCY_HISTOGRAM_VEC
+                .get_metric_with_label_values(&["poll_channel", &NODE.to_string(), "100", &raft_node.to_string()])
+                .unwrap()
+                .get_sample_count()
+        };
+        let before: Vec<u64> = [1u64, 2, 3].into_iter().map(sample_count).collect();
+
+        const IDLE_WINDOW: Duration = Duration::from_secs(2);
+        tokio::time::sleep(IDLE_WINDOW).await;
+
+        let after: Vec<u64> = [1u64, 2, 3].into_iter().map(sample_count).collect();
+
+        // With the old design the loop always woke up at least once per `MIN_LOOP_DURATION`
+        // (10ms) regardless of whether there was anything to do, i.e. at least
+        // `IDLE_WINDOW / 10ms` = 200 iterations here. The event-driven idle wait added in this
+        // change instead blocks until a message, proposal, control, or the next heartbeat tick
+        // (100ms) actually wakes it, so steady-state idle iterations should track
+        // `IDLE_WINDOW / RAFT_HEARTBEAT_TICK_DURATION` (~20) plus the odd extra iteration spent
+        // processing an incoming heartbeat, not the old busy-poll rate.
+        let old_design_iterations = IDLE_WINDOW.as_millis() / 10;
+        for (raft_node, (before, after)) in [1u64, 2, 3].into_iter().zip(before.into_iter().zip(after)) {
+            let woken = after - before;
+            assert!(
+                u128::from(woken) < old_design_iterations,
+                "raft node {} woke up {} times over {:?}, expected well under the old \
+                 busy-poll rate of {} wakeups",
+                raft_node,
+                woken,
+                IDLE_WINDOW,
+                old_design_iterations
+            );
+        }
+    }
+
+    /// `Fsm` that sleeps `delay` per entry before forwarding it, simulating a consumer that's
+    /// simply slow rather than fully stuck.
+    #[derive(Clone)]
+    struct SlowFsm {
+        delay: Duration,
+        tx: mpsc::UnboundedSender<raft::prelude::Entry>,
+    }
+
+    #[async_trait]
+    impl Fsm for SlowFsm {
+        async fn apply(
+            &self,
+            _group: u64,
+            is_leader: bool,
+            entries: Vec<raft::prelude::Entry>,
+        ) -> Result<Duration> {
+            if !is_leader {
+                return Ok(Duration::ZERO);
+            }
+            for entry in entries {
+                tokio::time::sleep(self.delay).await;
+                let _ = self.tx.send(entry);
+            }
+            Ok(Duration::ZERO)
+        }
+
+        async fn raft_applied_index(&self) -> Result<u64> {
+            Ok(0)
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn test_slow_apply_consumer_backpressures_without_deadlock() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+        raft_log_store.add_group(2).await.unwrap();
+        raft_log_store.add_group(3).await.unwrap();
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10), (2, 10), (3, 10)]))
+            .await
+            .unwrap();
+
+        // Unique node id so this test's time series don't mix with other tests' observations of
+        // the same globally registered histograms.
+        const NODE: u64 = 98;
+        // `Gear`'s command channel can only ever hold one pending `Apply` at a time, forcing
+        // `RaftWorker` to wait on `gear_command_tx.send()` whenever `Gear` is still busy.
+        const GEAR_COMMAND_BOUND: usize = 1;
+        const SLOW_DELAY: Duration = Duration::from_millis(20);
+
+        macro_rules! worker {
+            ($id:expr) => {{
+                let (proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+                let (_control_tx, control_rx) = mpsc::unbounded_channel();
+                let (tx, apply_rx) = mpsc::unbounded_channel();
+                let fsm = SlowFsm {
+                    delay: SLOW_DELAY,
+                    tx,
+                };
+                let group_raft_log_store = RaftGroupLogStore::new($id, raft_log_store.clone());
+                let gear_command_tx = spawn_gear(
+                    NODE,
+                    100,
+                    $id,
+                    group_raft_log_store.clone(),
+                    fsm,
+                    GEAR_COMMAND_BOUND,
+                );
+                let options = RaftWorkerOptions {
+                    group: 100,
+                    node: NODE,
+                    raft_node: $id,
+                    raft_start_mode: RaftStartMode::Initialize {
+                        peers: vec![1, 2, 3],
+                    },
+                    raft_log_store: group_raft_log_store,
+                    raft_logger: raft_logger.clone(),
+                    raft_network: raft_network.clone(),
+                    clock: Arc::new(RealClock),
+                    proposal_rx,
+                    control_rx,
+                    gear_command_tx,
+                    snapshot_policy: SnapshotPolicy::default(),
+                    max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+                    max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+                    min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+                    check_quorum: DEFAULT_CHECK_QUORUM,
+                    pre_vote: DEFAULT_PRE_VOTE,
+                    tick_jitter: DEFAULT_TICK_JITTER,
+                    metrics_enabled: true,
+                    metrics_cardinality_aggregated: false,
+                };
+                let mut worker = RaftWorker::build(options).await.unwrap();
+                tokio::spawn(async move {
+                    let _ = worker.run().await;
+                });
+                (proposal_tx, apply_rx)
+            }};
+        }
+
         let (proposal_tx_1, mut apply_rx_1) = worker!(1);
         let (_proposal_tx_2, mut apply_rx_2) = worker!(2);
         let (_proposal_tx_3, mut apply_rx_3) = worker!(3);
 
         tokio::time::sleep(Duration::from_secs(10)).await;
 
-        let data = vec![b'd'; 16];
-        let context = vec![b'c'; 16];
+        const PROPOSALS: usize = 20;
+
+        // A deliberately tight gear channel plus a slow-but-not-stuck consumer should only ever
+        // throttle proposing; it must never deadlock the cluster outright.
+        tokio::time::timeout(Duration::from_secs(30), async {
+            for i in 0..PROPOSALS {
+                proposal_tx_1
+                    .send(Proposal {
+                        data: format!("value-{:04}", i).into_bytes(),
+                        context: vec![],
+                    })
+                    .unwrap();
+            }
 
-        proposal_tx_1
-            .send(Proposal {
-                data: data.clone(),
-                context: context.clone(),
-            })
+            let mut last_seen = None;
+            while last_seen != Some(PROPOSALS - 1) {
+                let entry = tokio::select! {
+                    entry = apply_rx_1.recv() => entry,
+                    entry = apply_rx_2.recv() => entry,
+                    entry = apply_rx_3.recv() => entry,
+                };
+                let entry = entry.unwrap();
+                if entry.entry_type() != raft::prelude::EntryType::EntryNormal
+                    || entry.data.is_empty()
+                {
+                    continue;
+                }
+                let data = String::from_utf8(entry.data.to_vec()).unwrap();
+                let i: usize = data.strip_prefix("value-").unwrap().parse().unwrap();
+                last_seen = Some(i);
+            }
+        })
+        .await
+        .expect("proposals never completed: slow apply consumer deadlocked the cluster");
+    }
+
+    #[test(tokio::test)]
+    async fn test_apply_log_entries_coalesces_adjacent_ranges() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+        raft_log_store.add_group(1).await.unwrap();
+
+        let raft_network = MockRaftNetwork::default();
+        raft_network
+            .register(100, BTreeMap::from_iter([(1, 10)]))
+            .await
             .unwrap();
 
-        loop {
-            let entry = tokio::select! {
-                entry = apply_rx_1.recv() => entry,
-                entry = apply_rx_2.recv() => entry,
-                entry = apply_rx_3.recv() => entry,
-            };
-            let entry = entry.unwrap();
-            if entry.entry_type() != raft::prelude::EntryType::EntryNormal || entry.data.is_empty()
-            {
-                continue;
+        let group_raft_log_store = RaftGroupLogStore::new(1, raft_log_store.clone());
+        // A raw channel, not `spawn_gear`: this test only cares about what `RaftWorker` sends, so
+        // nothing needs to drain it.
+        let (gear_command_tx, mut gear_command_rx) = mpsc::channel(4);
+        let (_proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (_control_tx, control_rx) = mpsc::unbounded_channel();
+        let options = RaftWorkerOptions {
+            group: 100,
+            node: 10,
+            raft_node: 1,
+            raft_start_mode: RaftStartMode::Initialize { peers: vec![1] },
+            raft_log_store: group_raft_log_store,
+            raft_logger,
+            raft_network,
+            clock: Arc::new(RealClock),
+            proposal_rx,
+            control_rx,
+            gear_command_tx,
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: false,
+        };
+        let mut worker = RaftWorker::build(options).await.unwrap();
+
+        let entries = |range: std::ops::Range<u64>| {
+            range
+                .map(|index| raft::prelude::Entry {
+                    index,
+                    ..Default::default()
+                })
+                .collect_vec()
+        };
+
+        // Mirrors `handle_ready`'s steps 3 and 9: two adjacent ranges for the same group, staged
+        // by two separate `apply_log_entries` calls before either is sent.
+        worker.apply_log_entries(entries(5..8)).await.unwrap();
+        assert!(
+            gear_command_rx.try_recv().is_err(),
+            "an adjacent range should be coalesced, not sent immediately"
+        );
+        worker.apply_log_entries(entries(8..10)).await.unwrap();
+        assert!(gear_command_rx.try_recv().is_err());
+
+        worker.flush_pending_apply().await.unwrap();
+        match gear_command_rx.try_recv().unwrap() {
+            GearCommand::Apply { group, range, .. } => {
+                assert_eq!(group, 100);
+                assert_eq!(range, 5..10);
             }
-            assert_matches!(entry, raft::prelude::Entry {
-                data: edata,
-                context: econtext,
-                ..
-            } => {
-                assert_eq!(edata, data);
-                assert_eq!(econtext, context);
-            });
-            break;
+            other => panic!("expected a single coalesced Apply, got {:?}", other),
         }
+        assert!(
+            gear_command_rx.try_recv().is_err(),
+            "the two staged ranges should have been merged into one Apply"
+        );
     }
 
-    fn build_raft_logger() -> slog::Logger {
+    pub(crate) fn build_raft_logger() -> slog::Logger {
         slog::Logger::root(TracingSlogDrain, slog::o!("namespace" => "raft"))
     }
 
-    async fn build_raft_log_store(path: &str) -> RaftLogStore {
+    pub(crate) async fn build_raft_log_store(path: &str) -> RaftLogStore {
         let options = RaftLogStoreOptions {
             node: 0,
             log_dir_path: path.to_string(),
             log_file_capacity: 64 << 20,
             block_cache_capacity: 64 << 20,
             persist: Persist::Sync,
+            strict_repair: false,
+            compression_threshold: runkv_storage::raft_log_store::DEFAULT_COMPRESSION_THRESHOLD,
         };
         RaftLogStore::open(options).await.unwrap()
     }
 
-    async fn build_raft_worker<RN: RaftNetwork>(
+    pub(crate) async fn build_raft_worker<RN: RaftNetwork>(
         group: u64,
         node: u64,
         raft_node: u64,
@@ -681,12 +3885,24 @@ mod tests {
         raft_log_store: RaftGroupLogStore,
         raft_logger: slog::Logger,
         raft_network: RN,
+        clock: ClockRef,
     ) -> (
         mpsc::UnboundedSender<Proposal>,
+        mpsc::UnboundedSender<RaftWorkerControl>,
+        JoinHandle<()>,
         mpsc::UnboundedReceiver<raft::prelude::Entry>,
     ) {
         let (proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+        let (control_tx, control_rx) = mpsc::unbounded_channel();
         let (fsm, apply_rx) = MockFsm::new(true);
+        let gear_command_tx = spawn_gear(
+            node,
+            group,
+            raft_node,
+            raft_log_store.clone(),
+            fsm,
+            crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+        );
         let options = RaftWorkerOptions {
             group,
             node,
@@ -695,13 +3911,27 @@ mod tests {
             raft_log_store,
             raft_logger,
             raft_network,
+            clock,
 
             proposal_rx,
-
-            fsm,
+            control_rx,
+
+            gear_command_tx,
+
+            snapshot_policy: SnapshotPolicy::default(),
+            max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+            max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+            min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+            check_quorum: DEFAULT_CHECK_QUORUM,
+            pre_vote: DEFAULT_PRE_VOTE,
+            tick_jitter: DEFAULT_TICK_JITTER,
+            metrics_enabled: true,
+            metrics_cardinality_aggregated: false,
         };
         let mut worker = RaftWorker::build(options).await.unwrap();
-        let _handle = tokio::spawn(async move { worker.run().await });
-        (proposal_tx, apply_rx)
+        let handle = tokio::spawn(async move {
+            let _ = worker.run().await;
+        });
+        (proposal_tx, control_tx, handle, apply_rx)
     }
 }
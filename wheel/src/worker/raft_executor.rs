@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use runkv_common::Worker;
+
+use crate::components::raft_network::RaftNetwork;
+use crate::error::Result;
+use crate::worker::raft::RaftWorker;
+
+/// Default for [`MultiplexedRaftExecutor::new`]'s `group_budget`: a group that's still finding
+/// work after this many [`RaftWorker::poll_once`] turns in a row gets interrupted so its
+/// neighbours on the same task get a turn too. Each turn itself drains up to
+/// [`crate::worker::raft::RaftWorker::drain_and_process`]'s own batch of messages.
+pub const DEFAULT_GROUP_BUDGET: usize = 16;
+
+/// How long to sleep after a full round finds no group with any work, so an executor with
+/// nothing to do doesn't spin the host task at 100% CPU.
+const IDLE_ROUND_SLEEP: Duration = Duration::from_millis(1);
+
+/// Runs a fixed set of [`RaftWorker`]s on a single task instead of [`RaftManager`]'s default of
+/// one task per group, round-robining [`RaftWorker::poll_once`] across them with a per-group
+/// budget so one busy group can't monopolize the task and starve its neighbours.
+///
+/// This is an opt-in alternative for deployments that would rather bound the number of OS
+/// threads/tasks a wheel spends on raft groups than rely on tokio's own cooperative scheduling
+/// across one task per group. [`RaftManager`] does not wire this in itself today — callers that
+/// want it construct a [`MultiplexedRaftExecutor`] directly and hand it [`RaftWorker`]s built the
+/// same way [`RaftManager`] builds its own, then run it as a [`Worker`] like any other.
+///
+/// [`RaftManager`]: crate::components::raft_manager::RaftManager
+pub struct MultiplexedRaftExecutor<RN: RaftNetwork> {
+    workers: Vec<RaftWorker<RN>>,
+    group_budget: usize,
+}
+
+impl<RN: RaftNetwork> MultiplexedRaftExecutor<RN> {
+    pub fn new(group_budget: usize) -> Self {
+        Self {
+            workers: Vec::new(),
+            group_budget,
+        }
+    }
+
+    /// Adds a group to this executor's round-robin rotation. The group stays on this executor's
+    /// task for the rest of its lifetime; there is no mechanism yet to move a group back onto its
+    /// own task or to another executor.
+    pub fn add_worker(&mut self, worker: RaftWorker<RN>) {
+        self.workers.push(worker);
+    }
+
+    /// Round-robins one pass over every registered group, giving each up to `group_budget` back
+    /// to back turns via [`RaftWorker::poll_once`] as long as it keeps reporting it did work, and
+    /// yielding the task between groups so a long-idle neighbour isn't starved of scheduling by a
+    /// chatty one. Returns whether any group did any work this round.
+    async fn run_round(&mut self) -> Result<bool> {
+        let mut any_work = false;
+        for worker in &mut self.workers {
+            for _ in 0..self.group_budget {
+                if !worker.poll_once().await? {
+                    break;
+                }
+                any_work = true;
+            }
+            tokio::task::yield_now().await;
+        }
+        Ok(any_work)
+    }
+}
+
+#[async_trait]
+impl<RN: RaftNetwork> Worker for MultiplexedRaftExecutor<RN> {
+    async fn run(&mut self) -> anyhow::Result<()> {
+        loop {
+            if !self.run_round().await? {
+                tokio::time::sleep(IDLE_ROUND_SLEEP).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use test_log::test;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::components::clock::RealClock;
+    use crate::components::fsm::tests::MockFsm;
+    use crate::components::raft_log_store::RaftGroupLogStore;
+    use crate::components::raft_network::tests::MockRaftNetwork;
+    use crate::worker::raft::tests::{build_raft_log_store, build_raft_logger, spawn_gear};
+    use crate::worker::raft::{
+        Proposal, RaftStartMode, RaftWorkerOptions, SnapshotPolicy, DEFAULT_CHECK_QUORUM,
+        DEFAULT_MAX_INFLIGHT_MSGS, DEFAULT_MAX_SIZE_PER_MSG, DEFAULT_MIN_LOOP_DURATION,
+        DEFAULT_PRE_VOTE, DEFAULT_TICK_JITTER,
+    };
+
+    #[test(tokio::test)]
+    async fn test_cold_groups_progress_alongside_one_hot_group() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().to_str().unwrap();
+        let raft_logger = build_raft_logger();
+        let raft_log_store = build_raft_log_store(path).await;
+
+        // One hot group (1) that a background task keeps flooding with proposals, and several
+        // cold groups (2, 3, 4) that each only need to commit a single proposal.
+        const GROUPS: [u64; 4] = [1, 2, 3, 4];
+        for group in GROUPS {
+            raft_log_store.add_group(group).await.unwrap();
+        }
+
+        let mut executor = MultiplexedRaftExecutor::<MockRaftNetwork>::new(DEFAULT_GROUP_BUDGET);
+        let mut proposal_txs = Vec::new();
+        let mut apply_rxs = Vec::new();
+
+        for group in GROUPS {
+            let raft_network = MockRaftNetwork::default();
+            raft_network
+                .register(group, BTreeMap::from_iter([(group, group)]))
+                .await
+                .unwrap();
+
+            let (proposal_tx, proposal_rx) = mpsc::unbounded_channel();
+            let (_control_tx, control_rx) = mpsc::unbounded_channel();
+            let (fsm, apply_rx) = MockFsm::new(true);
+            let group_raft_log_store = RaftGroupLogStore::new(group, raft_log_store.clone());
+            let gear_command_tx = spawn_gear(
+                group,
+                group,
+                group,
+                group_raft_log_store.clone(),
+                fsm,
+                crate::worker::gear::DEFAULT_APPLY_CHANNEL_BOUND,
+            );
+            let options = RaftWorkerOptions {
+                group,
+                node: group,
+                raft_node: group,
+                raft_start_mode: RaftStartMode::Initialize { peers: vec![group] },
+                raft_log_store: group_raft_log_store,
+                raft_logger: raft_logger.clone(),
+                raft_network,
+                clock: Arc::new(RealClock),
+                proposal_rx,
+                control_rx,
+                gear_command_tx,
+                snapshot_policy: SnapshotPolicy::default(),
+                max_size_per_msg: DEFAULT_MAX_SIZE_PER_MSG,
+                max_inflight_msgs: DEFAULT_MAX_INFLIGHT_MSGS,
+                min_loop_duration: DEFAULT_MIN_LOOP_DURATION,
+                check_quorum: DEFAULT_CHECK_QUORUM,
+                pre_vote: DEFAULT_PRE_VOTE,
+                tick_jitter: DEFAULT_TICK_JITTER,
+                metrics_enabled: true,
+                metrics_cardinality_aggregated: false,
+            };
+            let worker = RaftWorker::build(options).await.unwrap();
+            executor.add_worker(worker);
+
+            proposal_txs.push(proposal_tx);
+            apply_rxs.push(apply_rx);
+        }
+
+        tokio::spawn(async move {
+            let _ = executor.run().await;
+        });
+
+        // Flood the hot group (index 0, group 1) with proposals for the duration of the test.
+        let hot_proposal_tx = proposal_txs[0].clone();
+        let hot_flood = tokio::spawn(async move {
+            loop {
+                if hot_proposal_tx
+                    .send(Proposal {
+                        data: vec![b'h'; 16],
+                        context: vec![],
+                    })
+                    .is_err()
+                {
+                    return;
+                }
+                // Yield rather than spin: this test runs on a current-thread runtime, so a loop
+                // with no await point at all would starve the executor task of CPU entirely
+                // instead of merely keeping the hot group busy.
+                tokio::task::yield_now().await;
+            }
+        });
+
+        // Single-member groups already won their election in `RaftWorker::build`; give everyone a
+        // moment to settle before proposing to the cold groups.
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        for (i, group) in GROUPS.into_iter().enumerate().skip(1) {
+            proposal_txs[i]
+                .send(Proposal {
+                    data: vec![group as u8; 16],
+                    context: vec![],
+                })
+                .unwrap();
+        }
+
+        for (i, group) in GROUPS.into_iter().enumerate().skip(1) {
+            let entry = tokio::time::timeout(Duration::from_secs(10), async {
+                loop {
+                    let entry = apply_rxs[i].recv().await.unwrap();
+                    if entry.entry_type() == raft::prelude::EntryType::EntryNormal
+                        && !entry.data.is_empty()
+                    {
+                        return entry;
+                    }
+                }
+            })
+            .await
+            .unwrap_or_else(|_| {
+                panic!(
+                    "cold group {} never made progress while the hot group kept proposing",
+                    group
+                )
+            });
+            assert_eq!(entry.data, vec![group as u8; 16]);
+        }
+
+        hot_flood.abort();
+    }
+}
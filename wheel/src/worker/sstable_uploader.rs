@@ -87,6 +87,10 @@ impl SstableUploader {
     }
 
     async fn run_inner(&mut self) -> Result<()> {
+        // Seal a long-idle active memtable even though it isn't full, so its writes eventually
+        // reach this same upload path instead of sitting in memory indefinitely.
+        self.lsm_tree.maybe_rotate_stale_memtable();
+
         if let Some(memtable) = self.lsm_tree.get_oldest_immutable_memtable() {
             let mut sst_infos =
                 Vec::with_capacity(memtable.mem_size() / self.options.sstable_capacity + 1);
@@ -97,6 +101,13 @@ impl SstableUploader {
                     restart_interval: self.options.restart_interval,
                     bloom_false_positive: self.options.bloom_false_positive,
                     compression_algorithm: self.options.compression_algorithm,
+                    dictionary: vec![],
+                    compression_level: 0,
+                    // Sstables flushed straight out of the memtable always land in L0.
+                    level: 0,
+                    parallel_bloom_build: false,
+                    value_separation_threshold: 0,
+                    blob_id: 0,
                 };
                 let mut sstable_builder = None;
                 let skiplist = memtable.unwrap();
@@ -158,7 +169,14 @@ impl SstableUploader {
             .put(&sst, data, CachePolicy::Fill)
             .await?;
         debug!("sst {} uploaded", id);
-        Ok(SstableInfo { id, data_size })
+        Ok(SstableInfo {
+            id,
+            data_size,
+            min_user_key: user_key(sst.first_key()).to_vec(),
+            max_user_key: user_key(sst.last_key()).to_vec(),
+            created_at: sst.created_at(),
+            level: sst.level(),
+        })
     }
 
     async fn notify_update_version(&mut self, sst_infos: Vec<SstableInfo>) -> Result<()> {